@@ -0,0 +1,132 @@
+use std::error::Error;
+use std::io::{BufRead, Write};
+
+use crate::parse::{self, CalcError};
+
+pub const USAGE: &str = "\
+usage: operations                 (a prompt, one expression per line)
+       operations <expression>    (prints its value, e.g. operations '2 ^ (3 + 1) % 5')";
+
+const PROMPT: &str = "> ";
+
+const HELP: &str = "\
+whole numbers, ( ), + - * / % and ^ (or **), from the loosest to the tightest
+/ rounds towards zero, % takes the sign of the left side, ^ groups from the right
+quit or exit to leave";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    // None for the prompt
+    pub expression: Option<String>,
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, &'static str> {
+        // skip the program name, the rest is one expression however the shell split it
+        let words: Vec<&str> = args.iter().skip(1).map(|arg| arg.as_str()).collect();
+        if words.is_empty() {
+            return Ok(Config { expression: None });
+        }
+        let expression = words.join(" ");
+        if expression.trim().is_empty() {
+            return Err("the expression is empty");
+        }
+        Ok(Config { expression: Some(expression) })
+    }
+}
+
+// The result of a line, or the error with a ^ under where it went wrong when the prompt
+// is in front of the line on screen
+fn answer<W: Write>(line: &str, prompt: &str, mut output: W) -> Result<(), Box<dyn Error>> {
+    match parse::evaluate(line) {
+        Ok(n) => writeln!(output, "{}", n)?,
+        Err(e) => {
+            if let CalcError::Parse(e) = &e {
+                if let Some(column) = e.column() {
+                    writeln!(output, "{}^", " ".repeat(prompt.chars().count() + column - 1))?;
+                }
+            }
+            writeln!(output, "error: {}", e)?;
+        }
+    }
+    Ok(())
+}
+
+// Reads expressions until quit or the end of the input. Errors are printed and the
+// prompt goes on.
+pub fn repl<R: BufRead, W: Write>(input: R, mut output: W) -> Result<(), Box<dyn Error>> {
+    write!(output, "{}", PROMPT)?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        match line.trim() {
+            "" => {}
+            "quit" | "exit" => return Ok(()),
+            "help" => writeln!(output, "{}", HELP)?,
+            _ => answer(&line, PROMPT, &mut output)?,
+        }
+        write!(output, "{}", PROMPT)?;
+        output.flush()?;
+    }
+    // the end of the input came right after the prompt
+    writeln!(output)?;
+    Ok(())
+}
+
+pub fn run<R: BufRead, W: Write>(config: Config, input: R, mut output: W) -> Result<(), Box<dyn Error>> {
+    match config.expression {
+        Some(expression) => {
+            let n = parse::evaluate(&expression)?;
+            writeln!(output, "{}", n)?;
+            Ok(())
+        }
+        None => repl(input, output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("operations").chain(line.split_whitespace()).map(String::from).collect()
+    }
+
+    fn session(input: &str) -> String {
+        let mut output = Vec::new();
+        repl(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn parses_arguments() {
+        assert_eq!(Config::new(&args("")), Ok(Config { expression: None }));
+        assert_eq!(Config::new(&args("1 + 2")), Ok(Config { expression: Some(String::from("1 + 2")) }));
+        assert_eq!(Config::new(&[String::from("operations"), String::from(" ")]), Err("the expression is empty"));
+    }
+
+    #[test]
+    fn evaluates_the_arguments() {
+        let mut output = Vec::new();
+        run(Config::new(&args("2 ^ (3 + 1) % 5")).unwrap(), io::empty(), &mut output).unwrap();
+        assert_eq!(output, b"1\n");
+
+        let error = run(Config::new(&args("1 / 0")).unwrap(), io::empty(), io::sink()).unwrap_err();
+        assert_eq!(error.to_string(), "1 / 0 divides by zero");
+    }
+
+    #[test]
+    fn answers_line_by_line() {
+        assert_eq!(session("1 + 2\n\n2 ^ 10\n"), "> 3\n> > 1024\n> \n");
+        assert_eq!(session("7 % 0\nquit\n1 + 1\n"), "> error: 7 % 0 divides by zero\n> ");
+        assert!(session("help\nexit").starts_with("> whole numbers"));
+    }
+
+    #[test]
+    fn points_at_the_mistake() {
+        // the prompt and the line typed after it are above the ^
+        assert_eq!(session("1 + * 2\n"), ">       ^\nerror: column 5: expected a number or '(', found '*'\n> \n");
+        assert_eq!(session("(1 + 2\n"), ">         ^\nerror: column 7: expected ')'\n> \n");
+    }
+}
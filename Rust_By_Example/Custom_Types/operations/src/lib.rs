@@ -0,0 +1,4 @@
+pub mod cli;
+pub mod ops;
+pub mod parse;
+pub mod token;
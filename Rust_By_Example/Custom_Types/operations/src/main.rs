@@ -0,0 +1,25 @@
+// The Operations of Enums.rs grown into a calculator:
+//   operations                    a prompt, one expression per line
+//   operations '2 ^ 10 - 1'       just the value
+use std::env;
+use std::io;
+use std::process;
+
+use operations::cli::{self, Config};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let config = Config::new(&args).unwrap_or_else(|err| {
+        eprintln!("Problem parsing arguments: {}", err);
+        eprintln!("{}", cli::USAGE);
+        process::exit(1);
+    });
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(e) = cli::run(config, stdin.lock(), stdout.lock()) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,157 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+// The enum of the type alias example in Custom_Types/Enums.rs, with more to do with numbers.
+// `run` can't panic any more: what doesn't fit an i64 or can't be done is an EvalError.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VeryVerboseEnumOfThingsToDoWithNumbers {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Power,
+}
+
+// Creates a type alias
+pub type Operations = VeryVerboseEnumOfThingsToDoWithNumbers;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    // the result doesn't fit an i64
+    Overflow { op: Operations, x: i64, y: i64 },
+    // -x where x is i64::MIN
+    NegateOverflow(i64),
+    // x / 0 and x % 0
+    DivideByZero { op: Operations, x: i64 },
+    // 2 ^ -1 is not a whole number
+    NegativeExponent { x: i64, y: i64 },
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::Overflow { op, x, y } => write!(f, "{} {} {} overflows", x, op, y),
+            EvalError::NegateOverflow(x) => write!(f, "-({}) overflows", x),
+            EvalError::DivideByZero { op, x } => write!(f, "{} {} 0 divides by zero", x, op),
+            EvalError::NegativeExponent { x, y } => write!(f, "{} ^ {} has a negative exponent", x, y),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl fmt::Display for Operations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+// Most common place is in `impl` blocks using the `Self` alias
+impl VeryVerboseEnumOfThingsToDoWithNumbers {
+    pub const ALL: [Operations; 6] =
+        [Self::Add, Self::Subtract, Self::Multiply, Self::Divide, Self::Modulo, Self::Power];
+
+    // Division rounds towards zero and the remainder has the sign of x, like / and % on
+    // integers in Rust.
+    pub fn run(&self, x: i64, y: i64) -> Result<i64, EvalError> {
+        let overflow = || EvalError::Overflow { op: *self, x, y };
+        match self {
+            Self::Add => x.checked_add(y).ok_or_else(overflow),
+            Self::Subtract => x.checked_sub(y).ok_or_else(overflow),
+            Self::Multiply => x.checked_mul(y).ok_or_else(overflow),
+            Self::Divide | Self::Modulo if y == 0 => Err(EvalError::DivideByZero { op: *self, x }),
+            // i64::MIN / -1 is the only other way these go wrong
+            Self::Divide => x.checked_div(y).ok_or_else(overflow),
+            Self::Modulo => x.checked_rem(y).ok_or_else(overflow),
+            Self::Power => match (x, y) {
+                (_, y) if y < 0 => Err(EvalError::NegativeExponent { x, y }),
+                // these don't grow however big the exponent is
+                (0, 0) => Ok(1),
+                (0, _) | (1, _) => Ok(x),
+                (-1, y) => Ok(if y % 2 == 0 { 1 } else { -1 }),
+                (x, y) => u32::try_from(y).ok().and_then(|y| x.checked_pow(y)).ok_or_else(overflow),
+            },
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+            Self::Modulo => "%",
+            Self::Power => "^",
+        }
+    }
+
+    // How tightly it binds, higher goes first: 1 + 2 * 3 ^ 2 is 1 + (2 * (3 ^ 2))
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Add | Self::Subtract => 1,
+            Self::Multiply | Self::Divide | Self::Modulo => 2,
+            Self::Power => 3,
+        }
+    }
+
+    // 2 ^ 3 ^ 2 is 2 ^ (3 ^ 2), the others go left to right: 8 - 4 - 2 is (8 - 4) - 2
+    pub fn is_right_associative(&self) -> bool {
+        *self == Self::Power
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_like_the_example() {
+        // We can refer to each variant via its alias, not its long and inconvenient name.
+        assert_eq!(Operations::Add.run(2, 3), Ok(5));
+        assert_eq!(Operations::Subtract.run(2, 3), Ok(-1));
+        assert_eq!(Operations::Multiply.run(-4, 3), Ok(-12));
+        assert_eq!(Operations::Divide.run(-7, 2), Ok(-3));
+        assert_eq!(Operations::Modulo.run(-7, 2), Ok(-1));
+        assert_eq!(Operations::Power.run(-3, 3), Ok(-27));
+        assert_eq!(Operations::Power.run(5, 0), Ok(1));
+    }
+
+    #[test]
+    fn reports_instead_of_panicking() {
+        assert_eq!(
+            Operations::Add.run(i64::MAX, 1),
+            Err(EvalError::Overflow { op: Operations::Add, x: i64::MAX, y: 1 })
+        );
+        assert!(Operations::Subtract.run(i64::MIN, 1).is_err());
+        assert!(Operations::Multiply.run(i64::MAX, 2).is_err());
+        assert!(Operations::Divide.run(i64::MIN, -1).is_err());
+        assert!(Operations::Modulo.run(i64::MIN, -1).is_err());
+        assert!(Operations::Power.run(2, 63).is_err());
+        assert!(Operations::Power.run(2, 1 << 40).is_err());
+        assert_eq!(Operations::Divide.run(1, 0), Err(EvalError::DivideByZero { op: Operations::Divide, x: 1 }));
+        assert_eq!(Operations::Modulo.run(1, 0), Err(EvalError::DivideByZero { op: Operations::Modulo, x: 1 }));
+        assert_eq!(Operations::Power.run(2, -1), Err(EvalError::NegativeExponent { x: 2, y: -1 }));
+
+        assert_eq!(EvalError::Overflow { op: Operations::Add, x: i64::MAX, y: 1 }.to_string(), "9223372036854775807 + 1 overflows");
+        assert_eq!(EvalError::DivideByZero { op: Operations::Modulo, x: 1 }.to_string(), "1 % 0 divides by zero");
+    }
+
+    #[test]
+    fn huge_exponents_of_small_bases() {
+        assert_eq!(Operations::Power.run(1, i64::MAX), Ok(1));
+        assert_eq!(Operations::Power.run(0, i64::MAX), Ok(0));
+        assert_eq!(Operations::Power.run(-1, i64::MAX), Ok(-1));
+        assert_eq!(Operations::Power.run(-1, 1 << 40), Ok(1));
+        assert_eq!(Operations::Power.run(0, 0), Ok(1));
+    }
+
+    #[test]
+    fn binds_by_precedence() {
+        let tightest: Vec<Operations> = Operations::ALL.iter().copied().filter(|op| op.precedence() == 3).collect();
+        assert_eq!(tightest, [Operations::Power]);
+        assert!(Operations::Multiply.precedence() > Operations::Subtract.precedence());
+        assert_eq!(Operations::Modulo.precedence(), Operations::Divide.precedence());
+        assert!(Operations::Power.is_right_associative() && !Operations::Subtract.is_right_associative());
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+
+use crate::ops::{EvalError, Operations};
+use crate::token::{self, Token, TokenError, TokenKind};
+
+// Longer lines are refused, so that neither parsing nor evaluating the tree can run out
+// of stack however the line is nested
+pub const MAX_TOKENS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Number(i64),
+    Negate(Box<Expr>),
+    Binary { op: Operations, lhs: Box<Expr>, rhs: Box<Expr> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Token(TokenError),
+    Unexpected { found: TokenKind, column: usize, expected: &'static str },
+    // the line ended too soon, column is just past its end
+    UnexpectedEnd { column: usize, expected: &'static str },
+    TooLong { tokens: usize },
+}
+
+impl ParseError {
+    // Where to point at in the line, None when it's the whole line
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            ParseError::Token(e) => Some(e.column()),
+            ParseError::Unexpected { column, .. } | ParseError::UnexpectedEnd { column, .. } => Some(*column),
+            ParseError::TooLong { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Token(e) => write!(f, "{}", e),
+            ParseError::Unexpected { found, column, expected } => {
+                write!(f, "column {}: expected {}, found '{}'", column, expected, found)
+            }
+            ParseError::UnexpectedEnd { column, expected } => write!(f, "column {}: expected {}", column, expected),
+            ParseError::TooLong { tokens } => write!(f, "{} tokens is too long, at most {} please", tokens, MAX_TOKENS),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<TokenError> for ParseError {
+    fn from(e: TokenError) -> ParseError {
+        ParseError::Token(e)
+    }
+}
+
+// Precedence climbing over the tokens. Each call to `expression` takes the operators at
+// least as tight as `min_precedence`; what is to the right of an operator is parsed with
+// a higher minimum, unless it is right associative.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    // the column past the end of the line
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn expression(&mut self, min_precedence: u8) -> Result<Expr, ParseError> {
+        let mut lhs = self.unary()?;
+        while let Some(Token { kind: TokenKind::Op(op), .. }) = self.peek() {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.next();
+            let next_precedence = if op.is_right_associative() { op.precedence() } else { op.precedence() + 1 };
+            let rhs = self.expression(next_precedence)?;
+            lhs = Expr::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) };
+        }
+        Ok(lhs)
+    }
+
+    // A leading - or + binds looser than ^ but tighter than the rest: -2 ^ 2 is -(2 ^ 2)
+    // and -2 * 3 is (-2) * 3
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        match self.peek().map(|token| token.kind) {
+            Some(TokenKind::Op(Operations::Subtract)) => {
+                self.next();
+                Ok(Expr::Negate(Box::new(self.expression(Operations::Power.precedence())?)))
+            }
+            Some(TokenKind::Op(Operations::Add)) => {
+                self.next();
+                self.expression(Operations::Power.precedence())
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        const EXPECTED: &str = "a number or '('";
+        match self.next() {
+            Some(Token { kind: TokenKind::Number(n), .. }) => Ok(Expr::Number(n)),
+            Some(Token { kind: TokenKind::LeftParen, .. }) => {
+                let inside = self.expression(1)?;
+                match self.next() {
+                    Some(Token { kind: TokenKind::RightParen, .. }) => Ok(inside),
+                    Some(Token { kind, column }) => Err(ParseError::Unexpected { found: kind, column, expected: "')'" }),
+                    None => Err(ParseError::UnexpectedEnd { column: self.end, expected: "')'" }),
+                }
+            }
+            Some(Token { kind, column }) => Err(ParseError::Unexpected { found: kind, column, expected: EXPECTED }),
+            None => Err(ParseError::UnexpectedEnd { column: self.end, expected: EXPECTED }),
+        }
+    }
+}
+
+pub fn parse(line: &str) -> Result<Expr, ParseError> {
+    let tokens = token::tokenize(line)?;
+    if tokens.len() > MAX_TOKENS {
+        return Err(ParseError::TooLong { tokens: tokens.len() });
+    }
+    let mut parser = Parser { tokens, position: 0, end: line.chars().count() + 1 };
+    let expr = parser.expression(1)?;
+    match parser.next() {
+        // 1 2 or 1 + 2)
+        Some(Token { kind, column }) => Err(ParseError::Unexpected { found: kind, column, expected: "an operator" }),
+        None => Ok(expr),
+    }
+}
+
+impl Expr {
+    pub fn eval(&self) -> Result<i64, EvalError> {
+        match self {
+            Expr::Number(n) => Ok(*n),
+            Expr::Negate(expr) => {
+                let n = expr.eval()?;
+                n.checked_neg().ok_or(EvalError::NegateOverflow(n))
+            }
+            Expr::Binary { op, lhs, rhs } => op.run(lhs.eval()?, rhs.eval()?),
+        }
+    }
+}
+
+// With every operation in parentheses, to show how the line was read
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Negate(expr) => write!(f, "-{}", expr),
+            Expr::Binary { op, lhs, rhs } => write!(f, "({} {} {})", lhs, op, rhs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalcError {
+    Parse(ParseError),
+    Eval(EvalError),
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalcError::Parse(e) => write!(f, "{}", e),
+            CalcError::Eval(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CalcError {}
+
+// Parses and evaluates a line in one go
+pub fn evaluate(line: &str) -> Result<i64, CalcError> {
+    parse(line).map_err(CalcError::Parse)?.eval().map_err(CalcError::Eval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_as(line: &str) -> String {
+        parse(line).unwrap().to_string()
+    }
+
+    #[test]
+    fn follows_precedence() {
+        assert_eq!(read_as("1 + 2 * 3"), "(1 + (2 * 3))");
+        assert_eq!(read_as("(1 + 2) * 3"), "((1 + 2) * 3)");
+        assert_eq!(read_as("8 - 4 - 2"), "((8 - 4) - 2)");
+        assert_eq!(read_as("8 / 4 % 3 * 2"), "(((8 / 4) % 3) * 2)");
+        assert_eq!(read_as("2 ^ 3 ^ 2"), "(2 ^ (3 ^ 2))");
+        assert_eq!(read_as("1 + 2 * 3 ^ 2 - 4"), "((1 + (2 * (3 ^ 2))) - 4)");
+        assert_eq!(read_as("((7))"), "7");
+    }
+
+    #[test]
+    fn negates() {
+        assert_eq!(read_as("-2 ^ 2"), "-(2 ^ 2)");
+        assert_eq!(read_as("-2 * 3"), "(-2 * 3)");
+        assert_eq!(read_as("2 * -3"), "(2 * -3)");
+        assert_eq!(read_as("2 ^ -3"), "(2 ^ -3)");
+        assert_eq!(read_as("--+3"), "--3");
+        assert_eq!(evaluate("-2 ^ 2"), Ok(-4));
+        assert_eq!(evaluate("(-2) ^ 2"), Ok(4));
+        assert_eq!(evaluate("1 - -1"), Ok(2));
+    }
+
+    #[test]
+    fn evaluates() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(7));
+        assert_eq!(evaluate("2 ** 3 ** 2"), Ok(512));
+        assert_eq!(evaluate("(100 - 1) % 7 / 2"), Ok(0));
+        assert_eq!(evaluate("-7 / 2"), Ok(-3));
+        assert_eq!(evaluate("-9223372036854775807 - 1"), Ok(i64::MIN));
+    }
+
+    #[test]
+    fn reports_evaluation_errors() {
+        assert_eq!(
+            evaluate("10 / (5 - 5)"),
+            Err(CalcError::Eval(EvalError::DivideByZero { op: Operations::Divide, x: 10 }))
+        );
+        assert_eq!(
+            evaluate("3 * 2 ^ 62"),
+            Err(CalcError::Eval(EvalError::Overflow { op: Operations::Multiply, x: 3, y: 1 << 62 }))
+        );
+        assert_eq!(
+            evaluate("-(-9223372036854775807 - 1)"),
+            Err(CalcError::Eval(EvalError::NegateOverflow(i64::MIN)))
+        );
+        assert_eq!(evaluate("2 ^ -1").unwrap_err().to_string(), "2 ^ -1 has a negative exponent");
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let error = |line: &str| parse(line).unwrap_err();
+        assert_eq!(error("1 +"), ParseError::UnexpectedEnd { column: 4, expected: "a number or '('" });
+        assert_eq!(error(""), ParseError::UnexpectedEnd { column: 1, expected: "a number or '('" });
+        assert_eq!(error("(1 + 2"), ParseError::UnexpectedEnd { column: 7, expected: "')'" });
+        assert_eq!(
+            error("1 + 2)"),
+            ParseError::Unexpected { found: TokenKind::RightParen, column: 6, expected: "an operator" }
+        );
+        assert_eq!(
+            error("1 2"),
+            ParseError::Unexpected { found: TokenKind::Number(2), column: 3, expected: "an operator" }
+        );
+        assert_eq!(
+            error("* 2"),
+            ParseError::Unexpected { found: TokenKind::Op(Operations::Multiply), column: 1, expected: "a number or '('" }
+        );
+        assert_eq!(error("(1 2)").to_string(), "column 4: expected ')', found '2'");
+        assert_eq!(error("1 $ 2").column(), Some(3));
+    }
+
+    #[test]
+    fn refuses_lines_too_long_to_nest() {
+        let deep = format!("{}1{}", "(".repeat(MAX_TOKENS), ")".repeat(MAX_TOKENS));
+        assert_eq!(parse(&deep), Err(ParseError::TooLong { tokens: 2 * MAX_TOKENS + 1 }));
+        assert_eq!(parse(&deep).unwrap_err().column(), None);
+
+        // as long as allowed, and as deep as it gets
+        let half = MAX_TOKENS / 2 - 1;
+        assert_eq!(evaluate(&format!("{}1{}", "(".repeat(half), ")".repeat(half))), Ok(1));
+        assert_eq!(evaluate(&format!("1{}", " + 1".repeat(half))), Ok(half as i64 + 1));
+        assert_eq!(evaluate(&format!("{}1", "-".repeat(MAX_TOKENS - 1))), Ok(-1));
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+
+use crate::ops::Operations;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Number(i64),
+    // - is an Operations::Subtract here, the parser decides when it negates
+    Op(Operations),
+    LeftParen,
+    RightParen,
+}
+
+// A token and the column it starts at, counted from 1, for the error messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    UnexpectedChar { ch: char, column: usize },
+    // digits that don't fit an i64
+    NumberTooLarge { column: usize },
+}
+
+impl TokenError {
+    pub fn column(&self) -> usize {
+        match self {
+            TokenError::UnexpectedChar { column, .. } | TokenError::NumberTooLarge { column } => *column,
+        }
+    }
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenError::UnexpectedChar { ch, column } => write!(f, "column {}: unexpected '{}'", column, ch),
+            TokenError::NumberTooLarge { column } => write!(f, "column {}: the number is too large", column),
+        }
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Number(n) => write!(f, "{}", n),
+            TokenKind::Op(op) => write!(f, "{}", op),
+            TokenKind::LeftParen => f.write_str("("),
+            TokenKind::RightParen => f.write_str(")"),
+        }
+    }
+}
+
+// Splits a line into numbers, operators and parentheses. Whitespace only separates, and
+// ** is another way to write ^.
+pub fn tokenize(line: &str) -> Result<Vec<Token>, TokenError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().enumerate().peekable();
+
+    while let Some((index, ch)) = chars.next() {
+        let column = index + 1;
+        let kind = match ch {
+            c if c.is_whitespace() => continue,
+            '0'..='9' => {
+                let mut digits = ch.to_string();
+                while let Some(&(_, digit)) = chars.peek() {
+                    // 1_000_000 like in Rust
+                    if digit.is_ascii_digit() || digit == '_' {
+                        digits.push(digit);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let n = digits.replace('_', "").parse().map_err(|_| TokenError::NumberTooLarge { column })?;
+                TokenKind::Number(n)
+            }
+            '+' => TokenKind::Op(Operations::Add),
+            '-' => TokenKind::Op(Operations::Subtract),
+            '*' if chars.peek().map(|&(_, next)| next) == Some('*') => {
+                chars.next();
+                TokenKind::Op(Operations::Power)
+            }
+            '*' => TokenKind::Op(Operations::Multiply),
+            '/' => TokenKind::Op(Operations::Divide),
+            '%' => TokenKind::Op(Operations::Modulo),
+            '^' => TokenKind::Op(Operations::Power),
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            _ => return Err(TokenError::UnexpectedChar { ch, column }),
+        };
+        tokens.push(Token { kind, column });
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(line: &str) -> Vec<TokenKind> {
+        tokenize(line).unwrap().into_iter().map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn splits_a_line() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("12+(3 - -4)*5"),
+            [
+                Number(12),
+                Op(Operations::Add),
+                LeftParen,
+                Number(3),
+                Op(Operations::Subtract),
+                Op(Operations::Subtract),
+                Number(4),
+                RightParen,
+                Op(Operations::Multiply),
+                Number(5)
+            ]
+        );
+        assert_eq!(kinds("2**3 ^ 4 % 1_000"), [
+            Number(2),
+            Op(Operations::Power),
+            Number(3),
+            Op(Operations::Power),
+            Number(4),
+            Op(Operations::Modulo),
+            Number(1000)
+        ]);
+        assert!(kinds(" \t").is_empty());
+    }
+
+    #[test]
+    fn remembers_columns() {
+        let columns: Vec<usize> = tokenize(" 1 +  23").unwrap().iter().map(|token| token.column).collect();
+        assert_eq!(columns, [2, 4, 7]);
+    }
+
+    #[test]
+    fn rejects_what_it_doesnt_know() {
+        assert_eq!(tokenize("1 + x"), Err(TokenError::UnexpectedChar { ch: 'x', column: 5 }));
+        assert_eq!(tokenize("1.5"), Err(TokenError::UnexpectedChar { ch: '.', column: 2 }));
+        assert_eq!(tokenize("1 + 9223372036854775808"), Err(TokenError::NumberTooLarge { column: 5 }));
+        assert_eq!(tokenize("9223372036854775807"), Ok(vec![Token { kind: TokenKind::Number(i64::MAX), column: 1 }]));
+        assert_eq!(TokenError::UnexpectedChar { ch: 'x', column: 5 }.to_string(), "column 5: unexpected 'x'");
+    }
+}
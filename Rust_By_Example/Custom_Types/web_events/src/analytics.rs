@@ -0,0 +1,491 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crate::event::{EventHandler, Record};
+
+// Numbers about sessions of WebEvents: the page views between PageLoad and PageUnload,
+// where the clicks go, how fast people type and how often they paste instead.
+//
+// Feed it one recorded session at a time, then ask for the summary.
+
+// A pause longer than this between two key presses is not typing any more, it doesn't
+// count towards the typing speed
+pub const TYPING_PAUSE: Duration = Duration::from_secs(2);
+
+// Clicks are counted in squares of this many pixels
+pub const DEFAULT_BUCKET_SIZE: i64 = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageView {
+    pub start: Duration,
+    pub end: Duration,
+    // false when the session ended or the next page loaded without a PageUnload
+    pub unloaded: bool,
+    pub clicks: u64,
+    pub key_presses: u64,
+    pub pastes: u64,
+}
+
+impl PageView {
+    pub fn duration(&self) -> Duration {
+        self.end.checked_sub(self.start).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analytics {
+    bucket_size: i64,
+    sessions: usize,
+    // how long the sessions went on, from the first event to the last
+    session_time: Duration,
+    page_views: Vec<PageView>,
+    open_view: Option<PageView>,
+    // events with no page loaded
+    stray_events: u64,
+    heatmap: BTreeMap<(i64, i64), u64>,
+    key_presses: u64,
+    last_key: Option<Duration>,
+    typing_time: Duration,
+    // the key presses that came soon enough after the one before
+    typed_keys: u64,
+    pastes: u64,
+    pasted_chars: u64,
+}
+
+impl Default for Analytics {
+    fn default() -> Analytics {
+        Analytics::new(DEFAULT_BUCKET_SIZE)
+    }
+}
+
+impl Analytics {
+    // bucket_size is the side of the heatmap squares, at least 1
+    pub fn new(bucket_size: i64) -> Analytics {
+        Analytics {
+            bucket_size: bucket_size.max(1),
+            sessions: 0,
+            session_time: Duration::from_secs(0),
+            page_views: Vec::new(),
+            open_view: None,
+            stray_events: 0,
+            heatmap: BTreeMap::new(),
+            key_presses: 0,
+            last_key: None,
+            typing_time: Duration::from_secs(0),
+            typed_keys: 0,
+            pastes: 0,
+            pasted_chars: 0,
+        }
+    }
+
+    // One recorded session. A page still open at its end closes with its last event.
+    pub fn feed(&mut self, records: &[Record]) {
+        let (first, last) = match (records.first(), records.last()) {
+            (Some(first), Some(last)) => (first.at, last.at),
+            _ => return,
+        };
+        self.sessions += 1;
+        self.session_time += last.checked_sub(first).unwrap_or_default();
+        for record in records {
+            record.dispatch(self);
+        }
+        self.close_view(last, false);
+        // typing doesn't go on from one session into the next
+        self.last_key = None;
+    }
+
+    pub fn page_views(&self) -> &[PageView] {
+        &self.page_views
+    }
+
+    // Clicks per bucket: (0, 1) holds the clicks with 0 <= x < size and size <= y < 2 * size
+    pub fn heatmap(&self) -> &BTreeMap<(i64, i64), u64> {
+        &self.heatmap
+    }
+
+    fn close_view(&mut self, at: Duration, unloaded: bool) {
+        if let Some(mut view) = self.open_view.take() {
+            view.end = at;
+            view.unloaded = unloaded;
+            self.page_views.push(view);
+        }
+    }
+
+    // the page view the event happened in, None when no page was loaded
+    fn view(&mut self) -> Option<&mut PageView> {
+        if self.open_view.is_none() {
+            self.stray_events += 1;
+        }
+        self.open_view.as_mut()
+    }
+
+    // The pixels of a heatmap column or row. The buckets at the edges of i64 stop there,
+    // so they are worked out in i128.
+    fn bucket_bounds(&self, bucket: i64) -> RangeInclusive<i64> {
+        let clamp = |n: i128| n.clamp(i64::MIN.into(), i64::MAX.into()) as i64;
+        let (bucket, size) = (i128::from(bucket), i128::from(self.bucket_size));
+        clamp(bucket * size)..=clamp((bucket + 1) * size - 1)
+    }
+
+    pub fn summary(&self) -> Summary {
+        let mut hot_spots: Vec<HotSpot> = self
+            .heatmap
+            .iter()
+            .map(|(&(column, row), &clicks)| HotSpot {
+                x: self.bucket_bounds(column),
+                y: self.bucket_bounds(row),
+                clicks,
+            })
+            .collect();
+        // most clicked first, ties top left first as the map is ordered
+        hot_spots.sort_by_key(|spot| std::cmp::Reverse(spot.clicks));
+
+        let minutes = |duration: Duration| duration.as_secs_f64() / 60.0;
+        let per_minute = |count: u64, duration: Duration| {
+            if duration > Duration::from_secs(0) {
+                Some(count as f64 / minutes(duration))
+            } else {
+                None
+            }
+        };
+        let total_view_time: Duration = self.page_views.iter().map(PageView::duration).sum();
+
+        Summary {
+            sessions: self.sessions,
+            session_time: self.session_time,
+            page_views: self.page_views.len(),
+            unloaded_views: self.page_views.iter().filter(|view| view.unloaded).count(),
+            average_view_time: if self.page_views.is_empty() {
+                None
+            } else {
+                Some(total_view_time / self.page_views.len() as u32)
+            },
+            stray_events: self.stray_events,
+            clicks: self.heatmap.values().sum(),
+            hot_spots,
+            key_presses: self.key_presses,
+            keys_per_minute: per_minute(self.typed_keys, self.typing_time),
+            pastes: self.pastes,
+            pasted_chars: self.pasted_chars,
+            pastes_per_minute: per_minute(self.pastes, self.session_time),
+        }
+    }
+}
+
+impl EventHandler for Analytics {
+    fn page_load(&mut self, at: Duration) {
+        // loading a page while one is open leaves that one without an unload
+        self.close_view(at, false);
+        self.open_view = Some(PageView { start: at, end: at, unloaded: false, clicks: 0, key_presses: 0, pastes: 0 });
+    }
+
+    fn page_unload(&mut self, at: Duration) {
+        if self.open_view.is_some() {
+            self.close_view(at, true);
+        } else {
+            self.stray_events += 1;
+        }
+    }
+
+    fn key_press(&mut self, at: Duration, _key: char) {
+        self.key_presses += 1;
+        if let Some(last) = self.last_key {
+            let gap = at.checked_sub(last).unwrap_or_default();
+            if gap <= TYPING_PAUSE {
+                self.typing_time += gap;
+                self.typed_keys += 1;
+            }
+        }
+        self.last_key = Some(at);
+        if let Some(view) = self.view() {
+            view.key_presses += 1;
+        }
+    }
+
+    fn paste(&mut self, _at: Duration, text: &str) {
+        self.pastes += 1;
+        self.pasted_chars += text.chars().count() as u64;
+        if let Some(view) = self.view() {
+            view.pastes += 1;
+        }
+    }
+
+    fn click(&mut self, _at: Duration, x: i64, y: i64) {
+        let bucket = (x.div_euclid(self.bucket_size), y.div_euclid(self.bucket_size));
+        *self.heatmap.entry(bucket).or_insert(0) += 1;
+        if let Some(view) = self.view() {
+            view.clicks += 1;
+        }
+    }
+}
+
+// A square of the heatmap with its clicks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotSpot {
+    pub x: RangeInclusive<i64>,
+    pub y: RangeInclusive<i64>,
+    pub clicks: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub sessions: usize,
+    pub session_time: Duration,
+    pub page_views: usize,
+    pub unloaded_views: usize,
+    pub average_view_time: Option<Duration>,
+    pub stray_events: u64,
+    pub clicks: u64,
+    // most clicked first
+    pub hot_spots: Vec<HotSpot>,
+    pub key_presses: u64,
+    // None without two key presses close enough together
+    pub keys_per_minute: Option<f64>,
+    pub pastes: u64,
+    pub pasted_chars: u64,
+    pub pastes_per_minute: Option<f64>,
+}
+
+// how many hot spots the report lists
+const REPORT_HOT_SPOTS: usize = 5;
+
+fn optional(value: Option<f64>) -> String {
+    value.map_or_else(|| String::from("-"), |value| format!("{:.1}", value))
+}
+
+impl Summary {
+    // The same numbers as one JSON object, for a spreadsheet or a dashboard
+    pub fn to_json(&self) -> String {
+        let number = |value: Option<f64>| value.map_or_else(|| String::from("null"), |value| format!("{:.3}", value));
+        let hot_spots: Vec<String> = self
+            .hot_spots
+            .iter()
+            .map(|spot| {
+                format!(
+                    "{{\"x\":[{},{}],\"y\":[{},{}],\"clicks\":{}}}",
+                    spot.x.start(),
+                    spot.x.end(),
+                    spot.y.start(),
+                    spot.y.end(),
+                    spot.clicks
+                )
+            })
+            .collect();
+        format!(
+            "{{\"sessions\":{},\"session_ms\":{},\"page_views\":{},\"unloaded_views\":{},\"average_view_ms\":{},\
+             \"stray_events\":{},\"clicks\":{},\"hot_spots\":[{}],\"key_presses\":{},\"keys_per_minute\":{},\
+             \"pastes\":{},\"pasted_chars\":{},\"pastes_per_minute\":{}}}",
+            self.sessions,
+            self.session_time.as_millis(),
+            self.page_views,
+            self.unloaded_views,
+            self.average_view_time.map_or_else(|| String::from("null"), |time| time.as_millis().to_string()),
+            self.stray_events,
+            self.clicks,
+            hot_spots.join(","),
+            self.key_presses,
+            number(self.keys_per_minute),
+            self.pastes,
+            self.pasted_chars,
+            number(self.pastes_per_minute)
+        )
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "sessions       {} ({:.1}s)", self.sessions, self.session_time.as_secs_f64())?;
+        write!(f, "page views     {} ({} unloaded", self.page_views, self.unloaded_views)?;
+        if let Some(time) = self.average_view_time {
+            write!(f, ", {:.1}s on average", time.as_secs_f64())?;
+        }
+        writeln!(f, ")")?;
+        if self.stray_events > 0 {
+            writeln!(f, "stray events   {} with no page loaded", self.stray_events)?;
+        }
+        writeln!(f, "clicks         {}", self.clicks)?;
+        for spot in self.hot_spots.iter().take(REPORT_HOT_SPOTS) {
+            writeln!(
+                f,
+                "  x {}..={}, y {}..={}: {}",
+                spot.x.start(),
+                spot.x.end(),
+                spot.y.start(),
+                spot.y.end(),
+                spot.clicks
+            )?;
+        }
+        writeln!(f, "key presses    {} ({} per minute)", self.key_presses, optional(self.keys_per_minute))?;
+        writeln!(
+            f,
+            "pastes         {} ({} chars, {} per minute)",
+            self.pastes,
+            self.pasted_chars,
+            optional(self.pastes_per_minute)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::WebEvent;
+
+    fn record(ms: u64, event: WebEvent) -> Record {
+        Record::new(Duration::from_millis(ms), event)
+    }
+
+    fn session() -> Vec<Record> {
+        vec![
+            record(0, WebEvent::PageLoad),
+            record(1000, WebEvent::Click { x: 10, y: 10 }),
+            record(1100, WebEvent::Click { x: 49, y: 0 }),
+            record(2000, WebEvent::KeyPress('h')),
+            record(2200, WebEvent::KeyPress('i')),
+            record(2400, WebEvent::KeyPress('!')),
+            // a long pause, not typing
+            record(9000, WebEvent::KeyPress('x')),
+            record(10_000, WebEvent::PageUnload),
+            record(11_000, WebEvent::Paste(String::from("no page"))),
+            record(12_000, WebEvent::PageLoad),
+            record(13_000, WebEvent::Click { x: -1, y: 120 }),
+            record(14_000, WebEvent::Paste(String::from("hello"))),
+            record(15_000, WebEvent::PageLoad),
+            record(30_000, WebEvent::Click { x: 60, y: 60 }),
+        ]
+    }
+
+    #[test]
+    fn splits_page_views() {
+        let mut analytics = Analytics::default();
+        analytics.feed(&session());
+
+        let views = analytics.page_views();
+        assert_eq!(views.len(), 3);
+        assert_eq!(
+            views[0],
+            PageView {
+                start: Duration::from_secs(0),
+                end: Duration::from_secs(10),
+                unloaded: true,
+                clicks: 2,
+                key_presses: 4,
+                pastes: 0
+            }
+        );
+        assert_eq!((views[1].duration(), views[1].unloaded, views[1].pastes), (Duration::from_secs(3), false, 1));
+        // closed by the end of the session
+        assert_eq!((views[2].end, views[2].clicks), (Duration::from_secs(30), 1));
+
+        let summary = analytics.summary();
+        assert_eq!(summary.page_views, 3);
+        assert_eq!(summary.unloaded_views, 1);
+        assert_eq!(summary.average_view_time, Some(Duration::from_secs(28) / 3));
+        assert_eq!(summary.stray_events, 1);
+    }
+
+    #[test]
+    fn buckets_clicks() {
+        let mut analytics = Analytics::new(50);
+        analytics.feed(&session());
+        let heatmap: Vec<((i64, i64), u64)> = analytics.heatmap().iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(heatmap, [((-1, 2), 1), ((0, 0), 2), ((1, 1), 1)]);
+
+        let summary = analytics.summary();
+        assert_eq!(summary.clicks, 4);
+        assert_eq!(summary.hot_spots[0], HotSpot { x: 0..=49, y: 0..=49, clicks: 2 });
+        assert_eq!(summary.hot_spots[1], HotSpot { x: -50..=-1, y: 100..=149, clicks: 1 });
+    }
+
+    #[test]
+    fn clicks_at_the_edges_of_i64() {
+        let mut analytics = Analytics::default();
+        analytics.feed(&[
+            record(0, WebEvent::Click { x: i64::MAX, y: 0 }),
+            record(1, WebEvent::Click { x: i64::MIN, y: i64::MAX }),
+            record(2, WebEvent::Click { x: i64::MIN, y: i64::MIN }),
+        ]);
+        let summary = analytics.summary();
+        let spots: Vec<(RangeInclusive<i64>, RangeInclusive<i64>)> =
+            summary.hot_spots.iter().map(|spot| (spot.x.clone(), spot.y.clone())).collect();
+        assert_eq!(
+            spots,
+            [
+                (i64::MIN..=i64::MIN + 7, i64::MIN..=i64::MIN + 7),
+                (i64::MIN..=i64::MIN + 7, i64::MAX - 7..=i64::MAX),
+                (i64::MAX - 7..=i64::MAX, 0..=49),
+            ]
+        );
+        assert!(summary.to_string().contains(&format!("  x {}..={}, y 0..=49: 1\n", i64::MAX - 7, i64::MAX)));
+        assert!(summary.to_json().contains(&format!("{{\"x\":[{},{}],\"y\":[0,49],\"clicks\":1}}", i64::MAX - 7, i64::MAX)));
+
+        // the smallest buckets reach i64::MIN and i64::MAX exactly
+        let mut analytics = Analytics::new(1);
+        analytics.feed(&[record(0, WebEvent::Click { x: i64::MIN, y: i64::MAX })]);
+        assert_eq!(analytics.summary().hot_spots[0], HotSpot { x: i64::MIN..=i64::MIN, y: i64::MAX..=i64::MAX, clicks: 1 });
+    }
+
+    #[test]
+    fn measures_typing_and_pasting() {
+        let mut analytics = Analytics::default();
+        analytics.feed(&session());
+        let summary = analytics.summary();
+
+        assert_eq!(summary.key_presses, 4);
+        // two keys typed in 0.4s, the one after the pause doesn't count
+        assert_eq!(summary.keys_per_minute, Some(300.0));
+        assert_eq!((summary.pastes, summary.pasted_chars), (2, 12));
+        // two pastes in half a minute
+        assert_eq!(summary.pastes_per_minute, Some(4.0));
+    }
+
+    #[test]
+    fn adds_up_sessions() {
+        let mut analytics = Analytics::default();
+        analytics.feed(&session());
+        // typing doesn't carry over into the next session
+        analytics.feed(&[record(0, WebEvent::KeyPress('a')), record(60_000, WebEvent::PageUnload)]);
+        analytics.feed(&[]);
+
+        let summary = analytics.summary();
+        assert_eq!(summary.sessions, 2);
+        assert_eq!(summary.session_time, Duration::from_secs(90));
+        assert_eq!(summary.key_presses, 5);
+        assert_eq!(summary.keys_per_minute, Some(300.0));
+        assert_eq!(summary.stray_events, 3);
+    }
+
+    #[test]
+    fn empty_summary() {
+        let summary = Analytics::default().summary();
+        assert_eq!((summary.sessions, summary.page_views, summary.average_view_time), (0, 0, None));
+        assert_eq!((summary.keys_per_minute, summary.pastes_per_minute), (None, None));
+        assert_eq!(
+            summary.to_json(),
+            "{\"sessions\":0,\"session_ms\":0,\"page_views\":0,\"unloaded_views\":0,\"average_view_ms\":null,\
+             \"stray_events\":0,\"clicks\":0,\"hot_spots\":[],\"key_presses\":0,\"keys_per_minute\":null,\
+             \"pastes\":0,\"pasted_chars\":0,\"pastes_per_minute\":null}"
+        );
+    }
+
+    #[test]
+    fn reports() {
+        let mut analytics = Analytics::default();
+        analytics.feed(&session());
+        assert_eq!(
+            analytics.summary().to_string(),
+            "sessions       1 (30.0s)\n\
+             page views     3 (1 unloaded, 9.3s on average)\n\
+             stray events   1 with no page loaded\n\
+             clicks         4\n\
+             \x20 x 0..=49, y 0..=49: 2\n\
+             \x20 x -50..=-1, y 100..=149: 1\n\
+             \x20 x 50..=99, y 50..=99: 1\n\
+             key presses    4 (300.0 per minute)\n\
+             pastes         2 (12 chars, 4.0 per minute)\n"
+        );
+        assert!(analytics.summary().to_json().contains("\"hot_spots\":[{\"x\":[0,49],\"y\":[0,49],\"clicks\":2},"));
+    }
+}
@@ -0,0 +1,246 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::time::Duration;
+
+use crate::analytics::Analytics;
+use crate::event::{inspect, EventHandler, WebEvent};
+use crate::log::{self, Recorder};
+use crate::replay::{self, Clock, Speed};
+
+pub const USAGE: &str = "\
+usage: web_events record <log.jsonl>           (type events: load, unload, key x, paste text, click 20 80)
+       web_events replay <log.jsonl> [speed]   (1x, 10x, 0.5x or max, 1x by default)
+       web_events report [--json] <log.jsonl>...  (one session per log)";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Record { log: String },
+    Replay { log: String, speed: Speed },
+    Report { logs: Vec<String>, json: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub command: Command,
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, &'static str> {
+        // skip the program name
+        let mut args = args.iter().skip(1);
+
+        let command = match args.next().map(|arg| arg.as_str()) {
+            Some("record") => Command::Record { log: args.next().ok_or("the log file is needed")?.clone() },
+            Some("replay") => {
+                let log = args.next().ok_or("the log file is needed")?.clone();
+                let speed = match args.next() {
+                    Some(speed) => speed.parse().map_err(|_| "the speed has to be like 1x, 10x, 0.5x or max")?,
+                    None => Speed::Original,
+                };
+                Command::Replay { log, speed }
+            }
+            Some("report") => {
+                let mut json = false;
+                let mut logs = Vec::new();
+                for arg in args.by_ref() {
+                    match arg.as_str() {
+                        "--json" => json = true,
+                        _ => logs.push(arg.clone()),
+                    }
+                }
+                if logs.is_empty() {
+                    return Err("at least one log file is needed");
+                }
+                Command::Report { logs, json }
+            }
+            Some(_) => return Err("unknown command"),
+            None => return Err("a command is needed"),
+        };
+
+        if args.next().is_some() {
+            return Err("too many arguments");
+        }
+        Ok(Config { command })
+    }
+}
+
+// Prints every event it is handed with its time, like inspect in Enums.rs
+pub struct Printer<W: Write> {
+    output: W,
+    // the first write that failed, handlers can't return errors
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Printer<W> {
+    pub fn new(output: W) -> Printer<W> {
+        Printer { output, error: None }
+    }
+
+    fn print(&mut self, at: Duration, event: &WebEvent) {
+        if self.error.is_none() {
+            if let Err(e) = writeln!(self.output, "[{:>8.3}s] {}", at.as_secs_f64(), inspect(event)) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    pub fn finish(self) -> io::Result<W> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.output),
+        }
+    }
+}
+
+impl<W: Write> EventHandler for Printer<W> {
+    fn page_load(&mut self, at: Duration) {
+        self.print(at, &WebEvent::PageLoad);
+    }
+
+    fn page_unload(&mut self, at: Duration) {
+        self.print(at, &WebEvent::PageUnload);
+    }
+
+    fn key_press(&mut self, at: Duration, key: char) {
+        self.print(at, &WebEvent::KeyPress(key));
+    }
+
+    fn paste(&mut self, at: Duration, text: &str) {
+        self.print(at, &WebEvent::Paste(text.to_string()));
+    }
+
+    fn click(&mut self, at: Duration, x: i64, y: i64) {
+        self.print(at, &WebEvent::Click { x, y });
+    }
+}
+
+// Typed events are stamped as they come in and written to `log`, a mistyped line is
+// reported and recording goes on
+pub fn record<R: BufRead, L: Write, W: Write>(input: R, log: L, mut output: W) -> Result<usize, Box<dyn Error>> {
+    let mut recorder = Recorder::new(log);
+    let mut count = 0;
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse() {
+            Ok(event) => {
+                let record = recorder.record(event)?;
+                writeln!(output, "[{:>8.3}s] {}", record.at.as_secs_f64(), inspect(&record.event))?;
+                count += 1;
+            }
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+    Ok(count)
+}
+
+pub fn run<R: BufRead, W: Write>(
+    config: Config,
+    input: R,
+    mut output: W,
+    clock: &mut dyn Clock,
+) -> Result<(), Box<dyn Error>> {
+    match config.command {
+        Command::Record { log } => {
+            let file = File::create(&log)?;
+            let count = record(input, file, &mut output)?;
+            writeln!(output, "{} events in {}", count, log)?;
+        }
+        Command::Replay { log, speed } => {
+            let records = log::read_log(BufReader::new(File::open(&log)?))?;
+            let mut printer = Printer::new(&mut output);
+            replay::replay(&records, speed, clock, &mut printer);
+            printer.finish()?;
+        }
+        Command::Report { logs, json } => {
+            let mut analytics = Analytics::default();
+            for log in &logs {
+                analytics.feed(&log::read_log(BufReader::new(File::open(log)?))?);
+            }
+            let summary = analytics.summary();
+            if json {
+                writeln!(output, "{}", summary.to_json())?;
+            } else {
+                write!(output, "{}", summary)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    struct NoWait;
+
+    impl Clock for NoWait {
+        fn sleep(&mut self, _duration: Duration) {}
+    }
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("web_events").chain(line.split_whitespace()).map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            Config::new(&args("record log.jsonl")).unwrap().command,
+            Command::Record { log: String::from("log.jsonl") }
+        );
+        assert_eq!(
+            Config::new(&args("replay log.jsonl 10x")).unwrap().command,
+            Command::Replay { log: String::from("log.jsonl"), speed: Speed::Accelerated(10.0) }
+        );
+        assert_eq!(
+            Config::new(&args("replay log.jsonl")).unwrap().command,
+            Command::Replay { log: String::from("log.jsonl"), speed: Speed::Original }
+        );
+
+        assert_eq!(
+            Config::new(&args("report a.jsonl --json b.jsonl")).unwrap().command,
+            Command::Report { logs: vec![String::from("a.jsonl"), String::from("b.jsonl")], json: true }
+        );
+
+        assert_eq!(Config::new(&args("")), Err("a command is needed"));
+        assert_eq!(Config::new(&args("report --json")), Err("at least one log file is needed"));
+        assert_eq!(Config::new(&args("play")), Err("unknown command"));
+        assert_eq!(Config::new(&args("record")), Err("the log file is needed"));
+        assert_eq!(Config::new(&args("replay log.jsonl fast")), Err("the speed has to be like 1x, 10x, 0.5x or max"));
+        assert_eq!(Config::new(&args("replay log.jsonl 2x 3x")), Err("too many arguments"));
+    }
+
+    #[test]
+    fn records_and_replays() {
+        let path = env::temp_dir().join(format!("web-events-{}-cli.jsonl", process::id()));
+        let log = path.to_str().unwrap();
+
+        let mut output = Vec::new();
+        let config = Config::new(&args(&format!("record {}", log))).unwrap();
+        run(config, "load\nkey x\n\nscroll\nclick 20 80\n".as_bytes(), &mut output, &mut NoWait).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("s] pressed 'x'.\n"));
+        assert!(output.contains("'scroll' is not an event"));
+        assert!(output.ends_with(&format!("3 events in {}\n", log)));
+
+        let mut output = Vec::new();
+        let config = Config::new(&args(&format!("replay {} max", log))).unwrap();
+        run(config, io::empty(), &mut output, &mut NoWait).unwrap();
+        let lines: Vec<String> = String::from_utf8(output).unwrap().lines().map(|line| line[12..].to_string()).collect();
+        assert_eq!(lines, ["page loaded", "pressed 'x'.", "clicked at x=20, y=80."]);
+
+        let mut output = Vec::new();
+        let config = Config::new(&args(&format!("report {} {}", log, log))).unwrap();
+        run(config, io::empty(), &mut output, &mut NoWait).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("sessions       2 ("));
+        assert!(output.contains("\nclicks         2\n  x 0..=49, y 50..=99: 2\n"));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+// The WebEvent of Custom_Types/Enums.rs. Both names and type information together
+// specify the variant: `PageLoad != PageUnload` and `KeyPress(char) != Paste(String)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebEvent {
+    // An `enum` may either be `unit-like`,
+    PageLoad,
+    PageUnload,
+    // like tuple structs,
+    KeyPress(char),
+    Paste(String),
+    // or c-like structures.
+    Click { x: i64, y: i64 },
+}
+
+// An event and when it happened, counted from the start of the recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub at: Duration,
+    pub event: WebEvent,
+}
+
+// The line `inspect` of Enums.rs prints for an event
+pub fn inspect(event: &WebEvent) -> String {
+    match event {
+        WebEvent::PageLoad => String::from("page loaded"),
+        WebEvent::PageUnload => String::from("page unloaded"),
+        // Destructure `c` from inside the `enum`.
+        WebEvent::KeyPress(c) => format!("pressed '{}'.", c),
+        WebEvent::Paste(s) => format!("pasted \"{}\".", s),
+        // Destructure `Click` into `x` and `y`.
+        WebEvent::Click { x, y } => format!("clicked at x={}, y={}.", x, y),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEventError(pub String);
+
+impl fmt::Display for ParseEventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' is not an event, try load, unload, key <c>, paste <text> or click <x> <y>", self.0)
+    }
+}
+
+impl std::error::Error for ParseEventError {}
+
+// Events typed by hand, one per line:
+//   load
+//   unload
+//   key x
+//   paste some text
+//   click 20 80
+impl FromStr for WebEvent {
+    type Err = ParseEventError;
+
+    fn from_str(s: &str) -> Result<WebEvent, ParseEventError> {
+        let err = || ParseEventError(s.to_string());
+        let (name, rest) = s.trim_start().split_once(' ').unwrap_or((s.trim(), ""));
+        match name {
+            "load" if rest.trim().is_empty() => Ok(WebEvent::PageLoad),
+            "unload" if rest.trim().is_empty() => Ok(WebEvent::PageUnload),
+            // `key  ` is the space key, so the rest is not trimmed
+            "key" => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(key), None) => Ok(WebEvent::KeyPress(key)),
+                    _ => Err(err()),
+                }
+            }
+            "paste" => Ok(WebEvent::Paste(rest.to_string())),
+            "click" => {
+                let numbers: Vec<&str> = rest.split_whitespace().collect();
+                match numbers.as_slice() {
+                    [x, y] => Ok(WebEvent::Click { x: x.parse().map_err(|_| err())?, y: y.parse().map_err(|_| err())? }),
+                    _ => Err(err()),
+                }
+            }
+            _ => Err(err()),
+        }
+    }
+}
+
+// What `dispatch` hands the events to, one method per variant, with the time of the event.
+// They all do nothing by default, so a handler only implements what it cares about.
+pub trait EventHandler {
+    fn page_load(&mut self, _at: Duration) {}
+
+    fn page_unload(&mut self, _at: Duration) {}
+
+    fn key_press(&mut self, _at: Duration, _key: char) {}
+
+    fn paste(&mut self, _at: Duration, _text: &str) {}
+
+    fn click(&mut self, _at: Duration, _x: i64, _y: i64) {}
+}
+
+impl Record {
+    pub fn new(at: Duration, event: WebEvent) -> Record {
+        Record { at, event }
+    }
+
+    pub fn dispatch(&self, handler: &mut dyn EventHandler) {
+        let at = self.at;
+        match &self.event {
+            WebEvent::PageLoad => handler.page_load(at),
+            WebEvent::PageUnload => handler.page_unload(at),
+            WebEvent::KeyPress(key) => handler.key_press(at, *key),
+            WebEvent::Paste(text) => handler.paste(at, text),
+            WebEvent::Click { x, y } => handler.click(at, *x, *y),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Keys(Vec<(u128, char)>);
+
+    impl EventHandler for Keys {
+        fn key_press(&mut self, at: Duration, key: char) {
+            self.0.push((at.as_millis(), key));
+        }
+    }
+
+    #[test]
+    fn inspects_like_the_example() {
+        assert_eq!(inspect(&WebEvent::KeyPress('x')), "pressed 'x'.");
+        assert_eq!(inspect(&WebEvent::Paste("my text".to_owned())), "pasted \"my text\".");
+        assert_eq!(inspect(&WebEvent::Click { x: 20, y: 80 }), "clicked at x=20, y=80.");
+        assert_eq!(inspect(&WebEvent::PageLoad), "page loaded");
+        assert_eq!(inspect(&WebEvent::PageUnload), "page unloaded");
+    }
+
+    #[test]
+    fn parses_typed_events() {
+        assert_eq!("load".parse(), Ok(WebEvent::PageLoad));
+        assert_eq!("unload".parse(), Ok(WebEvent::PageUnload));
+        assert_eq!("key x".parse(), Ok(WebEvent::KeyPress('x')));
+        assert_eq!("key  ".parse(), Ok(WebEvent::KeyPress(' ')));
+        assert_eq!("paste my  text".parse(), Ok(WebEvent::Paste(String::from("my  text"))));
+        assert_eq!("click 20 -80".parse(), Ok(WebEvent::Click { x: 20, y: -80 }));
+
+        for bad in ["", "scroll", "load now", "key", "key ab", "click 1", "click 1 two"].iter() {
+            assert_eq!(bad.parse::<WebEvent>(), Err(ParseEventError(bad.to_string())), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn dispatches_to_the_handler() {
+        let mut keys = Keys::default();
+        let records = vec![
+            Record::new(Duration::from_millis(5), WebEvent::PageLoad),
+            Record::new(Duration::from_millis(10), WebEvent::KeyPress('h')),
+            Record::new(Duration::from_millis(15), WebEvent::KeyPress('i')),
+        ];
+        for record in &records {
+            record.dispatch(&mut keys);
+        }
+        assert_eq!(keys.0, [(10, 'h'), (15, 'i')]);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod analytics;
+pub mod cli;
+pub mod event;
+pub mod log;
+pub mod replay;
@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+use crate::event::{Record, WebEvent};
+
+// The log is JSON Lines: one JSON object per line, the milliseconds since the start of the
+// recording, the type of the event and its fields.
+//
+//   {"at_ms":0,"type":"page_load"}
+//   {"at_ms":120,"type":"key_press","key":"x"}
+//   {"at_ms":200,"type":"paste","text":"my text"}
+//   {"at_ms":350,"type":"click","x":20,"y":80}
+//   {"at_ms":900,"type":"page_unload"}
+//
+// The objects are flat with only strings and integers in them, so they are written and
+// read here without a JSON library. Fields we don't know are skipped when reading.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    // not a flat JSON object of strings and integers, with where it went wrong
+    Syntax { column: usize, expected: &'static str },
+    MissingField(&'static str),
+    // a string where a number belongs or the other way round, or a number out of range
+    BadField(&'static str),
+    UnknownType(String),
+}
+
+#[derive(Debug)]
+pub enum LogError {
+    Io(io::Error),
+    Line { line: usize, error: ParseError },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax { column, expected } => write!(f, "column {}: expected {}", column, expected),
+            ParseError::MissingField(field) => write!(f, "the field \"{}\" is missing", field),
+            ParseError::BadField(field) => write!(f, "the field \"{}\" has the wrong type or is out of range", field),
+            ParseError::UnknownType(name) => write!(f, "\"{}\" is not a type of event", name),
+        }
+    }
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogError::Io(e) => write!(f, "could not read the log: {}", e),
+            LogError::Line { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl std::error::Error for LogError {}
+
+impl From<io::Error> for LogError {
+    fn from(e: io::Error) -> LogError {
+        LogError::Io(e)
+    }
+}
+
+fn write_string(line: &mut String, s: &str) {
+    line.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            // the other control characters have no short escape
+            c if c.is_control() => line.push_str(&format!("\\u{:04x}", c as u32)),
+            c => line.push(c),
+        }
+    }
+    line.push('"');
+}
+
+// One line of the log, without the line break
+pub fn to_json_line(record: &Record) -> String {
+    let mut line = format!("{{\"at_ms\":{},\"type\":", record.at.as_millis());
+    match &record.event {
+        WebEvent::PageLoad => line.push_str("\"page_load\""),
+        WebEvent::PageUnload => line.push_str("\"page_unload\""),
+        WebEvent::KeyPress(key) => {
+            line.push_str("\"key_press\",\"key\":");
+            write_string(&mut line, &key.to_string());
+        }
+        WebEvent::Paste(text) => {
+            line.push_str("\"paste\",\"text\":");
+            write_string(&mut line, text);
+        }
+        WebEvent::Click { x, y } => line.push_str(&format!("\"click\",\"x\":{},\"y\":{}", x, y)),
+    }
+    line.push('}');
+    line
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Str(String),
+    Int(i128),
+}
+
+// Just enough of JSON for the log: an object whose values are strings or integers
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    len: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn syntax<T>(&mut self, expected: &'static str) -> Result<T, ParseError> {
+        let column = self.chars.peek().map_or(self.len, |(i, _)| *i) + 1;
+        Err(ParseError::Syntax { column, expected })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some((_, ' ')) | Some((_, '\t')) | Some((_, '\r')) | Some((_, '\n')) = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if let Some((_, next)) = self.chars.peek() {
+            if *next == c {
+                self.chars.next();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn object(&mut self) -> Result<HashMap<String, Value>, ParseError> {
+        let mut fields = HashMap::new();
+        if !self.eat('{') {
+            return self.syntax("'{'");
+        }
+        if !self.eat('}') {
+            loop {
+                self.skip_whitespace();
+                let key = self.string()?;
+                if !self.eat(':') {
+                    return self.syntax("':'");
+                }
+                self.skip_whitespace();
+                let value = self.value()?;
+                fields.insert(key, value);
+                if self.eat('}') {
+                    break;
+                }
+                if !self.eat(',') {
+                    return self.syntax("',' or '}'");
+                }
+            }
+        }
+        self.skip_whitespace();
+        if self.chars.peek().is_some() {
+            return self.syntax("the end of the line");
+        }
+        Ok(fields)
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.chars.peek() {
+            Some((_, '"')) => Ok(Value::Str(self.string()?)),
+            Some((_, c)) if *c == '-' || c.is_ascii_digit() => self.integer(),
+            _ => self.syntax("a string or an integer"),
+        }
+    }
+
+    fn integer(&mut self) -> Result<Value, ParseError> {
+        let mut digits = String::new();
+        if let Some((_, '-')) = self.chars.peek() {
+            digits.push('-');
+            self.chars.next();
+        }
+        while let Some((_, c)) = self.chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            digits.push(*c);
+            self.chars.next();
+        }
+        // 1.5 or 1e3 are numbers in JSON, but not ones the log has
+        if let Some((_, '.')) | Some((_, 'e')) | Some((_, 'E')) = self.chars.peek() {
+            return self.syntax("an integer");
+        }
+        match digits.parse() {
+            Ok(n) => Ok(Value::Int(n)),
+            Err(_) => self.syntax("an integer"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        match self.chars.next() {
+            Some((_, '"')) => {}
+            _ => return self.syntax("'\"'"),
+        }
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(s),
+                Some((_, '\\')) => {
+                    let escaped = match self.chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'u')) => self.unicode_escape()?,
+                        _ => return self.syntax("an escape like \\n"),
+                    };
+                    s.push(escaped);
+                }
+                Some((_, c)) if c.is_control() => return self.syntax("no control characters in a string"),
+                Some((_, c)) => s.push(c),
+                None => return self.syntax("the end of the string"),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.chars.next().and_then(|(_, c)| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return self.syntax("four hex digits"),
+            }
+        }
+        Ok(code)
+    }
+
+    // é, and characters beyond the first 65536 as a surrogate pair, 😀
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let first = self.hex4()?;
+        let code = if (0xd800..0xdc00).contains(&first) {
+            let backslash = self.chars.next().map(|(_, c)| c);
+            let u = self.chars.next().map(|(_, c)| c);
+            if backslash != Some('\\') || u != Some('u') {
+                return self.syntax("the second half of a surrogate pair");
+            }
+            let second = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&second) {
+                return self.syntax("the second half of a surrogate pair");
+            }
+            0x10000 + ((first - 0xd800) << 10) + (second - 0xdc00)
+        } else {
+            first
+        };
+        match std::char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.syntax("a valid character"),
+        }
+    }
+}
+
+pub fn from_json_line(line: &str) -> Result<Record, ParseError> {
+    let mut parser = Parser { chars: line.char_indices().peekable(), len: line.len() };
+    let fields = parser.object()?;
+
+    let string = |field: &'static str| match fields.get(field) {
+        Some(Value::Str(s)) => Ok(s.as_str()),
+        Some(_) => Err(ParseError::BadField(field)),
+        None => Err(ParseError::MissingField(field)),
+    };
+    let integer = |field: &'static str| match fields.get(field) {
+        Some(Value::Int(n)) => Ok(*n),
+        Some(_) => Err(ParseError::BadField(field)),
+        None => Err(ParseError::MissingField(field)),
+    };
+
+    let at_ms = u64::try_from(integer("at_ms")?).map_err(|_| ParseError::BadField("at_ms"))?;
+    let event = match string("type")? {
+        "page_load" => WebEvent::PageLoad,
+        "page_unload" => WebEvent::PageUnload,
+        "key_press" => {
+            let mut chars = string("key")?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(key), None) => WebEvent::KeyPress(key),
+                _ => return Err(ParseError::BadField("key")),
+            }
+        }
+        "paste" => WebEvent::Paste(string("text")?.to_string()),
+        "click" => {
+            let x = i64::try_from(integer("x")?).map_err(|_| ParseError::BadField("x"))?;
+            let y = i64::try_from(integer("y")?).map_err(|_| ParseError::BadField("y"))?;
+            WebEvent::Click { x, y }
+        }
+        other => return Err(ParseError::UnknownType(other.to_string())),
+    };
+    Ok(Record::new(Duration::from_millis(at_ms), event))
+}
+
+// Reads a whole log. Blank lines are fine, anything else that isn't an event is an error
+// with its line number: a replay with events missing would be a different session.
+pub fn read_log<R: BufRead>(reader: R) -> Result<Vec<Record>, LogError> {
+    let mut records = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = from_json_line(&line).map_err(|error| LogError::Line { line: i + 1, error })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+pub fn write_log<W: Write>(mut writer: W, records: &[Record]) -> io::Result<()> {
+    for record in records {
+        writeln!(writer, "{}", to_json_line(record))?;
+    }
+    writer.flush()
+}
+
+// Stamps events with the time since the recorder was made and writes them to the log
+// as they come, so a crash loses at most the event being written.
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(writer: W) -> Recorder<W> {
+        Recorder { writer, start: Instant::now() }
+    }
+
+    pub fn record(&mut self, event: WebEvent) -> io::Result<Record> {
+        // whole milliseconds, the precision of the log
+        let at = Duration::from_millis(self.start.elapsed().as_millis() as u64);
+        let record = Record::new(at, event);
+        self.write(&record)?;
+        Ok(record)
+    }
+
+    // A record with a time of its own, for events that were timed elsewhere
+    pub fn write(&mut self, record: &Record) -> io::Result<()> {
+        writeln!(self.writer, "{}", to_json_line(record))?;
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ms: u64, event: WebEvent) -> Record {
+        Record::new(Duration::from_millis(ms), event)
+    }
+
+    fn session() -> Vec<Record> {
+        vec![
+            record(0, WebEvent::PageLoad),
+            record(120, WebEvent::KeyPress('x')),
+            record(200, WebEvent::Paste(String::from("my text"))),
+            record(350, WebEvent::Click { x: 20, y: -80 }),
+            record(900, WebEvent::PageUnload),
+        ]
+    }
+
+    #[test]
+    fn writes_json_lines() {
+        let mut log = Vec::new();
+        write_log(&mut log, &session()).unwrap();
+        assert_eq!(
+            String::from_utf8(log).unwrap(),
+            "{\"at_ms\":0,\"type\":\"page_load\"}\n\
+             {\"at_ms\":120,\"type\":\"key_press\",\"key\":\"x\"}\n\
+             {\"at_ms\":200,\"type\":\"paste\",\"text\":\"my text\"}\n\
+             {\"at_ms\":350,\"type\":\"click\",\"x\":20,\"y\":-80}\n\
+             {\"at_ms\":900,\"type\":\"page_unload\"}\n"
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut records = session();
+        records.push(record(1000, WebEvent::Paste(String::from("\"quoted\"\\ \n\ttab \u{1} é 😀"))));
+        records.push(record(1001, WebEvent::KeyPress('"')));
+        records.push(record(1002, WebEvent::KeyPress('\n')));
+        records.push(record(1003, WebEvent::Click { x: i64::MIN, y: i64::MAX }));
+
+        let mut log = Vec::new();
+        write_log(&mut log, &records).unwrap();
+        assert_eq!(read_log(log.as_slice()).unwrap(), records);
+    }
+
+    #[test]
+    fn reads_json_written_by_others() {
+        let line = r#" { "type" : "paste", "session": "abc", "text": "café 😀 \/", "at_ms": 7 } "#;
+        assert_eq!(from_json_line(line), Ok(record(7, WebEvent::Paste(String::from("café 😀 /")))));
+    }
+
+    #[test]
+    fn rejects_broken_lines() {
+        assert_eq!(from_json_line(""), Err(ParseError::Syntax { column: 1, expected: "'{'" }));
+        assert_eq!(
+            from_json_line(r#"{"at_ms":1,"type":"page_load"} x"#),
+            Err(ParseError::Syntax { column: 32, expected: "the end of the line" })
+        );
+        assert!(matches!(from_json_line(r#"{"at_ms":1.5,"type":"page_load"}"#), Err(ParseError::Syntax { .. })));
+        assert!(matches!(from_json_line(r#"{"at_ms":1,"type":"paste","text":"\ud83d"}"#), Err(ParseError::Syntax { .. })));
+        assert!(matches!(from_json_line(r#"{"at_ms":1,"type":true}"#), Err(ParseError::Syntax { .. })));
+        assert!(matches!(from_json_line(r#"{"at_ms":1 "type":"page_load"}"#), Err(ParseError::Syntax { .. })));
+
+        assert_eq!(from_json_line(r#"{"type":"page_load"}"#), Err(ParseError::MissingField("at_ms")));
+        assert_eq!(from_json_line(r#"{"at_ms":-1,"type":"page_load"}"#), Err(ParseError::BadField("at_ms")));
+        assert_eq!(from_json_line(r#"{"at_ms":"1","type":"page_load"}"#), Err(ParseError::BadField("at_ms")));
+        assert_eq!(from_json_line(r#"{"at_ms":1,"type":"key_press","key":"ab"}"#), Err(ParseError::BadField("key")));
+        assert_eq!(from_json_line(r#"{"at_ms":1,"type":"click","x":1}"#), Err(ParseError::MissingField("y")));
+        assert_eq!(
+            from_json_line(r#"{"at_ms":1,"type":"scroll"}"#),
+            Err(ParseError::UnknownType(String::from("scroll")))
+        );
+
+        let err = read_log("{\"at_ms\":0,\"type\":\"page_load\"}\n\n{}\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 3: the field \"at_ms\" is missing");
+    }
+
+    #[test]
+    fn recorder_stamps_events() {
+        let mut recorder = Recorder::new(Vec::new());
+        let first = recorder.record(WebEvent::PageLoad).unwrap();
+        let second = recorder.record(WebEvent::KeyPress('a')).unwrap();
+        assert!(first.at <= second.at);
+
+        let log = recorder.into_inner();
+        assert_eq!(read_log(log.as_slice()).unwrap().len(), 2);
+    }
+}
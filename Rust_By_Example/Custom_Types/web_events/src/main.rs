@@ -0,0 +1,26 @@
+// The WebEvent of Enums.rs, recorded to a log and played back:
+//   web_events record session.jsonl
+//   web_events replay session.jsonl 10x
+use std::env;
+use std::io;
+use std::process;
+
+use web_events::cli::{self, Config};
+use web_events::replay::SystemClock;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let config = Config::new(&args).unwrap_or_else(|err| {
+        eprintln!("Problem parsing arguments: {}", err);
+        eprintln!("{}", cli::USAGE);
+        process::exit(1);
+    });
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(e) = cli::run(config, stdin.lock(), stdout.lock(), &mut SystemClock) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
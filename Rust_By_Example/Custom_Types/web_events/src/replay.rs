@@ -0,0 +1,212 @@
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use crate::event::{EventHandler, Record};
+
+// How fast to play a recording back
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+    // with the gaps between events as they were recorded
+    Original,
+    // the gaps divided by the factor, 10.0 is ten times as fast
+    Accelerated(f64),
+    // no waiting at all
+    Instant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSpeedError(pub String);
+
+impl fmt::Display for ParseSpeedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' is not a speed, try 1x, 10x, 0.5x or max", self.0)
+    }
+}
+
+impl std::error::Error for ParseSpeedError {}
+
+// The slowest speed FromStr takes, a thousand times slower than recorded
+pub const MIN_FACTOR: f64 = 0.001;
+
+impl Speed {
+    // How long to wait for a gap of the recording. A wait too long for a Duration is
+    // Duration::MAX. A factor FromStr wouldn't take (0, negative, NaN or below MIN_FACTOR)
+    // plays as MIN_FACTOR, so there is no NaN or negative wait to turn into forever.
+    pub fn scale(&self, gap: Duration) -> Duration {
+        match self {
+            Speed::Original => gap,
+            Speed::Accelerated(factor) => {
+                let factor = if factor.is_finite() && *factor >= MIN_FACTOR { *factor } else { MIN_FACTOR };
+                Duration::try_from_secs_f64(gap.as_secs_f64() / factor).unwrap_or(Duration::MAX)
+            }
+            Speed::Instant => Duration::from_secs(0),
+        }
+    }
+}
+
+// 1x, 10x, 0.5x (with or without the x, down to MIN_FACTOR) or max
+impl FromStr for Speed {
+    type Err = ParseSpeedError;
+
+    fn from_str(s: &str) -> Result<Speed, ParseSpeedError> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("max") {
+            return Ok(Speed::Instant);
+        }
+        let factor: f64 = s
+            .strip_suffix('x')
+            .unwrap_or(s)
+            .parse()
+            .map_err(|_| ParseSpeedError(s.to_string()))?;
+        // inf and NaN parse too, but make no sense as a speed, and neither does waiting for ages
+        if !factor.is_finite() || factor < MIN_FACTOR {
+            return Err(ParseSpeedError(s.to_string()));
+        }
+        if factor == 1.0 {
+            Ok(Speed::Original)
+        } else {
+            Ok(Speed::Accelerated(factor))
+        }
+    }
+}
+
+// Where the waiting happens, so tests can replay without sleeping
+pub trait Clock {
+    fn sleep(&mut self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+// Feeds the records to the handler, waiting between them as the speed says. The first
+// event comes right away, the wait before it is not part of the session. A record older
+// than the one before it (two logs put together) comes without waiting.
+// Returns how long it waited in all.
+pub fn replay(records: &[Record], speed: Speed, clock: &mut dyn Clock, handler: &mut dyn EventHandler) -> Duration {
+    let mut waited = Duration::from_secs(0);
+    let mut previous = records.first().map(|record| record.at);
+    for record in records {
+        let gap = previous.map_or(Duration::from_secs(0), |previous| record.at.checked_sub(previous).unwrap_or_default());
+        let wait = speed.scale(gap);
+        if wait > Duration::from_secs(0) {
+            clock.sleep(wait);
+            waited = waited.saturating_add(wait);
+        }
+        previous = Some(record.at);
+        record.dispatch(handler);
+    }
+    waited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::WebEvent;
+
+    #[derive(Default)]
+    struct FakeClock(Vec<Duration>);
+
+    impl Clock for FakeClock {
+        fn sleep(&mut self, duration: Duration) {
+            self.0.push(duration);
+        }
+    }
+
+    #[derive(Default)]
+    struct Counter(usize);
+
+    impl EventHandler for Counter {
+        fn page_load(&mut self, _at: Duration) {
+            self.0 += 1;
+        }
+
+        fn click(&mut self, _at: Duration, _x: i64, _y: i64) {
+            self.0 += 1;
+        }
+    }
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record::new(Duration::from_millis(500), WebEvent::PageLoad),
+            Record::new(Duration::from_millis(1500), WebEvent::Click { x: 1, y: 2 }),
+            Record::new(Duration::from_millis(1500), WebEvent::Click { x: 3, y: 4 }),
+            Record::new(Duration::from_millis(4500), WebEvent::Click { x: 5, y: 6 }),
+            // from an older log
+            Record::new(Duration::from_millis(100), WebEvent::PageLoad),
+        ]
+    }
+
+    #[test]
+    fn keeps_the_original_gaps() {
+        let mut clock = FakeClock::default();
+        let mut counter = Counter::default();
+        let waited = replay(&records(), Speed::Original, &mut clock, &mut counter);
+
+        assert_eq!(counter.0, 5);
+        assert_eq!(clock.0, [Duration::from_secs(1), Duration::from_secs(3)]);
+        assert_eq!(waited, Duration::from_secs(4));
+    }
+
+    #[test]
+    fn accelerates() {
+        let mut clock = FakeClock::default();
+        let waited = replay(&records(), Speed::Accelerated(10.0), &mut clock, &mut Counter::default());
+        assert_eq!(clock.0, [Duration::from_millis(100), Duration::from_millis(300)]);
+        assert_eq!(waited, Duration::from_millis(400));
+
+        let mut clock = FakeClock::default();
+        assert_eq!(replay(&records(), Speed::Instant, &mut clock, &mut Counter::default()), Duration::from_secs(0));
+        assert!(clock.0.is_empty());
+    }
+
+    #[test]
+    fn scales_without_panicking() {
+        let gap = Duration::from_secs(2);
+        assert_eq!(Speed::Accelerated(MIN_FACTOR).scale(gap), Duration::from_secs(2000));
+        assert_eq!(Speed::Accelerated(1e-20).scale(gap), Duration::from_secs(2000));
+        assert_eq!(Speed::Accelerated(f64::MIN_POSITIVE).scale(Duration::MAX), Duration::MAX);
+        assert_eq!(Speed::Accelerated(1e20).scale(gap), Duration::from_secs(0));
+    }
+
+    #[test]
+    fn plays_factors_it_wouldnt_parse_as_the_slowest() {
+        let gap = Duration::from_secs(2);
+        for factor in [0.0, -0.0, -2.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY].iter() {
+            assert_eq!(Speed::Accelerated(*factor).scale(Duration::from_secs(0)), Duration::from_secs(0), "{}", factor);
+            assert_eq!(Speed::Accelerated(*factor).scale(gap), Duration::from_secs(2000), "{}", factor);
+        }
+    }
+
+    #[test]
+    fn adds_up_waits_too_long_for_a_duration() {
+        let records = [
+            Record::new(Duration::from_millis(0), WebEvent::PageLoad),
+            Record::new(Duration::from_millis(u64::MAX), WebEvent::PageLoad),
+            Record::new(Duration::from_millis(0), WebEvent::PageLoad),
+            Record::new(Duration::from_millis(u64::MAX), WebEvent::PageLoad),
+        ];
+        let mut clock = FakeClock::default();
+        let waited = replay(&records, Speed::Accelerated(MIN_FACTOR), &mut clock, &mut Counter::default());
+        assert_eq!(clock.0, [Duration::MAX, Duration::MAX]);
+        assert_eq!(waited, Duration::MAX);
+    }
+
+    #[test]
+    fn parses_speeds() {
+        assert_eq!("1x".parse(), Ok(Speed::Original));
+        assert_eq!("10x".parse(), Ok(Speed::Accelerated(10.0)));
+        assert_eq!("0.5".parse(), Ok(Speed::Accelerated(0.5)));
+        assert_eq!("MAX".parse(), Ok(Speed::Instant));
+        assert_eq!("0.001x".parse(), Ok(Speed::Accelerated(MIN_FACTOR)));
+        for bad in ["", "fast", "0x", "-2x", "infx", "NaN", "1e-20x", "0.0009"].iter() {
+            assert_eq!(bad.parse::<Speed>(), Err(ParseSpeedError(bad.to_string())), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use rand::distr::weighted::WeightedIndex;
+use rand::distr::Distribution;
+use rand::Rng;
+
+use crate::game::SecretSource;
+
+// How likely each number in the range is to become the secret.
+// Players soon learn to start at 50, making the edges more likely punishes that.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    // every number is equally likely
+    Uniform,
+    // the closer to either end of the range, the more likely, falling off linearly towards the middle
+    Edges,
+    // only the numbers in the table, each as likely as its weight says
+    Table(Table),
+}
+
+// what --distribution asked for, a file still has to be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DistributionConfig {
+    Uniform,
+    Edges,
+    File(PathBuf),
+}
+
+impl FromStr for DistributionConfig {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<DistributionConfig, Self::Err> {
+        match s {
+            "uniform" => Ok(DistributionConfig::Uniform),
+            "edges" => Ok(DistributionConfig::Edges),
+            _ if s.starts_with("file:") && s.len() > "file:".len() => {
+                Ok(DistributionConfig::File(PathBuf::from(&s["file:".len()..])))
+            }
+            _ => Err("distribution must be uniform, edges or file:<path>"),
+        }
+    }
+}
+
+impl DistributionConfig {
+    pub fn shape(&self, range: &RangeInclusive<u32>) -> Result<Shape, TableError> {
+        match self {
+            DistributionConfig::Uniform => Ok(Shape::Uniform),
+            DistributionConfig::Edges => Ok(Shape::Edges),
+            DistributionConfig::File(path) => Table::load(path, range).map(Shape::Table),
+        }
+    }
+}
+
+// A table of possible secrets, one per line: the number and optionally its weight
+// (1 when left out). Empty lines and lines starting with # are ignored.
+//
+//   # mostly 1 and 100, sometimes 42
+//   1 10
+//   100 10
+//   42
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    entries: Vec<(u32, f64)>,
+}
+
+#[derive(Debug)]
+pub enum TableError {
+    Io(PathBuf, io::Error),
+    BadLine { line: usize, content: String },
+    OutOfRange { line: usize, value: u32 },
+    // no number in the table can be picked
+    Empty,
+    // the weights add up to more than an f64 can hold
+    TooHeavy,
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableError::Io(path, e) => write!(f, "could not read {}: {}", path.display(), e),
+            TableError::BadLine { line, content } => {
+                write!(f, "line {}: expected a number and an optional weight, got '{}'", line, content)
+            }
+            TableError::OutOfRange { line, value } => {
+                write!(f, "line {}: {} is outside the range of the game", line, value)
+            }
+            TableError::Empty => write!(f, "the table needs at least one number with a weight above 0"),
+            TableError::TooHeavy => write!(f, "the weights add up to too much, make them smaller"),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+impl Table {
+    pub fn load(path: &Path, range: &RangeInclusive<u32>) -> Result<Table, TableError> {
+        let contents = fs::read_to_string(path).map_err(|e| TableError::Io(path.to_path_buf(), e))?;
+        Table::parse(&contents, range)
+    }
+
+    pub fn parse(contents: &str, range: &RangeInclusive<u32>) -> Result<Table, TableError> {
+        let mut entries = Vec::new();
+
+        for (i, line) in contents.lines().enumerate() {
+            let line_number = i + 1;
+            let content = line.trim();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+
+            let bad_line = || TableError::BadLine { line: line_number, content: content.to_string() };
+            let fields: Vec<&str> = content.split_whitespace().collect();
+            let value: u32 = fields[0].parse().map_err(|_| bad_line())?;
+            let weight: f64 = match fields.get(1) {
+                Some(weight) => weight.parse().map_err(|_| bad_line())?,
+                None => 1.0,
+            };
+            if fields.len() > 2 || !weight.is_finite() || weight < 0.0 {
+                return Err(bad_line());
+            }
+            if !range.contains(&value) {
+                return Err(TableError::OutOfRange { line: line_number, value });
+            }
+
+            entries.push((value, weight));
+        }
+
+        if entries.iter().all(|(_, weight)| *weight == 0.0) {
+            return Err(TableError::Empty);
+        }
+        if !entries.iter().map(|(_, weight)| weight).sum::<f64>().is_finite() {
+            return Err(TableError::TooHeavy);
+        }
+        Ok(Table { entries })
+    }
+}
+
+// A SecretSource that picks the secret with the given Shape, using `rng` for the randomness
+pub struct Shaped<R> {
+    pub rng: R,
+    pub shape: Shape,
+}
+
+impl<R: Rng> SecretSource for Shaped<R> {
+    fn secret_in(&mut self, range: &RangeInclusive<u32>) -> u32 {
+        match &self.shape {
+            Shape::Uniform => self.rng.random_range(range.clone()),
+            Shape::Edges => edges(&mut self.rng, range),
+            Shape::Table(table) => {
+                // Table::parse made sure there is at least one weight above 0 and that they
+                // don't add up to infinity
+                let weights = WeightedIndex::new(table.entries.iter().map(|(_, weight)| *weight))
+                    .expect("table has a positive weight");
+                table.entries[weights.sample(&mut self.rng)].0
+            }
+        }
+    }
+}
+
+// Fold the range in half: the distance from the nearest edge has a linearly falling
+// density, which 1 - sqrt(u) gives for a uniform u in [0, 1). Then pick a side.
+fn edges<R: Rng>(rng: &mut R, range: &RangeInclusive<u32>) -> u32 {
+    let (start, end) = (*range.start() as u64, *range.end() as u64);
+    if start >= end {
+        return start as u32;
+    }
+
+    let half = (end - start) / 2 + 1;
+    let u: f64 = rng.random();
+    let distance = ((half as f64 * (1.0 - u.sqrt())) as u64).min(half - 1);
+
+    let value = if rng.random_bool(0.5) { start + distance } else { end - distance };
+    value as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::seeded;
+    use crate::DEFAULT_RANGE;
+
+    fn histogram(shape: Shape, range: RangeInclusive<u32>, samples: usize) -> Vec<usize> {
+        let mut source = Shaped { rng: seeded(1), shape };
+        let mut counts = vec![0; (*range.end() - *range.start()) as usize + 1];
+        for _ in 0..samples {
+            let secret = source.secret_in(&range);
+            assert!(range.contains(&secret));
+            counts[(secret - *range.start()) as usize] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn uniform_covers_the_whole_range() {
+        let counts = histogram(Shape::Uniform, 1..=10, 10_000);
+        // each number should come up about 1000 times
+        assert!(counts.iter().all(|count| (800..1200).contains(count)), "{:?}", counts);
+    }
+
+    #[test]
+    fn edges_are_more_likely_than_the_middle() {
+        let counts = histogram(Shape::Edges, DEFAULT_RANGE, 100_000);
+        let outer: usize = counts[..10].iter().chain(&counts[90..]).sum();
+        let inner: usize = counts[40..60].iter().sum();
+        // the outer 20 numbers should be picked about 9 times as often as the middle 20
+        assert!(outer > inner * 6, "outer {} inner {}", outer, inner);
+        assert!(counts[0] > 0 && counts[99] > 0);
+    }
+
+    #[test]
+    fn edges_handle_tiny_and_huge_ranges() {
+        let mut source = Shaped { rng: seeded(2), shape: Shape::Edges };
+        assert_eq!(source.secret_in(&(5..=5)), 5);
+        for _ in 0..100 {
+            assert!((1..=2).contains(&source.secret_in(&(1..=2))));
+            source.secret_in(&(0..=u32::MAX));
+        }
+    }
+
+    #[test]
+    fn table_follows_its_weights() {
+        let table = Table::parse("# edges mostly\n1 3\n\n100 1\n50 0\n", &DEFAULT_RANGE).unwrap();
+        let counts = histogram(Shape::Table(table), DEFAULT_RANGE, 10_000);
+        assert_eq!(counts[49], 0);
+        assert_eq!(counts[0] + counts[99], 10_000);
+        assert!((7_000..8_000).contains(&counts[0]), "{}", counts[0]);
+
+        // huge weights are fine as long as their total still fits
+        let table = Table::parse("1 8e307\n2 8e307\n", &DEFAULT_RANGE).unwrap();
+        let counts = histogram(Shape::Table(table), DEFAULT_RANGE, 1_000);
+        assert_eq!(counts[0] + counts[1], 1_000);
+    }
+
+    #[test]
+    fn table_errors() {
+        let range = 1..=10;
+        assert!(matches!(
+            Table::parse("1\nten\n", &range),
+            Err(TableError::BadLine { line: 2, .. })
+        ));
+        assert!(matches!(Table::parse("1 -2", &range), Err(TableError::BadLine { line: 1, .. })));
+        assert!(matches!(Table::parse("1 2 3", &range), Err(TableError::BadLine { line: 1, .. })));
+        assert!(matches!(
+            Table::parse("11", &range),
+            Err(TableError::OutOfRange { line: 1, value: 11 })
+        ));
+        assert!(matches!(Table::parse("# nothing\n", &range), Err(TableError::Empty)));
+        assert!(matches!(Table::parse("3 0", &range), Err(TableError::Empty)));
+        assert!(matches!(Table::parse("1 1e308\n2 1e308", &range), Err(TableError::TooHeavy)));
+        assert!(matches!(
+            Table::load(Path::new("/does/not/exist"), &range),
+            Err(TableError::Io(_, _))
+        ));
+    }
+
+    #[test]
+    fn distribution_names() {
+        assert_eq!("uniform".parse(), Ok(DistributionConfig::Uniform));
+        assert_eq!("edges".parse(), Ok(DistributionConfig::Edges));
+        assert_eq!(
+            "file:secrets.txt".parse(),
+            Ok(DistributionConfig::File(PathBuf::from("secrets.txt")))
+        );
+        assert!("file:".parse::<DistributionConfig>().is_err());
+        assert!("normal".parse::<DistributionConfig>().is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::variant::{Classic, Variant};
+use crate::Guess;
+
+// Anything that can pick the secret number. Every rand Rng already can, so
+// rand::rng() is used when playing for real and a seeded StdRng when we want
+// the same secret number every time (tests, replays).
+// distribution::Shaped wraps an Rng to make some numbers more likely than others.
+pub trait SecretSource {
+    fn secret_in(&mut self, range: &RangeInclusive<u32>) -> u32;
+}
+
+impl<R: Rng> SecretSource for R {
+    fn secret_in(&mut self, range: &RangeInclusive<u32>) -> u32 {
+        // random_range takes the inclusive range as it is, the end can be picked too
+        self.random_range(range.clone())
+    }
+}
+
+// the same seed always gives the same sequence of secret numbers
+pub fn seeded(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome<S = u32> {
+    pub secret: S,
+    // valid guesses only, typos and out of range numbers don't count
+    pub attempts: u32,
+    // false when the input ran out before the number was found
+    pub won: bool,
+}
+
+// One round of some Variant of the game, the classic number guessing by default
+pub struct Game<V: Variant = Classic> {
+    variant: V,
+    secret: V::Secret,
+}
+
+impl Game {
+    pub fn new<S: SecretSource>(range: RangeInclusive<u32>, source: &mut S) -> Game {
+        Game::with_variant(Classic { range }, source)
+    }
+
+    pub fn range(&self) -> RangeInclusive<u32> {
+        self.variant.range.clone()
+    }
+
+    // how the guess compares to the secret number, Less means the guess is too small
+    pub fn check(&self, guess: Guess) -> Ordering {
+        self.variant.feedback(&self.secret, &guess)
+    }
+}
+
+impl<V: Variant> Game<V> {
+    pub fn with_variant<S: SecretSource>(variant: V, source: &mut S) -> Game<V> {
+        let secret = variant.new_secret(source);
+        Game { variant, secret }
+    }
+
+    pub fn secret(&self) -> &V::Secret {
+        &self.secret
+    }
+
+    pub fn intro(&self) -> String {
+        self.variant.intro()
+    }
+
+    // The game loop only talks to the player through BufRead and Write, so it works the
+    // same with stdin/stdout as with a byte slice of scripted input and a Vec<u8> transcript
+    pub fn play<R: BufRead, W: Write>(&self, mut input: R, mut output: W) -> io::Result<Outcome<V::Secret>> {
+        let mut attempts = 0;
+
+        // loop keyword creates an infinite loop
+        loop {
+            writeln!(output, "Please input your guess.")?;
+
+            // let creates a new variable and mut means the variable is mutable
+            // by default, a variable is immutable
+            // String::new() creates a new String, utf-8 encoded bit of text
+            // :: indicates that new is an associated function of the String type.
+            // So new is a static method of String type
+            let mut line = String::new();
+
+            // read_line takes whatever types into the input and place it into a String
+            // & means this argument is a reference, which gives you a way to let multiple parts
+            // of your code access to one piece of data without needing to copy that data into memory
+            // multiple times. References are immutable by default so &mut line makes it mutable
+            // ? hands an io::Error back to the caller, and read_line returns Ok(0) once
+            // there is nothing left to read
+            if input.read_line(&mut line)? == 0 {
+                return Ok(Outcome { secret: self.secret.clone(), attempts, won: false });
+            }
+
+            // rather than crashing the program when the user inputs a non-number, let's
+            // tell the player what was wrong and let them continue guessing (String => Guess)
+            let guess = match self.variant.parse_guess(&line) {
+                Ok(guess) => guess,
+                Err(e) => {
+                    writeln!(output, "{}", e)?;
+                    continue;
+                }
+            };
+            attempts += 1;
+
+            writeln!(output, "You guessed: {}", guess)?;
+
+            let feedback = self.variant.feedback(&self.secret, &guess);
+            if self.variant.is_solved(&feedback) {
+                writeln!(output, "You win!")?;
+                return Ok(Outcome { secret: self.secret.clone(), attempts, won: true });
+            }
+            writeln!(output, "{}", self.variant.hint(&feedback))?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::BullsAndCows;
+    use crate::DEFAULT_RANGE;
+
+    // a SecretSource that always picks the same number, handy for exact transcripts
+    struct Fixed(u32);
+
+    impl SecretSource for Fixed {
+        fn secret_in(&mut self, _range: &RangeInclusive<u32>) -> u32 {
+            self.0
+        }
+    }
+
+    struct Lowest;
+
+    impl SecretSource for Lowest {
+        fn secret_in(&mut self, range: &RangeInclusive<u32>) -> u32 {
+            *range.start()
+        }
+    }
+
+    fn transcript(game: &Game, input: &str) -> (Outcome, String) {
+        transcript_of(game, input)
+    }
+
+    fn transcript_of<V: Variant>(game: &Game<V>, input: &str) -> (Outcome<V::Secret>, String) {
+        let mut output = Vec::new();
+        let outcome = game.play(input.as_bytes(), &mut output).unwrap();
+        (outcome, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn plays_a_full_game() {
+        let game = Game::new(DEFAULT_RANGE, &mut Fixed(42));
+        let (outcome, output) = transcript(&game, "50\n25\n42\n");
+
+        assert_eq!(outcome, Outcome { secret: 42, attempts: 3, won: true });
+        assert_eq!(
+            output,
+            "Please input your guess.\n\
+             You guessed: 50\n\
+             Too big!\n\
+             Please input your guess.\n\
+             You guessed: 25\n\
+             Too small!\n\
+             Please input your guess.\n\
+             You guessed: 42\n\
+             You win!\n"
+        );
+    }
+
+    #[test]
+    fn invalid_input_is_reported_and_not_counted() {
+        let game = Game::new(DEFAULT_RANGE, &mut Fixed(7));
+        let (outcome, output) = transcript(&game, "seven\n700\n7\n");
+
+        assert_eq!(outcome, Outcome { secret: 7, attempts: 1, won: true });
+        assert_eq!(
+            output,
+            "Please input your guess.\n\
+             'seven' is not a number, please type a whole number\n\
+             Please input your guess.\n\
+             Guess value must be between 1 and 100, got 700\n\
+             Please input your guess.\n\
+             You guessed: 7\n\
+             You win!\n"
+        );
+    }
+
+    #[test]
+    fn running_out_of_input_loses() {
+        let game = Game::new(DEFAULT_RANGE, &mut Fixed(99));
+        let (outcome, output) = transcript(&game, "1\n");
+
+        assert_eq!(outcome, Outcome { secret: 99, attempts: 1, won: false });
+        assert!(output.ends_with("Too small!\nPlease input your guess.\n"));
+    }
+
+    #[test]
+    fn same_seed_same_secret() {
+        let first = Game::new(DEFAULT_RANGE, &mut seeded(2018));
+        let second = Game::new(DEFAULT_RANGE, &mut seeded(2018));
+        assert_eq!(first.secret(), second.secret());
+        assert!(DEFAULT_RANGE.contains(first.secret()));
+    }
+
+    #[test]
+    fn seeded_game_can_be_replayed_by_bisecting() {
+        let game = Game::new(DEFAULT_RANGE, &mut seeded(7));
+
+        // play a whole game by halving the range after every answer,
+        // feeding each guess back in as if the player typed it
+        let (mut low, mut high) = (1, 100);
+        let mut script = String::new();
+        loop {
+            let mid = (low + high) / 2;
+            script.push_str(&format!("{}\n", mid));
+            match mid.cmp(game.secret()) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid - 1,
+                Ordering::Equal => break,
+            }
+        }
+
+        let (outcome, output) = transcript(&game, &script);
+        assert!(outcome.won);
+        assert!(outcome.attempts <= 7);
+        assert_eq!(output.matches("You guessed:").count() as u32, outcome.attempts);
+        assert!(output.ends_with(&format!("You guessed: {}\nYou win!\n", game.secret())));
+    }
+
+    #[test]
+    fn plays_other_variants_with_the_same_loop() {
+        // always picking the lowest digit that is left gives the code 0123
+        let game = Game::with_variant(BullsAndCows::new(4), &mut Lowest);
+        let (outcome, output) = transcript_of(&game, "0132\n01\n0123\n");
+
+        assert_eq!(outcome.attempts, 2);
+        assert!(outcome.won);
+        assert_eq!(outcome.secret.to_string(), "0123");
+        assert_eq!(
+            output,
+            "Please input your guess.\n\
+             You guessed: 0132\n\
+             2 bulls, 2 cows\n\
+             Please input your guess.\n\
+             The code has 4 digits, you typed 2\n\
+             Please input your guess.\n\
+             You guessed: 0123\n\
+             You win!\n"
+        );
+    }
+
+    #[test]
+    fn whole_range_can_be_picked() {
+        let mut rng = seeded(1);
+        for _ in 0..1000 {
+            let secret = rng.secret_in(&(1..=2));
+            assert!(secret == 1 || secret == 2);
+        }
+        assert_eq!(rng.secret_in(&(u32::MAX..=u32::MAX)), u32::MAX);
+    }
+}
@@ -0,0 +1,410 @@
+use std::env;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub mod distribution;
+pub mod game;
+pub mod net;
+pub mod scores;
+pub mod solver;
+pub mod variant;
+
+use crate::distribution::DistributionConfig;
+use crate::variant::VariantKind;
+
+// The classic game asks for a number between 1 and 100
+pub const DEFAULT_RANGE: RangeInclusive<u32> = 1..=100;
+
+// how big the range of secret numbers is, scores are only compared within one difficulty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn range(self) -> RangeInclusive<u32> {
+        match self {
+            Difficulty::Easy => 1..=10,
+            Difficulty::Normal => DEFAULT_RANGE,
+            Difficulty::Hard => 1..=1000,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Difficulty, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err("difficulty must be easy, normal or hard"),
+        }
+    }
+}
+
+// A Guess can only be created through Guess::new or Guess::parse, so any Guess
+// we hold is known to be inside the range the game was configured with.
+// This is the Guess from Chapter 9/11, but returning a Result rather than panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Guess {
+    value: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessError {
+    // the input could not be parsed as a whole number at all
+    NotANumber(String),
+    // the input was a number, but outside the configured range
+    OutOfRange { value: u32, min: u32, max: u32 },
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuessError::NotANumber(input) => {
+                write!(f, "'{}' is not a number, please type a whole number", input)
+            }
+            GuessError::OutOfRange { value, min, max } => write!(
+                f,
+                "Guess value must be between {} and {}, got {}",
+                min, max, value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GuessError {}
+
+impl fmt::Display for Guess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Guess {
+    pub fn new(value: u32, range: &RangeInclusive<u32>) -> Result<Guess, GuessError> {
+        if !range.contains(&value) {
+            return Err(GuessError::OutOfRange {
+                value,
+                min: *range.start(),
+                max: *range.end(),
+            });
+        }
+
+        Ok(Guess { value })
+    }
+
+    // parse whatever the player typed, surrounding whitespace (and the newline
+    // left by read_line) is ignored
+    pub fn parse(input: &str, range: &RangeInclusive<u32>) -> Result<Guess, GuessError> {
+        let input = input.trim();
+        let value = input
+            .parse()
+            .map_err(|_| GuessError::NotANumber(input.to_string()))?;
+
+        Guess::new(value, range)
+    }
+
+    // getter, the field stays private so a Guess can't be built around validation
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+}
+
+// what the program was asked to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    // the classic game, the player guesses
+    Play,
+    // print the leaderboard instead of playing
+    Scores,
+    // the player thinks of the number and the computer guesses
+    Computer,
+    // host a game on the LAN, `players` have to join before it starts
+    Serve { port: u16, players: usize },
+    // take part in a game hosted by `guessing-game serve` at this address
+    Join(String),
+}
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+// command line options, parsed the same way minigrep parses its arguments
+#[derive(Debug, PartialEq, Eq)]
+pub struct Config {
+    pub mode: Mode,
+    // which game to play in Mode::Play
+    pub variant: VariantKind,
+    // play with a fixed secret number sequence instead of rand::rng()
+    pub seed: Option<u64>,
+    // how the secret number is picked from the range
+    pub distribution: DistributionConfig,
+    pub player: String,
+    pub difficulty: Difficulty,
+    pub score_file: PathBuf,
+    // print the secret before playing, for debugging
+    pub debug: bool,
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, &'static str> {
+        let mut config = Config {
+            mode: Mode::Play,
+            variant: VariantKind::Classic,
+            seed: None,
+            distribution: DistributionConfig::Uniform,
+            // the login name is a good guess for who is playing
+            player: env::var("USER").unwrap_or_else(|_| String::from("player")),
+            difficulty: Difficulty::Normal,
+            score_file: default_score_file(),
+            debug: false,
+        };
+        // skip the program name
+        let mut args = args.iter().skip(1).peekable();
+
+        // `serve` and `join <address>` are subcommands, everything else is an --option
+        match args.peek().map(|arg| arg.as_str()) {
+            Some("serve") => {
+                args.next();
+                config.mode = Mode::Serve { port: DEFAULT_PORT, players: 2 };
+            }
+            Some("join") => {
+                args.next();
+                let address = args.next().ok_or("join needs the address of the server")?;
+                config.mode = Mode::Join(address.clone());
+            }
+            _ => {}
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let seed = args.next().ok_or("--seed needs a value")?;
+                    config.seed = Some(seed.parse().map_err(|_| "--seed must be a whole number")?);
+                }
+                "--distribution" => {
+                    config.distribution = args.next().ok_or("--distribution needs a value")?.parse()?;
+                }
+                "--scores" => config.mode = Mode::Scores,
+                "--computer" => config.mode = Mode::Computer,
+                "--debug" => config.debug = true,
+                "--variant" => {
+                    config.variant = args.next().ok_or("--variant needs a value")?.parse()?;
+                }
+                "--name" => {
+                    config.player = args.next().ok_or("--name needs a value")?.clone();
+                }
+                "--difficulty" => {
+                    config.difficulty = args.next().ok_or("--difficulty needs a value")?.parse()?;
+                }
+                "--score-file" => {
+                    config.score_file = PathBuf::from(args.next().ok_or("--score-file needs a value")?);
+                }
+                "--port" => {
+                    let value = args.next().ok_or("--port needs a value")?;
+                    match &mut config.mode {
+                        Mode::Serve { port, .. } => {
+                            *port = value.parse().map_err(|_| "--port must be a number up to 65535")?
+                        }
+                        _ => return Err("--port only works with serve"),
+                    }
+                }
+                "--players" => {
+                    let value = args.next().ok_or("--players needs a value")?;
+                    match &mut config.mode {
+                        Mode::Serve { players, .. } => {
+                            *players = match value.parse() {
+                                Ok(n) if n > 0 => n,
+                                _ => return Err("--players must be at least 1"),
+                            }
+                        }
+                        _ => return Err("--players only works with serve"),
+                    }
+                }
+                _ => return Err("unknown argument"),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+// scores live in the home directory, or the current one if there is no HOME
+fn default_score_file() -> PathBuf {
+    let dir = env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    dir.join(".guessing-game-scores")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_values_inside_the_range() {
+        assert_eq!(Guess::new(1, &DEFAULT_RANGE).unwrap().value(), 1);
+        assert_eq!(Guess::new(100, &DEFAULT_RANGE).unwrap().value(), 100);
+    }
+
+    #[test]
+    fn rejects_values_outside_the_range() {
+        assert_eq!(
+            Guess::new(200, &DEFAULT_RANGE),
+            Err(GuessError::OutOfRange { value: 200, min: 1, max: 100 })
+        );
+        assert_eq!(
+            Guess::new(0, &DEFAULT_RANGE),
+            Err(GuessError::OutOfRange { value: 0, min: 1, max: 100 })
+        );
+    }
+
+    #[test]
+    fn error_message_names_the_configured_range() {
+        let err = Guess::new(11, &(1..=10)).unwrap_err();
+        assert_eq!(err.to_string(), "Guess value must be between 1 and 10, got 11");
+    }
+
+    #[test]
+    fn parse_trims_input() {
+        assert_eq!(Guess::parse(" 42\n", &DEFAULT_RANGE).unwrap().value(), 42);
+    }
+
+    #[test]
+    fn parse_reports_non_numbers() {
+        assert_eq!(
+            Guess::parse("forty\n", &DEFAULT_RANGE),
+            Err(GuessError::NotANumber(String::from("forty")))
+        );
+        assert_eq!(
+            Guess::parse("-3", &DEFAULT_RANGE),
+            Err(GuessError::NotANumber(String::from("-3")))
+        );
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn config_defaults() {
+        let config = Config::new(&args(&["guessing-game"])).unwrap();
+        assert_eq!(config.mode, Mode::Play);
+        assert_eq!(config.variant, VariantKind::Classic);
+        assert_eq!(config.seed, None);
+        assert_eq!(config.distribution, DistributionConfig::Uniform);
+        assert_eq!(config.difficulty, Difficulty::Normal);
+        assert!(config.score_file.ends_with(".guessing-game-scores"));
+        assert!(!config.debug);
+        assert!(Config::new(&args(&["guessing-game", "--debug"])).unwrap().debug);
+    }
+
+    #[test]
+    fn config_reads_seed() {
+        assert_eq!(Config::new(&args(&["guessing-game", "--seed", "42"])).unwrap().seed, Some(42));
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "--seed"])),
+            Err("--seed needs a value")
+        );
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "--seed", "x"])),
+            Err("--seed must be a whole number")
+        );
+    }
+
+    #[test]
+    fn config_reads_distribution() {
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "--distribution", "edges"])).unwrap().distribution,
+            DistributionConfig::Edges
+        );
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "--distribution", "dice"])),
+            Err("distribution must be uniform, edges or file:<path>")
+        );
+    }
+
+    #[test]
+    fn config_reads_score_options() {
+        let config = Config::new(&args(&[
+            "guessing-game",
+            "--scores",
+            "--name",
+            "ferris",
+            "--difficulty",
+            "hard",
+            "--score-file",
+            "scores.tsv",
+        ]))
+        .unwrap();
+        assert_eq!(config.mode, Mode::Scores);
+        assert_eq!(config.player, "ferris");
+        assert_eq!(config.difficulty, Difficulty::Hard);
+        assert_eq!(config.score_file, PathBuf::from("scores.tsv"));
+
+        assert_eq!(Config::new(&args(&["guessing-game", "--computer"])).unwrap().mode, Mode::Computer);
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "--variant", "bulls"])).unwrap().variant,
+            VariantKind::BullsAndCows
+        );
+
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "--difficulty", "impossible"])),
+            Err("difficulty must be easy, normal or hard")
+        );
+    }
+
+    #[test]
+    fn config_reads_network_modes() {
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "serve"])).unwrap().mode,
+            Mode::Serve { port: DEFAULT_PORT, players: 2 }
+        );
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "serve", "--port", "9000", "--players", "3"]))
+                .unwrap()
+                .mode,
+            Mode::Serve { port: 9000, players: 3 }
+        );
+        let config = Config::new(&args(&["guessing-game", "join", "10.0.0.2:7878", "--name", "ferris"])).unwrap();
+        assert_eq!(config.mode, Mode::Join(String::from("10.0.0.2:7878")));
+        assert_eq!(config.player, "ferris");
+
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "join"])),
+            Err("join needs the address of the server")
+        );
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "--port", "9000"])),
+            Err("--port only works with serve")
+        );
+        assert_eq!(
+            Config::new(&args(&["guessing-game", "serve", "--players", "0"])),
+            Err("--players must be at least 1")
+        );
+    }
+
+    #[test]
+    fn difficulty_round_trips_through_its_name() {
+        for difficulty in Difficulty::ALL.iter() {
+            assert_eq!(difficulty.to_string().parse(), Ok(*difficulty));
+        }
+        assert_eq!(Difficulty::Normal.range(), DEFAULT_RANGE);
+    }
+}
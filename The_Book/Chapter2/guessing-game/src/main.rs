@@ -1,67 +1,141 @@
 // io library in Rust, and it is from standard lib
+use std::env;
 use std::io;
-use rand::Rng;
-use std::cmp::Ordering;
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use std::time::Instant;
 
-fn main() {
-    println!("Guess the number!");
-    
-    // thread_rng() gives us a rand number that is local to the current thread of exec and seeded by the OS
-    // inclusive on the lower bound, but exclusive on the upper bound
-    let secret_number = rand::thread_rng().gen_range(1, 101);
-
-    // for debugging purpose
-    println!("The secret number is: {}", secret_number);
+use guessing_game::distribution::{Shape, Shaped};
+use guessing_game::game::{self, Game, Outcome};
+use guessing_game::scores::{Score, ScoreBoard};
+use guessing_game::variant::{BullsAndCows, Classic, Variant, VariantKind, Words};
+use guessing_game::{net, solver};
+use guessing_game::{Config, Difficulty, Mode};
 
-    // loop keyword creates an infinite loop
-    loop {
-        println!("Please input your guess.");
+fn main() {
+    let args: Vec<String> = env::args().collect();
 
-        // let creates a new variable and mut means the variable is mutable 
-        // by default, a variable is immutable
-        // String::new() creates a new String, utf-8 encoded bit of text
-        // :: indicates that new is an associated function of the String type.
-        // So new is a static method of String type
-        let mut guess = String::new();
+    let config = Config::new(&args).unwrap_or_else(|err| {
+        println!("Problem parsing arguments: {}", err);
+        process::exit(1);
+    });
 
-        // stdin is an associated function, stdin, on io. 
-        // read_line takes whatever types into the std input and place it into a String
-        // & means this argument is a reference, which gives you a way to let multiple parts
-        // of your code access to one piece of data without needing to copy that data into memory
-        // multiple times. References are immutable by default so &mut guess makes it mutable
-        // .expect is handling potential failure
-        // read_line also returns a value: io::Result, which has types as enumerations(enum)
-        io::stdin().read_line(&mut guess)
-            .expect("Failed to read line");
+    // stdin is an associated function, stdin, on io.
+    // lock() gives us a handle that implements BufRead, which is all the game needs to read guesses
+    let stdin = io::stdin();
 
-        // i32, a 32-bit number; u32, an unsigned 32-bit number, i64, u64
-        // The colon(:) after guess tells Rust we'll annotate the variable type.
-        // The parse() could easily cause an error, and it returns "Result" type
-        // much as the read_line() method does
-        // rather than crashing the program when the user inputs a non-number, let's
-        // make the game ignore a non-number so the user can continue guessing (String => u32)
-        // using a match expression
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
-        };
+    match &config.mode {
+        Mode::Play => {}
+        Mode::Scores => {
+            let board = load_scores(&config);
+            board.write_leaderboard(io::stdout(), 10)
+                .expect("Failed to write scores");
+            return;
+        }
+        Mode::Computer => {
+            solver::play(config.difficulty.range(), stdin.lock(), io::stdout())
+                .expect("Failed to read line");
+            return;
+        }
+        Mode::Serve { port, players } => {
+            let listener = TcpListener::bind(("0.0.0.0", *port)).unwrap_or_else(|err| {
+                println!("Could not listen on port {}: {}", port, err);
+                process::exit(1);
+            });
+            println!("Waiting for {} players on port {}", players, port);
+            let game = new_game(Classic { range: config.difficulty.range() }, secret_shape(&config), config.seed);
+            if let Err(e) = net::serve(&listener, &game, *players, net::TIMEOUT, io::stdout()) {
+                println!("Server error: {}", e);
+                process::exit(1);
+            }
+            return;
+        }
+        Mode::Join(address) => {
+            let stream = TcpStream::connect(address).unwrap_or_else(|err| {
+                println!("Could not connect to {}: {}", address, err);
+                process::exit(1);
+            });
+            if let Err(e) = net::join(stream, &config.player, stdin.lock(), io::stdout()) {
+                println!("Connection error: {}", e);
+                process::exit(1);
+            }
+            return;
+        }
+    }
 
-        // {} is place holder for the value in guess
-        println!("You guessed: {}", guess);
+    match config.variant {
+        VariantKind::Classic => {
+            let mut board = load_scores(&config);
+            let started = Instant::now();
+            let outcome = play(Classic { range: config.difficulty.range() }, secret_shape(&config), &config);
 
-        // Ordering is another enum, but the variants for Ordering is Less, Greater, Equal
-        // match expression is made up of arms. An arm consists of a pattern and the code
-        // that should be run if the value given to the beginning of the match expression
-        // fits that arm's pattern.
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break;
+            // only the classic game keeps scores, attempts in the other variants aren't comparable,
+            // and a game played with the secret on screen doesn't count
+            if outcome.won && !config.debug {
+                board.record(Score::new(&config.player, config.difficulty, outcome.attempts, started.elapsed()));
+                if let Err(e) = board.save(&config.score_file) {
+                    println!("Could not save your score to {}: {}", config.score_file.display(), e);
+                }
             }
         }
+        VariantKind::BullsAndCows => {
+            // harder means a longer code
+            let length = match config.difficulty {
+                Difficulty::Easy => 3,
+                Difficulty::Normal => 4,
+                Difficulty::Hard => 5,
+            };
+            play(BullsAndCows::new(length), Shape::Uniform, &config);
+        }
+        VariantKind::Words => {
+            play(Words::default(), Shape::Uniform, &config);
+        }
+    }
+}
+
+// rand::rng() gives us a rand number generator that is local to the current thread of exec and seeded by the OS
+// with --seed the secret is the same on every run, so a game can be replayed
+fn new_game<V: Variant>(variant: V, shape: Shape, seed: Option<u64>) -> Game<V> {
+    match seed {
+        Some(seed) => Game::with_variant(variant, &mut Shaped { rng: game::seeded(seed), shape }),
+        None => Game::with_variant(variant, &mut Shaped { rng: rand::rng(), shape }),
+    }
+}
+
+// --distribution only makes sense for a secret number, so only the classic game uses it
+fn secret_shape(config: &Config) -> Shape {
+    config.distribution.shape(&config.difficulty.range()).unwrap_or_else(|err| {
+        println!("Problem with the distribution: {}", err);
+        process::exit(1);
+    })
+}
+
+fn play<V: Variant>(variant: V, shape: Shape, config: &Config) -> Outcome<V::Secret> {
+    let game = new_game(variant, shape, config.seed);
+    println!("{}", game.intro());
+
+    // for debugging purpose
+    if config.debug {
+        println!("The secret is: {}", game.secret());
+    }
+
+    // .expect is handling potential failure
+    let stdin = io::stdin();
+    game.play(stdin.lock(), io::stdout())
+        .expect("Failed to read line")
+}
+
+// a score file we can't read at all shouldn't stop anyone from playing,
+// we just start from an empty board (and won't be able to save to it either)
+fn load_scores(config: &Config) -> ScoreBoard {
+    let board = ScoreBoard::load(&config.score_file).unwrap_or_else(|err| {
+        println!("Could not read scores from {}: {}", config.score_file.display(), err);
+        ScoreBoard::default()
+    });
+    if board.skipped() > 0 {
+        println!("Ignored {} unreadable line(s) in {}", board.skipped(), config.score_file.display());
     }
+    board
 }
 
 // By default, Rust brings only a few types into the scope of every program in the prelude
@@ -0,0 +1,638 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::game::Game;
+use crate::Guess;
+
+// A small line based protocol, every message is one line of text.
+// Names always come last on a line so they may contain spaces.
+//
+//   client -> server          server -> client
+//   NAME <name>               WELCOME <min> <max> <name>
+//   GUESS <number>            WAITING <joined> <needed>
+//   QUIT                      START <players>
+//                             TURN
+//                             WAIT <name>
+//                             RESULT <number> <LESS|GREATER|EQUAL> <name>
+//                             ERROR <message>
+//                             LEFT <name>
+//                             WINNER <attempts> <name>
+//                             BYE
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientMessage {
+    Name(String),
+    Guess(String),
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerMessage {
+    Welcome { min: u32, max: u32, name: String },
+    Waiting { joined: usize, needed: usize },
+    Start { players: usize },
+    Turn,
+    Wait { name: String },
+    Result { guess: u32, ordering: Ordering, name: String },
+    Error(String),
+    Left { name: String },
+    Winner { attempts: u32, name: String },
+    Bye,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolError(pub String);
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unexpected message: {}", self.0)
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<ProtocolError> for io::Error {
+    fn from(e: ProtocolError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+// split "COMMAND rest of the line" into its two parts
+fn command(line: &str) -> (&str, &str) {
+    let line = line.trim_end_matches(&['\r', '\n'][..]);
+    match line.find(' ') {
+        Some(space) => (&line[..space], &line[space + 1..]),
+        None => (line, ""),
+    }
+}
+
+impl fmt::Display for ClientMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientMessage::Name(name) => write!(f, "NAME {}", name),
+            ClientMessage::Guess(guess) => write!(f, "GUESS {}", guess),
+            ClientMessage::Quit => write!(f, "QUIT"),
+        }
+    }
+}
+
+impl FromStr for ClientMessage {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<ClientMessage, ProtocolError> {
+        match command(line) {
+            ("NAME", name) if !name.trim().is_empty() => Ok(ClientMessage::Name(name.trim().to_string())),
+            // the guess is checked by the server with Guess::parse, like any other input
+            ("GUESS", guess) => Ok(ClientMessage::Guess(guess.to_string())),
+            ("QUIT", "") => Ok(ClientMessage::Quit),
+            _ => Err(ProtocolError(line.trim_end().to_string())),
+        }
+    }
+}
+
+fn ordering_name(ordering: Ordering) -> &'static str {
+    match ordering {
+        Ordering::Less => "LESS",
+        Ordering::Greater => "GREATER",
+        Ordering::Equal => "EQUAL",
+    }
+}
+
+impl fmt::Display for ServerMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerMessage::Welcome { min, max, name } => write!(f, "WELCOME {} {} {}", min, max, name),
+            ServerMessage::Waiting { joined, needed } => write!(f, "WAITING {} {}", joined, needed),
+            ServerMessage::Start { players } => write!(f, "START {}", players),
+            ServerMessage::Turn => write!(f, "TURN"),
+            ServerMessage::Wait { name } => write!(f, "WAIT {}", name),
+            ServerMessage::Result { guess, ordering, name } => {
+                write!(f, "RESULT {} {} {}", guess, ordering_name(*ordering), name)
+            }
+            ServerMessage::Error(message) => write!(f, "ERROR {}", message),
+            ServerMessage::Left { name } => write!(f, "LEFT {}", name),
+            ServerMessage::Winner { attempts, name } => write!(f, "WINNER {} {}", attempts, name),
+            ServerMessage::Bye => write!(f, "BYE"),
+        }
+    }
+}
+
+impl FromStr for ServerMessage {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<ServerMessage, ProtocolError> {
+        let error = || ProtocolError(line.trim_end().to_string());
+        let (cmd, rest) = command(line);
+        // splitn keeps whatever comes after the numbers together as the name
+        let fields: Vec<&str> = rest.splitn(3, ' ').collect();
+        let number = |i: usize| fields.get(i).and_then(|field| field.parse().ok()).ok_or_else(error);
+        let name = |i: usize| fields.get(i).map(|name| name.to_string()).ok_or_else(error);
+
+        let message = match cmd {
+            "WELCOME" => ServerMessage::Welcome { min: number(0)?, max: number(1)?, name: name(2)? },
+            "WAITING" => ServerMessage::Waiting {
+                joined: number(0)? as usize,
+                needed: number(1)? as usize,
+            },
+            "START" => ServerMessage::Start { players: number(0)? as usize },
+            "TURN" => ServerMessage::Turn,
+            "WAIT" => ServerMessage::Wait { name: rest.to_string() },
+            "RESULT" => {
+                let ordering = match fields.get(1) {
+                    Some(&"LESS") => Ordering::Less,
+                    Some(&"GREATER") => Ordering::Greater,
+                    Some(&"EQUAL") => Ordering::Equal,
+                    _ => return Err(error()),
+                };
+                ServerMessage::Result { guess: number(0)?, ordering, name: name(2)? }
+            }
+            "ERROR" => ServerMessage::Error(rest.to_string()),
+            "LEFT" => ServerMessage::Left { name: rest.to_string() },
+            "WINNER" => {
+                let fields: Vec<&str> = rest.splitn(2, ' ').collect();
+                let attempts = fields[0].parse().map_err(|_| error())?;
+                ServerMessage::Winner { attempts, name: fields.get(1).ok_or_else(error)?.to_string() }
+            }
+            "BYE" => ServerMessage::Bye,
+            _ => return Err(error()),
+        };
+        Ok(message)
+    }
+}
+
+// How long the server waits for a player to send or take a line before counting them as
+// gone, so one silent client can't hold up everybody else
+pub const TIMEOUT: Duration = Duration::from_secs(300);
+
+struct Player {
+    name: String,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    attempts: u32,
+    // false once a write failed or the player stopped answering, `leave` takes them out
+    connected: bool,
+}
+
+impl Player {
+    fn send(&mut self, message: &ServerMessage) {
+        if self.connected && writeln!(self.writer, "{}", message).is_err() {
+            self.connected = false;
+        }
+    }
+
+    // None when the player hung up, took too long or sent something we don't understand
+    fn receive(&mut self) -> Option<ClientMessage> {
+        if !self.connected {
+            return None;
+        }
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => line.parse().ok(),
+        }
+    }
+}
+
+// Tell everyone. Players we can no longer write to are taken out by `leave`.
+fn broadcast(players: &mut [Player], message: &ServerMessage) {
+    for player in players.iter_mut() {
+        player.send(message);
+    }
+}
+
+// Removes the items that are `gone` and moves `turn` along with the ones that stay, so it
+// still points at the same item, or the one after it if that one is gone
+fn remove_gone<T, F: Fn(&T) -> bool>(items: &mut Vec<T>, turn: &mut usize, gone: F) -> Vec<T> {
+    let mut removed = Vec::new();
+    let mut i = 0;
+    while i < items.len() {
+        if gone(&items[i]) {
+            if i < *turn {
+                *turn -= 1;
+            }
+            removed.push(items.remove(i));
+        } else {
+            i += 1;
+        }
+    }
+    removed
+}
+
+// The one place players leave the game: everybody is told, which may lose more players
+fn leave<W: Write>(players: &mut Vec<Player>, turn: &mut usize, log: &mut W) -> io::Result<()> {
+    loop {
+        let gone = remove_gone(players, turn, |player| !player.connected);
+        if gone.is_empty() {
+            return Ok(());
+        }
+        for player in gone {
+            writeln!(log, "{} left", player.name)?;
+            broadcast(players, &ServerMessage::Left { name: player.name });
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerOutcome {
+    // name and number of guesses of the winner, None if everybody left
+    pub winner: Option<(String, u32)>,
+}
+
+// Host one game: wait until `needed` players joined, then let them guess the
+// same secret number one after another until somebody finds it.
+// Progress is logged to `log` (stdout for the real server). A player who says nothing
+// for `timeout` (TIMEOUT for the real server) is out.
+pub fn serve<W: Write>(
+    listener: &TcpListener,
+    game: &Game,
+    needed: usize,
+    timeout: Duration,
+    mut log: W,
+) -> io::Result<ServerOutcome> {
+    let range = game.range();
+    let mut players: Vec<Player> = Vec::new();
+
+    while players.len() < needed {
+        let (stream, address) = listener.accept()?;
+        // the clone made for reading shares these with the stream
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        // the first line from a new connection has to be its name
+        let mut line = String::new();
+        let name = match reader.read_line(&mut line).ok().map(|_| line.parse()) {
+            Some(Ok(ClientMessage::Name(name))) => name,
+            _ => {
+                writeln!(log, "{} did not say its name, hanging up", address)?;
+                continue;
+            }
+        };
+
+        let mut player = Player { name, reader, writer: stream, attempts: 0, connected: true };
+        // the client tells whether it won by the name of the winner, so names have to differ
+        if players.iter().any(|other| other.name == player.name) {
+            writeln!(log, "{} from {} is already playing, hanging up", player.name, address)?;
+            player.send(&ServerMessage::Error(format!("The name {} is taken, please pick another one", player.name)));
+            player.send(&ServerMessage::Bye);
+            continue;
+        }
+        let welcome = ServerMessage::Welcome {
+            min: *range.start(),
+            max: *range.end(),
+            name: player.name.clone(),
+        };
+        player.send(&welcome);
+        if !player.connected {
+            continue;
+        }
+        writeln!(log, "{} joined from {}", player.name, address)?;
+        players.push(player);
+
+        let joined = players.len();
+        broadcast(&mut players, &ServerMessage::Waiting { joined, needed });
+        leave(&mut players, &mut 0, &mut log)?;
+    }
+
+    let count = players.len();
+    broadcast(&mut players, &ServerMessage::Start { players: count });
+    writeln!(log, "Starting with {} players", count)?;
+
+    let mut turn = 0;
+    loop {
+        leave(&mut players, &mut turn, &mut log)?;
+        if players.is_empty() {
+            break;
+        }
+        turn %= players.len();
+        let name = players[turn].name.clone();
+
+        for (i, player) in players.iter_mut().enumerate() {
+            let message = if i == turn {
+                ServerMessage::Turn
+            } else {
+                ServerMessage::Wait { name: name.clone() }
+            };
+            player.send(&message);
+        }
+
+        let input = match players[turn].receive() {
+            Some(ClientMessage::Guess(input)) => input,
+            // QUIT, garbage, a closed connection or no answer in time all mean the player is out
+            _ => {
+                players[turn].connected = false;
+                continue;
+            }
+        };
+
+        let guess = match Guess::parse(&input, &range) {
+            Ok(guess) => guess,
+            Err(e) => {
+                // same player goes again, a typo doesn't cost a turn
+                players[turn].send(&ServerMessage::Error(e.to_string()));
+                continue;
+            }
+        };
+        players[turn].attempts += 1;
+        let attempts = players[turn].attempts;
+
+        let ordering = game.check(guess);
+        writeln!(log, "{} guessed {}", name, guess.value())?;
+        broadcast(&mut players, &ServerMessage::Result { guess: guess.value(), ordering, name: name.clone() });
+
+        if ordering == Ordering::Equal {
+            writeln!(log, "{} wins after {} guesses", name, attempts)?;
+            broadcast(&mut players, &ServerMessage::Winner { attempts, name: name.clone() });
+            broadcast(&mut players, &ServerMessage::Bye);
+            return Ok(ServerOutcome { winner: Some((name, attempts)) });
+        }
+
+        turn += 1;
+    }
+
+    writeln!(log, "Everybody left, nobody wins, the secret number was {}", game.secret())?;
+    Ok(ServerOutcome { winner: None })
+}
+
+// The client side: print what the server says and, when it is our turn, read a
+// guess from `input` and send it. Returns once the server says BYE.
+pub fn join<R: BufRead, W: Write>(
+    stream: TcpStream,
+    name: &str,
+    mut input: R,
+    mut output: W,
+) -> io::Result<()> {
+    let mut server = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+    writeln!(stream, "{}", ClientMessage::Name(name.to_string()))?;
+
+    loop {
+        let mut line = String::new();
+        if server.read_line(&mut line)? == 0 {
+            writeln!(output, "The server went away.")?;
+            return Ok(());
+        }
+
+        match line.parse::<ServerMessage>()? {
+            ServerMessage::Welcome { min, max, name } => {
+                writeln!(output, "Welcome {}! Guess the number between {} and {}.", name, min, max)?
+            }
+            ServerMessage::Waiting { joined, needed } => {
+                writeln!(output, "Waiting for players ({}/{} joined)...", joined, needed)?
+            }
+            ServerMessage::Start { players } => writeln!(output, "All {} players are here, let's go!", players)?,
+            ServerMessage::Turn => {
+                writeln!(output, "Your turn, please input your guess.")?;
+                let mut guess = String::new();
+                let message = if input.read_line(&mut guess)? == 0 {
+                    ClientMessage::Quit
+                } else {
+                    ClientMessage::Guess(guess.trim().to_string())
+                };
+                writeln!(stream, "{}", message)?;
+                if message == ClientMessage::Quit {
+                    return Ok(());
+                }
+            }
+            ServerMessage::Wait { name } => writeln!(output, "Waiting for {}...", name)?,
+            ServerMessage::Result { guess, ordering, name } => {
+                let verdict = match ordering {
+                    Ordering::Less => "Too small!",
+                    Ordering::Greater => "Too big!",
+                    Ordering::Equal => "Correct!",
+                };
+                writeln!(output, "{} guessed {}: {}", name, guess, verdict)?
+            }
+            ServerMessage::Error(message) => writeln!(output, "{}", message)?,
+            ServerMessage::Left { name } => writeln!(output, "{} left the game.", name)?,
+            ServerMessage::Winner { attempts, name: winner } => {
+                if winner == name {
+                    writeln!(output, "You win after {} guesses!", attempts)?
+                } else {
+                    writeln!(output, "{} wins after {} guesses.", winner, attempts)?
+                }
+            }
+            ServerMessage::Bye => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::SecretSource;
+    use crate::DEFAULT_RANGE;
+    use std::ops::RangeInclusive;
+    use std::thread;
+
+    struct Fixed(u32);
+
+    impl SecretSource for Fixed {
+        fn secret_in(&mut self, _range: &RangeInclusive<u32>) -> u32 {
+            self.0
+        }
+    }
+
+    // run a server on a free localhost port and one client per (name, script),
+    // returning what the server decided and every client's transcript
+    fn play(secret: u32, clients: &[(&str, &str)]) -> (ServerOutcome, Vec<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let needed = clients.len();
+        let server = thread::spawn(move || {
+            let game = Game::new(DEFAULT_RANGE, &mut Fixed(secret));
+            serve(&listener, &game, needed, TIMEOUT, Vec::new()).unwrap()
+        });
+
+        // connect one after another, the server accepts them in that order
+        // so the turn order is the order of `clients`
+        let mut handles = Vec::new();
+        for (name, script) in clients {
+            let stream = TcpStream::connect(address).unwrap();
+            let (name, script) = (name.to_string(), script.to_string());
+            handles.push(thread::spawn(move || {
+                let mut output = Vec::new();
+                join(stream, &name, script.as_bytes(), &mut output).unwrap();
+                String::from_utf8(output).unwrap()
+            }));
+        }
+
+        let transcripts = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        (server.join().unwrap(), transcripts)
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let messages = vec![
+            ServerMessage::Welcome { min: 1, max: 100, name: String::from("Ferris the Crab") },
+            ServerMessage::Waiting { joined: 1, needed: 3 },
+            ServerMessage::Start { players: 3 },
+            ServerMessage::Turn,
+            ServerMessage::Wait { name: String::from("corro") },
+            ServerMessage::Result { guess: 50, ordering: Ordering::Greater, name: String::from("a b") },
+            ServerMessage::Error(String::from("Guess value must be between 1 and 100, got 200")),
+            ServerMessage::Left { name: String::from("corro") },
+            ServerMessage::Winner { attempts: 4, name: String::from("Ferris the Crab") },
+            ServerMessage::Bye,
+        ];
+        for message in messages {
+            assert_eq!(message.to_string().parse(), Ok(message));
+        }
+
+        for message in [
+            ClientMessage::Name(String::from("Ferris the Crab")),
+            ClientMessage::Guess(String::from("42")),
+            ClientMessage::Quit,
+        ] {
+            assert_eq!(format!("{}\n", message).parse(), Ok(message));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_messages() {
+        assert!("HELLO".parse::<ServerMessage>().is_err());
+        assert!("RESULT 5 SIDEWAYS bob".parse::<ServerMessage>().is_err());
+        assert!("WINNER many bob".parse::<ServerMessage>().is_err());
+        assert!("NAME  ".parse::<ClientMessage>().is_err());
+    }
+
+    #[test]
+    fn players_take_turns_until_someone_wins() {
+        let (outcome, transcripts) = play(42, &[("ferris", "50\n30\n"), ("corro", "25\n42\n")]);
+
+        assert_eq!(outcome.winner, Some((String::from("corro"), 2)));
+        assert_eq!(
+            transcripts[0],
+            "Welcome ferris! Guess the number between 1 and 100.\n\
+             Waiting for players (1/2 joined)...\n\
+             Waiting for players (2/2 joined)...\n\
+             All 2 players are here, let's go!\n\
+             Your turn, please input your guess.\n\
+             ferris guessed 50: Too big!\n\
+             Waiting for corro...\n\
+             corro guessed 25: Too small!\n\
+             Your turn, please input your guess.\n\
+             ferris guessed 30: Too small!\n\
+             Waiting for corro...\n\
+             corro guessed 42: Correct!\n\
+             corro wins after 2 guesses.\n"
+        );
+        assert!(transcripts[1].ends_with("corro guessed 42: Correct!\nYou win after 2 guesses!\n"));
+    }
+
+    #[test]
+    fn invalid_guesses_do_not_cost_a_turn() {
+        let (outcome, transcripts) = play(7, &[("ferris", "seven\n700\n7\n"), ("corro", "")]);
+
+        assert_eq!(outcome.winner, Some((String::from("ferris"), 1)));
+        assert!(transcripts[0].contains(
+            "'seven' is not a number, please type a whole number\n\
+             Your turn, please input your guess.\n\
+             Guess value must be between 1 and 100, got 700\n\
+             Your turn, please input your guess.\n\
+             ferris guessed 7: Correct!\n"
+        ));
+    }
+
+    #[test]
+    fn players_who_leave_are_skipped() {
+        // corro runs out of input on its first turn and quits
+        let (outcome, transcripts) = play(3, &[("ferris", "1\n2\n3\n"), ("corro", "")]);
+
+        assert_eq!(outcome.winner, Some((String::from("ferris"), 3)));
+        assert!(transcripts[0].contains(
+            "Waiting for corro...\n\
+             corro left the game.\n\
+             Your turn, please input your guess.\n\
+             ferris guessed 2: Too small!\n\
+             Your turn, please input your guess.\n"
+        ));
+    }
+
+    #[test]
+    fn removing_players_keeps_the_turn() {
+        // it is 3's turn, 1 and 3 are gone: 4 goes next
+        let mut players = vec![0, 1, 2, 3, 4];
+        let mut turn = 3;
+        assert_eq!(remove_gone(&mut players, &mut turn, |&p| p == 1 || p == 3), [1, 3]);
+        assert_eq!((players[turn], players), (4, vec![0, 2, 4]));
+
+        // the last one leaving on its turn, the caller wraps around to the first
+        let mut players = vec![0, 1, 2];
+        let mut turn = 2;
+        remove_gone(&mut players, &mut turn, |&p| p == 2);
+        assert_eq!((turn, players), (2, vec![0, 1]));
+    }
+
+    fn server(
+        secret: u32,
+        needed: usize,
+        timeout: Duration,
+    ) -> (std::net::SocketAddr, thread::JoinHandle<(ServerOutcome, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let game = Game::new(DEFAULT_RANGE, &mut Fixed(secret));
+            let mut log = Vec::new();
+            let outcome = serve(&listener, &game, needed, timeout, &mut log).unwrap();
+            (outcome, String::from_utf8(log).unwrap())
+        });
+        (address, handle)
+    }
+
+    fn client(address: std::net::SocketAddr, name: &str, script: &str) -> thread::JoinHandle<String> {
+        let stream = TcpStream::connect(address).unwrap();
+        let (name, script) = (name.to_string(), script.to_string());
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            join(stream, &name, script.as_bytes(), &mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        })
+    }
+
+    #[test]
+    fn names_must_differ() {
+        let (address, server) = server(5, 2, TIMEOUT);
+        let ferris = client(address, "ferris", "5\n");
+        let copycat = client(address, "ferris", "");
+        assert_eq!(copycat.join().unwrap(), "The name ferris is taken, please pick another one\n");
+        let corro = client(address, "corro", "");
+
+        let (outcome, log) = server.join().unwrap();
+        assert_eq!(outcome.winner, Some((String::from("ferris"), 1)));
+        assert!(log.contains("ferris from 127.0.0.1:"));
+        assert!(ferris.join().unwrap().ends_with("You win after 1 guesses!\n"));
+        assert!(corro.join().unwrap().ends_with("ferris wins after 1 guesses.\n"));
+    }
+
+    #[test]
+    fn silent_clients_time_out() {
+        let timeout = Duration::from_millis(200);
+        let (address, server) = server(9, 2, timeout);
+
+        // never says its name
+        let mute = TcpStream::connect(address).unwrap();
+        let ferris = client(address, "ferris", "1\n2\n9\n");
+        // says its name, then never guesses
+        let mut sleepy = TcpStream::connect(address).unwrap();
+        writeln!(sleepy, "NAME sleepy").unwrap();
+
+        let (outcome, log) = server.join().unwrap();
+        assert_eq!(outcome.winner, Some((String::from("ferris"), 3)));
+        assert!(log.contains("did not say its name, hanging up\nferris joined"));
+        assert!(log.contains("ferris guessed 1\nsleepy left\nferris guessed 2\n"));
+        assert!(!log.contains("secret"));
+        assert!(ferris.join().unwrap().contains("Waiting for sleepy...\nsleepy left the game.\n"));
+        drop((mute, sleepy));
+    }
+
+    #[test]
+    fn nobody_wins_when_everybody_leaves() {
+        let (outcome, _) = play(3, &[("ferris", "1\n"), ("corro", "")]);
+        assert_eq!(outcome.winner, None);
+    }
+}
@@ -0,0 +1,295 @@
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::Difficulty;
+
+// The name a score gets when the player didn't give one, an empty name can't be read back
+pub const ANONYMOUS: &str = "anonymous";
+
+// One finished game. The file stores one score per line, fields separated by tabs:
+// player, difficulty, attempts, milliseconds taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub player: String,
+    pub difficulty: Difficulty,
+    pub attempts: u32,
+    pub time: Duration,
+}
+
+impl Score {
+    pub fn new(player: &str, difficulty: Difficulty, attempts: u32, time: Duration) -> Score {
+        // tabs and newlines would break the file format, so they become plain spaces
+        let player: String = player
+            .trim()
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        let player = if player.trim().is_empty() { ANONYMOUS.to_string() } else { player };
+
+        Score { player, difficulty, attempts, time }
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.player,
+            self.difficulty,
+            self.attempts,
+            self.time.as_millis()
+        )
+    }
+
+    // None when the line is not a score we wrote, e.g. the file got truncated or edited by hand
+    fn from_line(line: &str) -> Option<Score> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 4 || fields[0].is_empty() {
+            return None;
+        }
+
+        Some(Score {
+            player: fields[0].to_string(),
+            difficulty: fields[1].parse().ok()?,
+            attempts: fields[2].parse().ok()?,
+            time: Duration::from_millis(fields[3].parse().ok()?),
+        })
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ScoreBoard {
+    scores: Vec<Score>,
+    // lines of the score file that could not be read back, they are dropped on the next save
+    skipped: usize,
+}
+
+impl ScoreBoard {
+    // A missing file is simply an empty board and broken lines are skipped, so a bad
+    // score file never stops anyone from playing. Other io errors (permissions, ...) are returned.
+    pub fn load(path: &Path) -> io::Result<ScoreBoard> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(ScoreBoard::default()),
+            Err(e) => return Err(e),
+        };
+
+        // from_utf8_lossy so that garbage bytes only spoil the lines they are on
+        let contents = String::from_utf8_lossy(&bytes);
+        let mut board = ScoreBoard::default();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            match Score::from_line(line) {
+                Some(score) => board.scores.push(score),
+                None => board.skipped += 1,
+            }
+        }
+
+        Ok(board)
+    }
+
+    // write everything to a temporary file first and then rename it over the old one,
+    // so a crash half way through saving can't leave a half written score file behind
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        // scores.txt.tmp, with_extension would give scores.txt and scores.csv the same one
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut file = fs::File::create(&tmp)?;
+            for score in &self.scores {
+                writeln!(file, "{}", score.to_line())?;
+            }
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, path)
+    }
+
+    pub fn record(&mut self, score: Score) {
+        self.scores.push(score);
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    // best scores first: fewest attempts, then fastest
+    pub fn leaderboard(&self, difficulty: Difficulty) -> Vec<&Score> {
+        let mut best: Vec<&Score> = self
+            .scores
+            .iter()
+            .filter(|score| score.difficulty == difficulty)
+            .collect();
+        best.sort_by_key(|score| (score.attempts, score.time));
+        best
+    }
+
+    pub fn write_leaderboard<W: Write>(&self, mut output: W, limit: usize) -> io::Result<()> {
+        if self.scores.is_empty() {
+            return writeln!(output, "No scores yet, go play a game!");
+        }
+
+        for difficulty in Difficulty::ALL.iter() {
+            let best = self.leaderboard(*difficulty);
+            if best.is_empty() {
+                continue;
+            }
+
+            writeln!(output, "== {} ({}-{}) ==", difficulty, difficulty.range().start(), difficulty.range().end())?;
+            for (rank, score) in best.iter().take(limit).enumerate() {
+                writeln!(
+                    output,
+                    "{:>2}. {:<16} {:>3} attempts {:>8.1}s",
+                    rank + 1,
+                    score.player,
+                    score.attempts,
+                    score.time.as_secs_f64()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+    use std::process;
+
+    // every test gets its own file in the temp dir so they can run in parallel
+    fn temp_file(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("guessing-game-{}-{}", process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn score(player: &str, difficulty: Difficulty, attempts: u32, millis: u64) -> Score {
+        Score::new(player, difficulty, attempts, Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn missing_file_is_an_empty_board() {
+        let board = ScoreBoard::load(&temp_file("missing")).unwrap();
+        assert_eq!(board, ScoreBoard::default());
+    }
+
+    #[test]
+    fn scores_survive_a_round_trip() {
+        let path = temp_file("round-trip");
+        let mut board = ScoreBoard::default();
+        board.record(score("ferris", Difficulty::Normal, 5, 12_345));
+        board.record(score("corro", Difficulty::Hard, 10, 60_000));
+        board.save(&path).unwrap();
+
+        let loaded = ScoreBoard::load(&path).unwrap();
+        assert_eq!(loaded, board);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn corrupted_lines_are_skipped() {
+        let path = temp_file("corrupted");
+        let mut contents = b"ferris\tnormal\t5\t1000\n\
+                             this is not a score\n\
+                             corro\tnightmare\t3\t10\n\
+                             bob\teasy\tthree\t10\n\
+                             \n\
+                             alice\teasy\t2\t"
+            .to_vec();
+        contents.extend_from_slice(&[0xff, 0xfe, b'\n']);
+        contents.extend_from_slice(b"dave\teasy\t3\t2000\n");
+        fs::write(&path, contents).unwrap();
+
+        let board = ScoreBoard::load(&path).unwrap();
+        assert_eq!(board.skipped(), 4);
+        assert_eq!(board.leaderboard(Difficulty::Normal)[0].player, "ferris");
+        assert_eq!(board.leaderboard(Difficulty::Easy)[0].player, "dave");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn player_names_cannot_break_the_format() {
+        let path = temp_file("names");
+        let mut board = ScoreBoard::default();
+        board.record(score(" tab\there\nnewline ", Difficulty::Easy, 1, 1));
+        board.save(&path).unwrap();
+
+        let loaded = ScoreBoard::load(&path).unwrap();
+        assert_eq!(loaded.skipped(), 0);
+        assert_eq!(loaded.leaderboard(Difficulty::Easy)[0].player, "tab here newline");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn empty_names_become_anonymous() {
+        let path = temp_file("anonymous");
+        let mut board = ScoreBoard::default();
+        board.record(score("", Difficulty::Easy, 1, 1));
+        board.record(score(" \t\n ", Difficulty::Easy, 2, 1));
+        board.save(&path).unwrap();
+
+        let loaded = ScoreBoard::load(&path).unwrap();
+        assert_eq!(loaded.skipped(), 0);
+        assert_eq!(loaded, board);
+        let players: Vec<&str> = loaded.leaderboard(Difficulty::Easy).iter().map(|score| score.player.as_str()).collect();
+        assert_eq!(players, vec![ANONYMOUS, ANONYMOUS]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn saving_leaves_no_temporary_file() {
+        let path = temp_file("scores.txt");
+        let tmp = path.with_file_name(format!("{}.tmp", path.file_name().unwrap().to_str().unwrap()));
+        // where with_extension("tmp") would put it, shared with scores.csv and the like
+        let shared = path.with_extension("tmp");
+        fs::write(&shared, "someone else's").unwrap();
+
+        let mut board = ScoreBoard::default();
+        board.record(score("ferris", Difficulty::Easy, 1, 1));
+        board.save(&path).unwrap();
+
+        assert!(!tmp.exists());
+        assert_eq!(fs::read_to_string(&shared).unwrap(), "someone else's");
+        assert_eq!(ScoreBoard::load(&path).unwrap(), board);
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&shared).unwrap();
+    }
+
+    #[test]
+    fn leaderboard_ranks_by_attempts_then_time() {
+        let mut board = ScoreBoard::default();
+        board.record(score("slow", Difficulty::Normal, 4, 9_000));
+        board.record(score("lucky", Difficulty::Normal, 1, 30_000));
+        board.record(score("fast", Difficulty::Normal, 4, 2_000));
+        board.record(score("easy", Difficulty::Easy, 1, 1_000));
+
+        let players: Vec<&str> = board
+            .leaderboard(Difficulty::Normal)
+            .iter()
+            .map(|score| score.player.as_str())
+            .collect();
+        assert_eq!(players, vec!["lucky", "fast", "slow"]);
+    }
+
+    #[test]
+    fn leaderboard_output() {
+        let mut board = ScoreBoard::default();
+        let mut output = Vec::new();
+        board.write_leaderboard(&mut output, 10).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "No scores yet, go play a game!\n");
+
+        board.record(score("ferris", Difficulty::Hard, 9, 61_500));
+        board.record(score("corro", Difficulty::Easy, 3, 4_000));
+        let mut output = Vec::new();
+        board.write_leaderboard(&mut output, 10).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "== easy (1-10) ==\n \
+             1. corro              3 attempts      4.0s\n\
+             == hard (1-1000) ==\n \
+             1. ferris             9 attempts     61.5s\n"
+        );
+    }
+}
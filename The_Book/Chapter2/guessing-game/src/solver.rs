@@ -0,0 +1,288 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+
+// The tables are turned: the player thinks of a number and the computer guesses.
+// After every guess the player answers with the same Ordering the normal game uses,
+// guess.cmp(&secret), so Less means the guess was too small.
+// Each answer halves the numbers that are still possible (binary search).
+#[derive(Debug)]
+pub struct Solver {
+    range: RangeInclusive<u32>,
+    // every number from low to high (inclusive) is still a possible secret
+    low: u32,
+    high: u32,
+    history: Vec<(u32, Ordering)>,
+}
+
+// the answers so far can't all be true, there is no number left they fit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cheating {
+    pub history: Vec<(u32, Ordering)>,
+}
+
+impl fmt::Display for Cheating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let answers: Vec<String> = self
+            .history
+            .iter()
+            .map(|(guess, answer)| format!("{} was {}", guess, describe(*answer)))
+            .collect();
+        write!(f, "no number fits all of your answers: {}", answers.join(", "))
+    }
+}
+
+impl std::error::Error for Cheating {}
+
+impl Solver {
+    pub fn new(range: RangeInclusive<u32>) -> Solver {
+        Solver {
+            low: *range.start(),
+            high: *range.end(),
+            range,
+            history: Vec::new(),
+        }
+    }
+
+    // always the middle of what is left, written so low + high can't overflow
+    pub fn next_guess(&self) -> u32 {
+        self.low + (self.high - self.low) / 2
+    }
+
+    pub fn guesses(&self) -> u32 {
+        self.history.len() as u32
+    }
+
+    // Ok(Some(number)) once the player says Equal, Ok(None) while we are still searching
+    pub fn answer(&mut self, answer: Ordering) -> Result<Option<u32>, Cheating> {
+        let guess = self.next_guess();
+        self.history.push((guess, answer));
+
+        // checked_add/checked_sub: a guess of u32::MAX can't be too small
+        // and a guess of 0 can't be too big
+        let still_possible = match answer {
+            Ordering::Equal => return Ok(Some(guess)),
+            Ordering::Less => guess.checked_add(1).map(|low| (low, self.high)),
+            Ordering::Greater => guess.checked_sub(1).map(|high| (self.low, high)),
+        };
+
+        match still_possible {
+            Some((low, high)) if low <= high => {
+                self.low = low;
+                self.high = high;
+                Ok(None)
+            }
+            _ => Err(Cheating { history: self.history.clone() }),
+        }
+    }
+
+    // most guesses binary search ever needs for this range, no strategy can do better
+    pub fn optimal_guesses(&self) -> u32 {
+        optimal_guesses(&self.range)
+    }
+}
+
+// n numbers take ceil(log2(n + 1)) guesses in the worst case
+pub fn optimal_guesses(range: &RangeInclusive<u32>) -> u32 {
+    if range.is_empty() {
+        return 0;
+    }
+    let count = (*range.end() - *range.start()) as u64 + 1;
+    64 - count.leading_zeros()
+}
+
+// accept the Ordering names as well as a few shortcuts
+pub fn parse_answer(input: &str) -> Option<Ordering> {
+    match input.trim().to_lowercase().as_str() {
+        "less" | "l" | "<" | "too small" | "small" => Some(Ordering::Less),
+        "greater" | "g" | ">" | "too big" | "big" => Some(Ordering::Greater),
+        "equal" | "e" | "=" | "yes" | "y" => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+fn describe(answer: Ordering) -> &'static str {
+    match answer {
+        Ordering::Less => "too small",
+        Ordering::Greater => "too big",
+        Ordering::Equal => "right",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputerOutcome {
+    Found { number: u32, guesses: u32 },
+    Cheated(Cheating),
+    // the input ran out before the number was found
+    GaveUp,
+}
+
+// same idea as Game::play: everything goes through BufRead and Write so tests can script the player
+pub fn play<R: BufRead, W: Write>(
+    range: RangeInclusive<u32>,
+    mut input: R,
+    mut output: W,
+) -> io::Result<ComputerOutcome> {
+    let mut solver = Solver::new(range);
+    writeln!(
+        output,
+        "Think of a number between {} and {}, I will find it in at most {} guesses.",
+        solver.range.start(),
+        solver.range.end(),
+        solver.optimal_guesses()
+    )?;
+
+    loop {
+        writeln!(
+            output,
+            "I guess {}. Is that less, greater or equal to your number?",
+            solver.next_guess()
+        )?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(ComputerOutcome::GaveUp);
+        }
+
+        let answer = match parse_answer(&line) {
+            Some(answer) => answer,
+            None => {
+                writeln!(output, "Please answer less, greater or equal (l/g/e).")?;
+                continue;
+            }
+        };
+
+        match solver.answer(answer) {
+            Ok(Some(number)) => {
+                writeln!(
+                    output,
+                    "Got it, your number is {}! That took {} guesses, the best possible worst case is {}.",
+                    number,
+                    solver.guesses(),
+                    solver.optimal_guesses()
+                )?;
+                return Ok(ComputerOutcome::Found { number, guesses: solver.guesses() });
+            }
+            Ok(None) => {}
+            Err(cheating) => {
+                writeln!(output, "Hey, {}", cheating)?;
+                return Ok(ComputerOutcome::Cheated(cheating));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_RANGE;
+
+    // answer honestly for `secret` until the solver finds it
+    fn solve(range: RangeInclusive<u32>, secret: u32) -> u32 {
+        let mut solver = Solver::new(range);
+        loop {
+            let guess = solver.next_guess();
+            if let Some(found) = solver.answer(guess.cmp(&secret)).unwrap() {
+                assert_eq!(found, secret);
+                return solver.guesses();
+            }
+        }
+    }
+
+    #[test]
+    fn finds_every_number_within_the_optimal_count() {
+        for secret in DEFAULT_RANGE {
+            assert!(solve(DEFAULT_RANGE, secret) <= 7);
+        }
+    }
+
+    #[test]
+    fn optimal_guess_counts() {
+        assert_eq!(optimal_guesses(&(1..=1)), 1);
+        assert_eq!(optimal_guesses(&(1..=3)), 2);
+        assert_eq!(optimal_guesses(&(1..=10)), 4);
+        assert_eq!(optimal_guesses(&DEFAULT_RANGE), 7);
+        assert_eq!(optimal_guesses(&(1..=1000)), 10);
+        assert_eq!(optimal_guesses(&(0..=u32::MAX)), 33);
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 10..=1;
+        assert_eq!(optimal_guesses(&empty), 0);
+    }
+
+    #[test]
+    fn handles_the_edges_of_u32() {
+        assert_eq!(solve(0..=u32::MAX, 0), 32);
+        assert!(solve(0..=u32::MAX, u32::MAX) <= 33);
+    }
+
+    #[test]
+    fn detects_contradicting_answers() {
+        let mut solver = Solver::new(1..=10);
+        // 5 is too small, 8 is too big, 6 is too big... so 5 < secret < 6
+        assert_eq!(solver.answer(Ordering::Less), Ok(None));
+        assert_eq!(solver.next_guess(), 8);
+        assert_eq!(solver.answer(Ordering::Greater), Ok(None));
+        assert_eq!(solver.next_guess(), 6);
+        let cheating = solver.answer(Ordering::Greater).unwrap_err();
+        assert_eq!(
+            cheating.history,
+            vec![(5, Ordering::Less), (8, Ordering::Greater), (6, Ordering::Greater)]
+        );
+    }
+
+    #[test]
+    fn detects_answers_past_the_end_of_the_range() {
+        let mut solver = Solver::new(u32::MAX..=u32::MAX);
+        assert!(solver.answer(Ordering::Less).is_err());
+
+        let mut solver = Solver::new(0..=0);
+        assert!(solver.answer(Ordering::Greater).is_err());
+    }
+
+    #[test]
+    fn parses_answers() {
+        assert_eq!(parse_answer("Less\n"), Some(Ordering::Less));
+        assert_eq!(parse_answer(" > "), Some(Ordering::Greater));
+        assert_eq!(parse_answer("e"), Some(Ordering::Equal));
+        assert_eq!(parse_answer("maybe"), None);
+    }
+
+    #[test]
+    fn plays_against_a_scripted_player() {
+        let mut output = Vec::new();
+        let outcome = play(1..=10, &b"l\nwhat\ng\n=\n"[..], &mut output).unwrap();
+
+        assert_eq!(outcome, ComputerOutcome::Found { number: 6, guesses: 3 });
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Think of a number between 1 and 10, I will find it in at most 4 guesses.\n\
+             I guess 5. Is that less, greater or equal to your number?\n\
+             I guess 8. Is that less, greater or equal to your number?\n\
+             Please answer less, greater or equal (l/g/e).\n\
+             I guess 8. Is that less, greater or equal to your number?\n\
+             I guess 6. Is that less, greater or equal to your number?\n\
+             Got it, your number is 6! That took 3 guesses, the best possible worst case is 4.\n"
+        );
+    }
+
+    #[test]
+    fn calls_out_a_cheating_player() {
+        let mut output = Vec::new();
+        let outcome = play(1..=3, &b"less\nless\nless\n"[..], &mut output).unwrap();
+
+        match outcome {
+            ComputerOutcome::Cheated(cheating) => assert_eq!(cheating.history.len(), 2),
+            other => panic!("expected cheating, got {:?}", other),
+        }
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .ends_with("Hey, no number fits all of your answers: 2 was too small, 3 was too small\n"));
+    }
+
+    #[test]
+    fn running_out_of_input_gives_up() {
+        let outcome = play(DEFAULT_RANGE, &b"l\n"[..], Vec::new()).unwrap();
+        assert_eq!(outcome, ComputerOutcome::GaveUp);
+    }
+}
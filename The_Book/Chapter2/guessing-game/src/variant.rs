@@ -0,0 +1,155 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::game::SecretSource;
+use crate::{Guess, GuessError};
+
+pub mod bulls_and_cows;
+pub mod words;
+
+pub use self::bulls_and_cows::BullsAndCows;
+pub use self::words::Words;
+
+// Everything that makes one kind of guessing game different from another.
+// The game loop in game.rs only asks the variant for a secret, hands it what the
+// player typed and tells the player the feedback, so adding a game means adding a Variant.
+pub trait Variant {
+    type Secret: Clone + fmt::Display;
+    type Guess: fmt::Display;
+    type Feedback;
+    type Error: fmt::Display;
+
+    // printed once before the first guess
+    fn intro(&self) -> String;
+
+    fn new_secret<S: SecretSource>(&self, source: &mut S) -> Self::Secret;
+
+    fn parse_guess(&self, input: &str) -> Result<Self::Guess, Self::Error>;
+
+    fn feedback(&self, secret: &Self::Secret, guess: &Self::Guess) -> Self::Feedback;
+
+    fn is_solved(&self, feedback: &Self::Feedback) -> bool;
+
+    // what the player is told after a guess that wasn't right
+    fn hint(&self, feedback: &Self::Feedback) -> String;
+}
+
+// The original game: a number in a range, and the player is told whether the guess
+// was too small or too big
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Classic {
+    pub range: RangeInclusive<u32>,
+}
+
+impl Variant for Classic {
+    type Secret = u32;
+    type Guess = Guess;
+    // Ordering is another enum, but the variants for Ordering is Less, Greater, Equal
+    type Feedback = Ordering;
+    type Error = GuessError;
+
+    fn intro(&self) -> String {
+        String::from("Guess the number!")
+    }
+
+    fn new_secret<S: SecretSource>(&self, source: &mut S) -> u32 {
+        source.secret_in(&self.range)
+    }
+
+    fn parse_guess(&self, input: &str) -> Result<Guess, GuessError> {
+        Guess::parse(input, &self.range)
+    }
+
+    fn feedback(&self, secret: &u32, guess: &Guess) -> Ordering {
+        guess.value().cmp(secret)
+    }
+
+    fn is_solved(&self, feedback: &Ordering) -> bool {
+        *feedback == Ordering::Equal
+    }
+
+    fn hint(&self, feedback: &Ordering) -> String {
+        // match expression is made up of arms. An arm consists of a pattern and the code
+        // that should be run if the value given to the beginning of the match expression
+        // fits that arm's pattern.
+        let hint = match feedback {
+            Ordering::Less => "Too small!",
+            Ordering::Greater => "Too big!",
+            Ordering::Equal => "Correct!",
+        };
+        String::from(hint)
+    }
+}
+
+// which game to play, picked with --variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariantKind {
+    Classic,
+    BullsAndCows,
+    Words,
+}
+
+impl FromStr for VariantKind {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<VariantKind, Self::Err> {
+        match s {
+            "classic" => Ok(VariantKind::Classic),
+            "bulls" | "bulls-and-cows" | "mastermind" => Ok(VariantKind::BullsAndCows),
+            "words" => Ok(VariantKind::Words),
+            _ => Err("variant must be classic, bulls or words"),
+        }
+    }
+}
+
+// pick `count` different values out of 0..=max, in random order (a partial Fisher-Yates shuffle)
+pub(crate) fn distinct<S: SecretSource>(source: &mut S, max: u32, count: usize) -> Vec<u32> {
+    let mut pool: Vec<u32> = (0..=max).collect();
+    for i in 0..count.min(pool.len()) {
+        let j = source.secret_in(&(i as u32..=max)) as usize;
+        pool.swap(i, j);
+    }
+    pool.truncate(count);
+    pool
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::seeded;
+    use crate::DEFAULT_RANGE;
+
+    #[test]
+    fn classic_compares_numbers() {
+        let classic = Classic { range: DEFAULT_RANGE };
+        let guess = classic.parse_guess("50").unwrap();
+        assert_eq!(classic.feedback(&42, &guess), Ordering::Greater);
+        assert_eq!(classic.hint(&Ordering::Greater), "Too big!");
+        assert!(classic.is_solved(&classic.feedback(&50, &guess)));
+        assert!(classic.parse_guess("101").is_err());
+    }
+
+    #[test]
+    fn variant_names() {
+        assert_eq!("classic".parse(), Ok(VariantKind::Classic));
+        assert_eq!("bulls".parse(), Ok(VariantKind::BullsAndCows));
+        assert_eq!("words".parse(), Ok(VariantKind::Words));
+        assert!("chess".parse::<VariantKind>().is_err());
+    }
+
+    #[test]
+    fn distinct_values_are_distinct() {
+        let mut rng = seeded(3);
+        for _ in 0..100 {
+            let mut values = distinct(&mut rng, 9, 4);
+            assert_eq!(values.len(), 4);
+            assert!(values.iter().all(|v| *v <= 9));
+            values.sort();
+            values.dedup();
+            assert_eq!(values.len(), 4);
+        }
+        assert_eq!(distinct(&mut rng, 2, 10).len(), 3);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+use super::{distinct, Variant};
+use crate::game::SecretSource;
+
+// Bulls and Cows, the pen and paper game Mastermind is based on: the secret is a
+// code of different digits, a bull is a right digit in the right place and a cow
+// is a right digit in the wrong place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BullsAndCows {
+    // how many digits the code has, 1 to 10
+    pub length: usize,
+}
+
+// a code of distinct digits, used for the secret as well as for guesses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Code(Vec<u8>);
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for digit in &self.0 {
+            write!(f, "{}", digit)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    pub bulls: usize,
+    pub cows: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeError {
+    WrongLength { expected: usize, got: usize },
+    NotADigit(char),
+    RepeatedDigit(u8),
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodeError::WrongLength { expected, got } => {
+                write!(f, "The code has {} digits, you typed {}", expected, got)
+            }
+            CodeError::NotADigit(c) => write!(f, "'{}' is not a digit", c),
+            CodeError::RepeatedDigit(d) => write!(f, "Every digit is different, {} appears more than once", d),
+        }
+    }
+}
+
+impl BullsAndCows {
+    pub fn new(length: usize) -> BullsAndCows {
+        // with only ten digits a longer code of different digits doesn't exist
+        BullsAndCows { length: length.clamp(1, 10) }
+    }
+}
+
+impl Variant for BullsAndCows {
+    type Secret = Code;
+    type Guess = Code;
+    type Feedback = Score;
+    type Error = CodeError;
+
+    fn intro(&self) -> String {
+        format!(
+            "Guess the code of {} different digits! A bull is a right digit in the right place, \
+             a cow is a right digit in the wrong place.",
+            self.length
+        )
+    }
+
+    fn new_secret<S: SecretSource>(&self, source: &mut S) -> Code {
+        Code(distinct(source, 9, self.length).into_iter().map(|d| d as u8).collect())
+    }
+
+    fn parse_guess(&self, input: &str) -> Result<Code, CodeError> {
+        let input = input.trim();
+        let mut digits = Vec::new();
+        for c in input.chars() {
+            let digit = c.to_digit(10).ok_or(CodeError::NotADigit(c))? as u8;
+            if digits.contains(&digit) {
+                return Err(CodeError::RepeatedDigit(digit));
+            }
+            digits.push(digit);
+        }
+
+        if digits.len() != self.length {
+            return Err(CodeError::WrongLength { expected: self.length, got: digits.len() });
+        }
+        Ok(Code(digits))
+    }
+
+    fn feedback(&self, secret: &Code, guess: &Code) -> Score {
+        let bulls = secret.0.iter().zip(&guess.0).filter(|(s, g)| s == g).count();
+        // digits never repeat, so every shared digit that isn't a bull is a cow
+        let shared = guess.0.iter().filter(|g| secret.0.contains(g)).count();
+        Score { bulls, cows: shared - bulls }
+    }
+
+    fn is_solved(&self, score: &Score) -> bool {
+        score.bulls == self.length
+    }
+
+    fn hint(&self, score: &Score) -> String {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        format!("{} bull{}, {} cow{}", score.bulls, plural(score.bulls), score.cows, plural(score.cows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::seeded;
+
+    fn code(digits: &str) -> Code {
+        BullsAndCows::new(digits.len()).parse_guess(digits).unwrap()
+    }
+
+    #[test]
+    fn scores_bulls_and_cows() {
+        let game = BullsAndCows::new(4);
+        let secret = code("1234");
+        assert_eq!(game.feedback(&secret, &code("5678")), Score { bulls: 0, cows: 0 });
+        assert_eq!(game.feedback(&secret, &code("1243")), Score { bulls: 2, cows: 2 });
+        assert_eq!(game.feedback(&secret, &code("4321")), Score { bulls: 0, cows: 4 });
+        assert_eq!(game.feedback(&secret, &code("1325")), Score { bulls: 1, cows: 2 });
+        assert!(game.is_solved(&game.feedback(&secret, &code("1234"))));
+    }
+
+    #[test]
+    fn rejects_bad_codes() {
+        let game = BullsAndCows::new(4);
+        assert_eq!(game.parse_guess("123"), Err(CodeError::WrongLength { expected: 4, got: 3 }));
+        assert_eq!(game.parse_guess("12a4"), Err(CodeError::NotADigit('a')));
+        assert_eq!(game.parse_guess("1224"), Err(CodeError::RepeatedDigit(2)));
+        assert_eq!(game.parse_guess(" 0123\n"), Ok(Code(vec![0, 1, 2, 3])));
+    }
+
+    #[test]
+    fn secrets_are_valid_codes() {
+        let game = BullsAndCows::new(4);
+        let mut rng = seeded(9);
+        for _ in 0..100 {
+            let secret = game.new_secret(&mut rng);
+            assert_eq!(game.parse_guess(&secret.to_string()), Ok(secret));
+        }
+    }
+
+    #[test]
+    fn hints_read_naturally() {
+        let game = BullsAndCows::new(4);
+        assert_eq!(game.hint(&Score { bulls: 1, cows: 2 }), "1 bull, 2 cows");
+        assert_eq!(game.hint(&Score { bulls: 0, cows: 1 }), "0 bulls, 1 cow");
+        assert_eq!(BullsAndCows::new(12).length, 10);
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+
+use super::Variant;
+use crate::game::SecretSource;
+
+// Guess a five letter word. After each guess every letter is marked as
+// in the right place (upper case), in the word but somewhere else (lower case)
+// or not in the word at all (.), so "react" against the secret "crane" gives "reAc.".
+const WORDS: [&str; 40] = [
+    "cargo", "crate", "bytes", "trait", "match", "slice", "stack", "loops", "macro", "tuple",
+    "float", "range", "clone", "panic", "yield", "async", "await", "scope", "shell", "guess",
+    "catch", "proof", "spawn", "mutex", "queue", "array", "state", "error", "parse", "token",
+    "lemon", "ocean", "piano", "river", "stone", "tiger", "zebra", "quest", "brave", "ghost",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    // right letter, right place
+    Correct,
+    // the letter is in the word, but somewhere else
+    Present,
+    Absent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feedback {
+    pub guess: String,
+    pub marks: Vec<Mark>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordError {
+    WrongLength { expected: usize, got: usize },
+    NotALetter(char),
+}
+
+impl fmt::Display for WordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordError::WrongLength { expected, got } => {
+                write!(f, "The word has {} letters, you typed {}", expected, got)
+            }
+            WordError::NotALetter(c) => write!(f, "'{}' is not a letter", c),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Words {
+    words: Vec<String>,
+}
+
+impl Default for Words {
+    fn default() -> Words {
+        Words { words: WORDS.iter().map(|word| word.to_string()).collect() }
+    }
+}
+
+impl Words {
+    pub fn length(&self) -> usize {
+        self.words[0].chars().count()
+    }
+}
+
+impl Variant for Words {
+    type Secret = String;
+    type Guess = String;
+    type Feedback = Feedback;
+    type Error = WordError;
+
+    fn intro(&self) -> String {
+        format!(
+            "Guess the {} letter word! Upper case letters are in the right place, \
+             lower case letters are in the word somewhere else and . is not in the word.",
+            self.length()
+        )
+    }
+
+    fn new_secret<S: SecretSource>(&self, source: &mut S) -> String {
+        let index = source.secret_in(&(0..=self.words.len() as u32 - 1));
+        self.words[index as usize].clone()
+    }
+
+    fn parse_guess(&self, input: &str) -> Result<String, WordError> {
+        let guess = input.trim().to_lowercase();
+        if let Some(c) = guess.chars().find(|c| !c.is_alphabetic()) {
+            return Err(WordError::NotALetter(c));
+        }
+
+        let got = guess.chars().count();
+        if got != self.length() {
+            return Err(WordError::WrongLength { expected: self.length(), got });
+        }
+        Ok(guess)
+    }
+
+    fn feedback(&self, secret: &String, guess: &String) -> Feedback {
+        let secret: Vec<char> = secret.chars().collect();
+        let guess_chars: Vec<char> = guess.chars().collect();
+        let mut marks = vec![Mark::Absent; guess_chars.len()];
+        // letters of the secret not used up by an exact match yet
+        let mut unused: Vec<Option<char>> = secret.iter().map(|c| Some(*c)).collect();
+
+        // exact matches first, so a repeated letter in the guess doesn't steal them
+        for (i, c) in guess_chars.iter().enumerate() {
+            if secret.get(i) == Some(c) {
+                marks[i] = Mark::Correct;
+                unused[i] = None;
+            }
+        }
+        for (i, c) in guess_chars.iter().enumerate() {
+            if marks[i] == Mark::Correct {
+                continue;
+            }
+            if let Some(slot) = unused.iter_mut().find(|slot| **slot == Some(*c)) {
+                marks[i] = Mark::Present;
+                *slot = None;
+            }
+        }
+
+        Feedback { guess: guess.clone(), marks }
+    }
+
+    fn is_solved(&self, feedback: &Feedback) -> bool {
+        feedback.marks.iter().all(|mark| *mark == Mark::Correct)
+    }
+
+    fn hint(&self, feedback: &Feedback) -> String {
+        feedback
+            .guess
+            .chars()
+            .zip(&feedback.marks)
+            .map(|(c, mark)| match mark {
+                Mark::Correct => c.to_ascii_uppercase(),
+                Mark::Present => c,
+                Mark::Absent => '.',
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::seeded;
+
+    fn hint(secret: &str, guess: &str) -> String {
+        let words = Words::default();
+        words.hint(&words.feedback(&secret.to_string(), &guess.to_string()))
+    }
+
+    #[test]
+    fn marks_letters() {
+        assert_eq!(hint("crane", "react"), "reAc.");
+        assert_eq!(hint("crane", "crane"), "CRANE");
+        assert_eq!(hint("crane", "ghost"), ".....");
+    }
+
+    #[test]
+    fn repeated_letters_are_only_marked_as_often_as_they_appear() {
+        // one e in the secret: the exact match wins, the other e's are absent
+        assert_eq!(hint("lemon", "eerie"), ".E...");
+        // two l's in the secret and in the guess
+        assert_eq!(hint("hello", "llama"), "ll...");
+    }
+
+    #[test]
+    fn checks_guesses() {
+        let words = Words::default();
+        assert_eq!(words.parse_guess(" Cargo\n"), Ok(String::from("cargo")));
+        assert_eq!(words.parse_guess("car"), Err(WordError::WrongLength { expected: 5, got: 3 }));
+        assert_eq!(words.parse_guess("car-o"), Err(WordError::NotALetter('-')));
+    }
+
+    #[test]
+    fn built_in_list_only_has_five_letter_words() {
+        let words = Words::default();
+        assert_eq!(words.length(), 5);
+        assert!(WORDS.iter().all(|word| word.len() == 5));
+        let mut rng = seeded(5);
+        for _ in 0..100 {
+            let secret = words.new_secret(&mut rng);
+            assert_eq!(words.parse_guess(&secret), Ok(secret));
+        }
+    }
+}
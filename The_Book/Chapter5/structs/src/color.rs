@@ -0,0 +1,367 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+// An sRGB color with an alpha channel, named like the Color of Rust By Example.
+// Every component is a u8, so any Color that exists is valid: the checks happen
+// when converting from wider types like the i32 tuple of Color(i32, i32, i32) in main.rs.
+// alpha 255 is opaque and 0 fully transparent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+// Hue in degrees (0 up to 360), the rest from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub hue: f64,
+    pub saturation: f64,
+    pub lightness: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    pub hue: f64,
+    pub saturation: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorError {
+    OutOfRange { component: &'static str, value: i32 },
+    // more than 0xffffff, the value of an RGB color like the Color enum of Rust By Example
+    TooLarge(u32),
+    // a hex color has 3, 4, 6 or 8 digits after the #
+    HexLength(usize),
+    HexDigit(char),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorError::OutOfRange { component, value } => {
+                write!(f, "{} is {}, it has to be between 0 and 255", component, value)
+            }
+            ColorError::TooLarge(value) => write!(f, "{:#x} is larger than 0xffffff", value),
+            ColorError::HexLength(len) => write!(f, "a hex color has 3, 4, 6 or 8 digits, not {}", len),
+            ColorError::HexDigit(c) => write!(f, "'{}' is not a hex digit", c),
+        }
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    pub const GREEN: Color = Color::rgb(0, 255, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue, alpha: 255 }
+    }
+
+    pub const fn rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Color {
+        Color { red, green, blue, alpha }
+    }
+
+    // 0xff0000 is red, the same numbers as `Red = 0xff0000` in Rust By Example
+    pub fn from_u32(value: u32) -> Result<Color, ColorError> {
+        if value > 0xff_ffff {
+            return Err(ColorError::TooLarge(value));
+        }
+        Ok(Color::rgb((value >> 16) as u8, (value >> 8) as u8, value as u8))
+    }
+
+    // the RGB part only, alpha is left out
+    pub fn to_u32(&self) -> u32 {
+        (self.red as u32) << 16 | (self.green as u32) << 8 | self.blue as u32
+    }
+
+    pub fn to_hsl(&self) -> Hsl {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) / 2.0;
+        let chroma = max - min;
+        let saturation = if chroma == 0.0 { 0.0 } else { chroma / (1.0 - (2.0 * lightness - 1.0).abs()) };
+        Hsl { hue, saturation, lightness }
+    }
+
+    // Hues outside 0..360 wrap around, saturation and lightness are clamped to 0..=1
+    pub fn from_hsl(hsl: Hsl) -> Color {
+        let saturation = hsl.saturation.clamp(0.0, 1.0);
+        let lightness = hsl.lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Color::from_hue(hsl.hue, chroma, lightness - chroma / 2.0)
+    }
+
+    pub fn to_hsv(&self) -> Hsv {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        Hsv { hue, saturation, value: max }
+    }
+
+    pub fn from_hsv(hsv: Hsv) -> Color {
+        let saturation = hsv.saturation.clamp(0.0, 1.0);
+        let value = hsv.value.clamp(0.0, 1.0);
+        let chroma = value * saturation;
+        Color::from_hue(hsv.hue, chroma, value - chroma)
+    }
+
+    // the hue in degrees and the largest and smallest of the components, from 0 to 1
+    fn hue_max_min(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (unit(self.red), unit(self.green), unit(self.blue));
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+
+        let hue = if chroma == 0.0 {
+            // gray has no hue, 0 by convention
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        (hue, max, min)
+    }
+
+    // what HSL and HSV have in common: pick the sector of the color wheel the hue is in,
+    // then add `lightest` to all components
+    fn from_hue(hue: f64, chroma: f64, lightest: f64) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Color::rgb(byte(r + lightest), byte(g + lightest), byte(b + lightest))
+    }
+
+    // Part of the way from this color to `other`: 0 gives this color, 1 gives `other`
+    // and 0.5 the color half way between. Alpha is mixed as well.
+    pub fn mix(&self, other: &Color, amount: f64) -> Color {
+        let t = amount.clamp(0.0, 1.0);
+        let lerp = |from: u8, to: u8| byte(unit(from) + (unit(to) - unit(from)) * t);
+        Color::rgba(
+            lerp(self.red, other.red),
+            lerp(self.green, other.green),
+            lerp(self.blue, other.blue),
+            lerp(self.alpha, other.alpha),
+        )
+    }
+
+    // Paints this color on top of `background`, how see-through it is depends on its alpha
+    pub fn over(&self, background: &Color) -> Color {
+        let top = unit(self.alpha);
+        let bottom = unit(background.alpha) * (1.0 - top);
+        let alpha = top + bottom;
+        if alpha == 0.0 {
+            return Color::TRANSPARENT;
+        }
+
+        let blend = |front: u8, back: u8| byte((unit(front) * top + unit(back) * bottom) / alpha);
+        Color::rgba(
+            blend(self.red, background.red),
+            blend(self.green, background.green),
+            blend(self.blue, background.blue),
+            byte(alpha),
+        )
+    }
+
+    // How bright the color looks, from 0 for black to 1 for white, as defined by WCAG.
+    // Alpha is ignored: paint a see-through color over its background first.
+    pub fn relative_luminance(&self) -> f64 {
+        // undo the gamma of sRGB to get linear light
+        let linear = |component: u8| {
+            let c = unit(component);
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.red) + 0.7152 * linear(self.green) + 0.0722 * linear(self.blue)
+    }
+
+    // From 1 (the same brightness) to 21 (black and white). WCAG asks for at least
+    // 4.5 between normal text and its background.
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+fn unit(component: u8) -> f64 {
+    component as f64 / 255.0
+}
+
+fn byte(unit: f64) -> u8 {
+    (unit.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// Color(i32, i32, i32) from main.rs, each has to fit in a u8
+impl TryFrom<(i32, i32, i32)> for Color {
+    type Error = ColorError;
+
+    fn try_from((red, green, blue): (i32, i32, i32)) -> Result<Color, ColorError> {
+        let component = |component: &'static str, value: i32| {
+            u8::try_from(value).map_err(|_| ColorError::OutOfRange { component, value })
+        };
+        Ok(Color::rgb(component("red", red)?, component("green", green)?, component("blue", blue)?))
+    }
+}
+
+// #ff0000, or #ff000080 when the color is see-through
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)?;
+        if self.alpha != 255 {
+            write!(f, "{:02x}", self.alpha)?;
+        }
+        Ok(())
+    }
+}
+
+// #rgb, #rgba, #rrggbb or #rrggbbaa, the # may be left out and case doesn't matter
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Color, ColorError> {
+        let s = s.trim();
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        let digits = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8).ok_or(ColorError::HexDigit(c)))
+            .collect::<Result<Vec<u8>, ColorError>>()?;
+
+        let components: Vec<u8> = match digits.len() {
+            // the short forms repeat each digit, #f80 is #ff8800
+            3 | 4 => digits.iter().map(|d| d * 17).collect(),
+            6 | 8 => digits.chunks(2).map(|pair| pair[0] * 16 + pair[1]).collect(),
+            len => return Err(ColorError::HexLength(len)),
+        };
+        let alpha = components.get(3).copied().unwrap_or(255);
+        Ok(Color::rgba(components[0], components[1], components[2], alpha))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!("#ff0000".parse(), Ok(Color::RED));
+        assert_eq!("00FF00".parse(), Ok(Color::GREEN));
+        assert_eq!("#00f".parse(), Ok(Color::BLUE));
+        assert_eq!("#f808".parse(), Ok(Color::rgba(255, 136, 0, 136)));
+        // four digits are #rgba, not half of #rrggbb
+        assert_eq!("#ff00".parse(), Ok(Color::rgba(255, 255, 0, 0)));
+        assert_eq!("#12345678".parse(), Ok(Color::rgba(0x12, 0x34, 0x56, 0x78)));
+
+        assert_eq!(Color::RED.to_string(), "#ff0000");
+        assert_eq!(Color::rgba(0, 3, 254, 128).to_string(), "#0003fe80");
+        let teal = Color::rgb(0, 128, 128);
+        assert_eq!(teal.to_string().parse(), Ok(teal));
+    }
+
+    #[test]
+    fn bad_hex_colors() {
+        assert_eq!("#ff000".parse::<Color>(), Err(ColorError::HexLength(5)));
+        assert_eq!("#".parse::<Color>(), Err(ColorError::HexLength(0)));
+        assert_eq!("#gg0000".parse::<Color>(), Err(ColorError::HexDigit('g')));
+        assert_eq!("#ff0000 ff".parse::<Color>(), Err(ColorError::HexDigit(' ')));
+    }
+
+    #[test]
+    fn numbers_like_the_color_enum() {
+        assert_eq!(Color::from_u32(0xff0000), Ok(Color::RED));
+        assert_eq!(Color::from_u32(0x0000ff), Ok(Color::BLUE));
+        assert_eq!(Color::from_u32(0x1000000), Err(ColorError::TooLarge(0x1000000)));
+        assert_eq!(Color::rgb(128, 255, 90).to_u32(), 0x80ff5a);
+    }
+
+    #[test]
+    fn from_the_i32_tuple() {
+        assert_eq!(Color::try_from((0, 0, 0)), Ok(Color::BLACK));
+        assert_eq!(
+            Color::try_from((0, 256, 0)),
+            Err(ColorError::OutOfRange { component: "green", value: 256 })
+        );
+        assert_eq!(
+            Color::try_from((-1, 0, 0)),
+            Err(ColorError::OutOfRange { component: "red", value: -1 })
+        );
+    }
+
+    #[test]
+    fn hsl_conversion() {
+        let hsl = Color::rgb(255, 128, 0).to_hsl();
+        assert!(close(hsl.hue, 30.1176) && close(hsl.saturation, 1.0) && close(hsl.lightness, 0.5), "{:?}", hsl);
+
+        let gray = Color::rgb(128, 128, 128).to_hsl();
+        assert_eq!((gray.hue, gray.saturation), (0.0, 0.0));
+
+        assert_eq!(Color::from_hsl(Hsl { hue: 120.0, saturation: 1.0, lightness: 0.25 }), Color::rgb(0, 128, 0));
+        assert_eq!(Color::from_hsl(Hsl { hue: -120.0, saturation: 1.0, lightness: 0.5 }), Color::BLUE);
+        assert_eq!(Color::from_hsl(Hsl { hue: 0.0, saturation: 2.0, lightness: 1.5 }), Color::WHITE);
+    }
+
+    #[test]
+    fn hsv_conversion() {
+        let hsv = Color::rgb(0, 3, 254).to_hsv();
+        assert!(close(hsv.hue, 239.2913) && close(hsv.saturation, 1.0) && close(hsv.value, 254.0 / 255.0), "{:?}", hsv);
+        assert_eq!(Color::from_hsv(Hsv { hue: 300.0, saturation: 1.0, value: 1.0 }), Color::rgb(255, 0, 255));
+        assert_eq!(Color::BLACK.to_hsv(), Hsv { hue: 0.0, saturation: 0.0, value: 0.0 });
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        for value in (0..=0xff_ffff).step_by(0x01_0305) {
+            let color = Color::from_u32(value).unwrap();
+            assert_eq!(Color::from_hsl(color.to_hsl()), color);
+            assert_eq!(Color::from_hsv(color.to_hsv()), color);
+        }
+    }
+
+    #[test]
+    fn mixing_and_painting_over() {
+        assert_eq!(Color::BLACK.mix(&Color::WHITE, 0.5), Color::rgb(128, 128, 128));
+        assert_eq!(Color::RED.mix(&Color::BLUE, 0.0), Color::RED);
+        assert_eq!(Color::RED.mix(&Color::BLUE, 7.0), Color::BLUE);
+
+        let half_red = Color::rgba(255, 0, 0, 128);
+        assert_eq!(half_red.over(&Color::WHITE), Color::rgb(255, 127, 127));
+        assert_eq!(Color::RED.over(&Color::BLUE), Color::RED);
+        assert_eq!(Color::TRANSPARENT.over(&Color::GREEN), Color::GREEN);
+        assert_eq!(Color::TRANSPARENT.over(&Color::TRANSPARENT), Color::TRANSPARENT);
+        // two see-through layers let less through than one
+        assert_eq!(half_red.over(&Color::rgba(0, 0, 255, 128)).alpha, 192);
+    }
+
+    #[test]
+    fn contrast_ratios() {
+        assert!(close(Color::BLACK.contrast_ratio(&Color::WHITE), 21.0));
+        assert!(close(Color::WHITE.contrast_ratio(&Color::BLACK), 21.0));
+        assert!(close(Color::RED.contrast_ratio(&Color::RED), 1.0));
+        // #777777 on white just misses the 4.5 WCAG asks for
+        let gray: Color = "#777".parse().unwrap();
+        assert!(close(gray.contrast_ratio(&Color::WHITE), 4.478), "{}", gray.contrast_ratio(&Color::WHITE));
+        assert!(close(Color::WHITE.relative_luminance(), 1.0));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod color;
+pub mod packing;
+pub mod rectangle;
+pub mod shape;
+pub mod user;
+
+pub use crate::rectangle::Rectangle;
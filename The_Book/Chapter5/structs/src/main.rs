@@ -1,4 +1,8 @@
-/* 
+// An attribute to hide warnings for unused code.
+#![allow(dead_code)]
+#![allow(unused_variables)]
+
+/*
 A struct (structure) is a custom data type that lets you name and package together multiple related values
 that make up a meaningful group. A struct is like an object's data attribute in OOP. Using structs and enums
 are the building blocks for creating new types in your program's domain to take full advantage of Rust's
@@ -6,34 +10,14 @@ compile time type checking.
 
 */
 
-// adding the annotation to derive the Debug trait and printing the Rectangle instance using debug formatting
-#[derive(Debug)]
-struct Rectangle {
-    width: u32,
-    height: u32,
-}
-
-// defining a method
-impl Rectangle {
-    // self is Rectangle due to this method's being inside impl Rectangle context
-    fn area(&self) -> u32 {
-        self.width * self.height
-    }
+// Rectangle lives in the library now (src/rectangle.rs), with a position so it can
+// do real geometry. It still derives Debug, so it can be printed using debug formatting
+use std::convert::TryFrom;
 
-    // method
-    fn can_hold(&self, other: &Rectangle) -> bool {
-        self.width > other.width && height > other.height
-    }
-
-    // associated functions, often used for constructors that will return a new instance
-    // not methods, but functions
-    fn square(size: u32) -> Rectangle {
-        Rectangle {
-            width: size,
-            height: size,
-        }
-    }
-}
+use structs::color;
+use structs::shape::{self, Circle, Shape, Square};
+use structs::user;
+use structs::Rectangle;
 
 struct Color(i32, i32, i32);
 struct Point(i32, i32, i32);
@@ -69,7 +53,7 @@ fn build_user(email: String, username: String) -> User {
 // }
 
 fn area(rectangle: &Rectangle) -> u32 {
-   rectangle.width * rectangle.height
+    rectangle.width * rectangle.height
 }
 
 
@@ -95,9 +79,28 @@ fn main() {
         ..user1
     };
 
+    // the library's user checks what it is built from, so this can fail
+    match user::build_user("someone@example.com", "some username") {
+        Ok(user) => println!("Built {:?}", user),
+        Err(e) => println!("Could not build the user: {}", e),
+    }
+    let mut user3 = user::User::builder()
+        .username("someusername")
+        .email("someone@example.com")
+        .build()
+        .expect("a valid user");
+    user3.sign_in().expect("an active user");
+    println!("{} signed in {} time(s)", user3.username(), user3.sign_in_count());
+
     let black = Color(0, 0, 0);
     let origin = Point(0, 0, 0);
 
+    // the library's Color checks that each component fits in a byte
+    match color::Color::try_from((black.0, black.1, black.2)) {
+        Ok(black) => println!("black is {}, contrast with white {:.1}", black, black.contrast_ratio(&color::Color::WHITE)),
+        Err(e) => println!("not a color: {}", e),
+    }
+
     // let width1 = 30;
     // let height1 = 50;
 
@@ -113,9 +116,9 @@ fn main() {
     //     area(rect1)
     // );
 
-    let rect1 = Rectangle { width: 30, height: 50};
-    let rect2 = Rectangle { width: 10, height: 40};
-    let rect3 = Rectangle { width: 60, height: 45};
+    let rect1 = Rectangle { x: 0, y: 0, width: 30, height: 50 };
+    let rect2 = Rectangle::new(10, 40);
+    let rect3 = Rectangle::at(20, 10, 60, 45);
 
     println!(
         "The area of a rectangle is {} square pixels.",
@@ -138,7 +141,22 @@ fn main() {
     println!("Can rect2 hold rect3? {}", rect2.can_hold(&rect3));
 
     let sq = Rectangle::square(3);
-    
+
+    println!("Does rect1 overlap rect3? {}", rect1.intersects(&rect3));
+    println!("The overlap of rect1 and rect3 is {:?}", rect1.intersection(&rect3));
+    println!("Together rect1 and rect3 cover {:?}", rect1.union(&rect3));
+    println!("Is (1, 1) inside sq? {}", sq.contains_point(1, 1));
+
+    // different shapes behind one trait object type
+    let shapes: Vec<Box<dyn Shape>> = vec![
+        Box::new(rect1),
+        Box::new(Circle { center: shape::Point::new(0.0, 0.0), radius: 10.0 }),
+        Box::new(Square { top_left: shape::Point::new(5.0, 5.0), side: 4.0 }),
+    ];
+    for s in &shapes {
+        println!("area {:.2}, perimeter {:.2}", s.area(), s.perimeter());
+    }
+    println!("All shapes together cover {:.2}", shape::total_area(&shapes));
 }
 
 
@@ -0,0 +1,406 @@
+use std::cmp::Reverse;
+use std::convert::TryFrom;
+
+use crate::rectangle::Rectangle;
+
+// Put many rectangles into one container without any of them overlapping,
+// e.g. sprites into a texture atlas. Rectangles are never rotated.
+//
+// All three strategies place the big rectangles first, since small ones are
+// much easier to squeeze into whatever space is left at the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // Rows ("shelves") as high as the first rectangle put on them, filled left to right.
+    // Fast and simple, wastes the space above lower rectangles on a shelf.
+    Shelf,
+    // Keep a list of free rectangles, put each rectangle into the free one it fills best
+    // and cut what is left into two new free rectangles.
+    Guillotine,
+    // Like Guillotine, but the free rectangles are all maximal and may overlap each other,
+    // which finds more space at the cost of more bookkeeping. Usually packs the tightest.
+    MaxRects,
+}
+
+// rectangle number `index` of the input went to `rect`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub index: usize,
+    pub rect: Rectangle,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packing {
+    pub container: Rectangle,
+    // in the order of the input
+    pub placed: Vec<Placement>,
+    // indexes of the rectangles that did not fit
+    pub unplaced: Vec<usize>,
+}
+
+impl Packing {
+    pub fn used_area(&self) -> u64 {
+        self.placed.iter().map(|placement| placement.rect.area()).sum()
+    }
+
+    pub fn unused_area(&self) -> u64 {
+        self.container.area() - self.used_area()
+    }
+
+    // how much of the container is covered, from 0.0 to 1.0
+    pub fn occupancy(&self) -> f64 {
+        if self.container.area() == 0 {
+            return 0.0;
+        }
+        self.used_area() as f64 / self.container.area() as f64
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.unplaced.is_empty()
+    }
+}
+
+// Only the sizes of `rectangles` matter, their positions are ignored.
+// Placed rectangles are positioned inside `container`, wherever that is.
+pub fn pack(container: &Rectangle, rectangles: &[Rectangle], strategy: Strategy) -> Packing {
+    // biggest first, the sort is stable so equal ones keep their input order
+    let mut order: Vec<usize> = (0..rectangles.len()).collect();
+    match strategy {
+        // shelves are as high as their first rectangle, so tallest first
+        Strategy::Shelf => order.sort_by_key(|&i| Reverse((rectangles[i].height, rectangles[i].width))),
+        _ => order.sort_by_key(|&i| Reverse(rectangles[i].area())),
+    }
+
+    let mut packer: Box<dyn Packer> = match strategy {
+        Strategy::Shelf => Box::new(ShelfPacker::new(*container)),
+        Strategy::Guillotine => Box::new(GuillotinePacker::new(*container)),
+        Strategy::MaxRects => Box::new(MaxRectsPacker::new(*container)),
+    };
+
+    let mut placed = Vec::new();
+    let mut unplaced = Vec::new();
+    for index in order {
+        let size = rectangles[index];
+        // nothing to place, and it can't overlap anything either
+        let spot = if size.is_empty() {
+            Some(Rectangle::at(container.x, container.y, size.width, size.height))
+        } else {
+            packer.place(size.width, size.height)
+        };
+
+        match spot {
+            Some(rect) => placed.push(Placement { index, rect }),
+            None => unplaced.push(index),
+        }
+    }
+
+    placed.sort_by_key(|placement| placement.index);
+    unplaced.sort();
+    Packing { container: *container, placed, unplaced }
+}
+
+// each strategy finds a free spot for one rectangle at a time
+trait Packer {
+    fn place(&mut self, width: u32, height: u32) -> Option<Rectangle>;
+}
+
+// the same rectangle moved `right` to the right and `down` downwards, None when it would
+// start past i32::MAX, where no rectangle can be placed
+fn offset(rect: &Rectangle, right: u32, down: u32) -> Option<Rectangle> {
+    Some(Rectangle::at(
+        i32::try_from(rect.left() + right as i64).ok()?,
+        i32::try_from(rect.top() + down as i64).ok()?,
+        rect.width,
+        rect.height,
+    ))
+}
+
+struct Shelf {
+    top: u32,
+    height: u32,
+    // the width already used on this shelf
+    used: u32,
+}
+
+struct ShelfPacker {
+    container: Rectangle,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    fn new(container: Rectangle) -> ShelfPacker {
+        ShelfPacker { container, shelves: Vec::new() }
+    }
+}
+
+impl Packer for ShelfPacker {
+    fn place(&mut self, width: u32, height: u32) -> Option<Rectangle> {
+        let container = self.container;
+        let corner = Rectangle::at(container.x, container.y, width, height);
+        let spot = |shelf: &Shelf| {
+            if shelf.height >= height && container.width - shelf.used >= width {
+                offset(&corner, shelf.used, shelf.top)
+            } else {
+                None
+            }
+        };
+
+        // the first shelf with room left, otherwise start a new shelf below the last one
+        if let Some((shelf, rect)) = self.shelves.iter_mut().find_map(|shelf| spot(shelf).map(|rect| (shelf, rect))) {
+            shelf.used += width;
+            return Some(rect);
+        }
+
+        let top = self.shelves.last().map_or(0, |shelf| shelf.top + shelf.height);
+        if width > container.width || height > container.height - top {
+            return None;
+        }
+        let rect = offset(&corner, 0, top)?;
+        self.shelves.push(Shelf { top, height, used: width });
+        Some(rect)
+    }
+}
+
+struct GuillotinePacker {
+    free: Vec<Rectangle>,
+}
+
+impl GuillotinePacker {
+    fn new(container: Rectangle) -> GuillotinePacker {
+        GuillotinePacker { free: vec![container] }
+    }
+}
+
+impl Packer for GuillotinePacker {
+    fn place(&mut self, width: u32, height: u32) -> Option<Rectangle> {
+        let wanted = Rectangle::new(width, height);
+
+        // best area fit: the free rectangle with the least space left over
+        let (i, _) = self
+            .free
+            .iter()
+            .enumerate()
+            .filter(|(_, free)| free.can_fit(&wanted))
+            .min_by_key(|(_, free)| free.area() - wanted.area())?;
+        let free = self.free.swap_remove(i);
+        let placed = Rectangle::at(free.x, free.y, width, height);
+
+        // Cut the leftover L shape into two rectangles. Splitting along the shorter
+        // leftover side keeps the bigger of the two pieces as large as possible.
+        let right_width = free.width - width;
+        let below_height = free.height - height;
+        let (right, below) = if right_width < below_height {
+            (
+                Rectangle::at(free.x, free.y, right_width, height),
+                Rectangle::at(free.x, free.y, free.width, below_height),
+            )
+        } else {
+            (
+                Rectangle::at(free.x, free.y, right_width, free.height),
+                Rectangle::at(free.x, free.y, width, below_height),
+            )
+        };
+        // a piece starting past i32::MAX can't hold anything
+        for piece in [offset(&right, width, 0), offset(&below, 0, height)].iter().flatten() {
+            if !piece.is_empty() {
+                self.free.push(*piece);
+            }
+        }
+
+        Some(placed)
+    }
+}
+
+struct MaxRectsPacker {
+    free: Vec<Rectangle>,
+}
+
+impl MaxRectsPacker {
+    fn new(container: Rectangle) -> MaxRectsPacker {
+        MaxRectsPacker { free: vec![container] }
+    }
+}
+
+impl Packer for MaxRectsPacker {
+    fn place(&mut self, width: u32, height: u32) -> Option<Rectangle> {
+        let wanted = Rectangle::new(width, height);
+
+        // best short side fit: the free rectangle where the smaller leftover side is smallest
+        let free = *self
+            .free
+            .iter()
+            .filter(|free| free.can_fit(&wanted))
+            .min_by_key(|free| {
+                let (w, h) = (free.width - width, free.height - height);
+                (w.min(h), w.max(h))
+            })?;
+        let placed = Rectangle::at(free.x, free.y, width, height);
+
+        // every free rectangle the new one overlaps is replaced by the (up to four)
+        // maximal pieces of it that are left around the placed rectangle
+        let mut free_rects = Vec::new();
+        for free in self.free.drain(..) {
+            if !free.intersects(&placed) {
+                free_rects.push(free);
+                continue;
+            }
+            // the pieces right of and below it are None when they would start past i32::MAX
+            let pieces = [
+                // left of the placed rectangle
+                Some(Rectangle::at(free.x, free.y, (placed.left() - free.left()).max(0) as u32, free.height)),
+                // right
+                i32::try_from(placed.right()).ok().map(|x| {
+                    Rectangle::at(x, free.y, (free.right() - placed.right()).max(0) as u32, free.height)
+                }),
+                // above
+                Some(Rectangle::at(free.x, free.y, free.width, (placed.top() - free.top()).max(0) as u32)),
+                // below
+                i32::try_from(placed.bottom()).ok().map(|y| {
+                    Rectangle::at(free.x, y, free.width, (free.bottom() - placed.bottom()).max(0) as u32)
+                }),
+            ];
+            free_rects.extend(pieces.iter().flatten().filter(|piece| !piece.is_empty()));
+        }
+
+        // a free rectangle inside another one adds nothing
+        let mut maximal: Vec<Rectangle> = Vec::new();
+        for (i, rect) in free_rects.iter().enumerate() {
+            let redundant = free_rects.iter().enumerate().any(|(j, other)| {
+                i != j && other.contains(rect) && (other != rect || j < i)
+            });
+            if !redundant {
+                maximal.push(*rect);
+            }
+        }
+        self.free = maximal;
+
+        Some(placed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRATEGIES: [Strategy; 3] = [Strategy::Shelf, Strategy::Guillotine, Strategy::MaxRects];
+
+    // whatever the strategy, a packing has to follow the rules
+    fn check(packing: &Packing, rectangles: &[Rectangle]) {
+        for placement in &packing.placed {
+            let wanted = rectangles[placement.index];
+            assert_eq!((placement.rect.width, placement.rect.height), (wanted.width, wanted.height));
+            assert!(packing.container.contains(&placement.rect), "{:?} sticks out", placement);
+        }
+        for (i, a) in packing.placed.iter().enumerate() {
+            for b in &packing.placed[i + 1..] {
+                assert!(!a.rect.intersects(&b.rect), "{:?} overlaps {:?}", a, b);
+            }
+        }
+        assert_eq!(packing.placed.len() + packing.unplaced.len(), rectangles.len());
+        assert_eq!(packing.used_area() + packing.unused_area(), packing.container.area());
+    }
+
+    fn sizes(list: &[(u32, u32)]) -> Vec<Rectangle> {
+        list.iter().map(|&(w, h)| Rectangle::new(w, h)).collect()
+    }
+
+    #[test]
+    fn exact_fit_fills_the_container() {
+        let container = Rectangle::new(10, 10);
+        let rectangles = sizes(&[(5, 5), (5, 5), (5, 5), (5, 5)]);
+        for strategy in STRATEGIES.iter() {
+            let packing = pack(&container, &rectangles, *strategy);
+            check(&packing, &rectangles);
+            assert!(packing.is_complete(), "{:?}", strategy);
+            assert_eq!(packing.unused_area(), 0);
+            assert_eq!(packing.occupancy(), 1.0);
+        }
+    }
+
+    #[test]
+    fn mixed_sizes() {
+        let container = Rectangle::new(64, 64);
+        let rectangles = sizes(&[
+            (32, 32), (16, 16), (16, 16), (8, 24), (24, 8), (40, 10), (10, 40),
+            (12, 12), (5, 30), (30, 5), (7, 7), (7, 7), (3, 3), (20, 6),
+        ]);
+        for strategy in STRATEGIES.iter() {
+            let packing = pack(&container, &rectangles, *strategy);
+            check(&packing, &rectangles);
+        }
+        let packing = pack(&container, &rectangles, Strategy::MaxRects);
+        assert!(packing.is_complete());
+    }
+
+    #[test]
+    fn rectangles_that_do_not_fit_are_reported() {
+        let container = Rectangle::new(10, 10);
+        let rectangles = sizes(&[(11, 1), (6, 6), (6, 6), (1, 11)]);
+        for strategy in STRATEGIES.iter() {
+            let packing = pack(&container, &rectangles, *strategy);
+            check(&packing, &rectangles);
+            // only one of the 6x6 fits next to nothing else
+            assert_eq!(packing.placed.len(), 1, "{:?}", strategy);
+            assert_eq!(packing.unplaced, vec![0, 2, 3]);
+            assert_eq!(packing.unused_area(), 64);
+        }
+    }
+
+    #[test]
+    fn placements_follow_the_container() {
+        let container = Rectangle::at(-100, 50, 20, 20);
+        let rectangles = sizes(&[(10, 20), (10, 10)]);
+        for strategy in STRATEGIES.iter() {
+            let packing = pack(&container, &rectangles, *strategy);
+            check(&packing, &rectangles);
+            assert!(packing.is_complete());
+            assert_eq!(packing.placed[0].rect.x, -100);
+        }
+    }
+
+    #[test]
+    fn nothing_is_placed_past_i32_max() {
+        // room for 3x3 of the 10x10, but only the first two columns and rows start at or
+        // before i32::MAX
+        let container = Rectangle::at(i32::MAX - 10, i32::MAX - 10, 30, 30);
+        let rectangles = sizes(&[(10, 10); 9]);
+        for strategy in STRATEGIES.iter() {
+            let packing = pack(&container, &rectangles, *strategy);
+            check(&packing, &rectangles);
+            assert_eq!(packing.placed.len(), 4, "{:?}", strategy);
+            for placement in &packing.placed {
+                assert!(placement.rect.x >= i32::MAX - 10 && placement.rect.y >= i32::MAX - 10, "{:?}", placement);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_input_and_zero_sizes() {
+        let container = Rectangle::new(4, 4);
+        for strategy in STRATEGIES.iter() {
+            let packing = pack(&container, &[], *strategy);
+            assert!(packing.placed.is_empty());
+            assert_eq!(packing.unused_area(), 16);
+
+            let rectangles = sizes(&[(0, 0), (4, 4), (0, 3)]);
+            let packing = pack(&container, &rectangles, *strategy);
+            check(&packing, &rectangles);
+            assert!(packing.is_complete());
+        }
+
+        let nothing = Rectangle::new(0, 0);
+        let packing = pack(&nothing, &sizes(&[(1, 1)]), Strategy::MaxRects);
+        assert_eq!(packing.unplaced, vec![0]);
+        assert_eq!(packing.occupancy(), 0.0);
+    }
+
+    #[test]
+    fn shelves_stack_downwards() {
+        let container = Rectangle::new(10, 10);
+        let rectangles = sizes(&[(6, 4), (6, 3), (4, 3)]);
+        let packing = pack(&container, &rectangles, Strategy::Shelf);
+        check(&packing, &rectangles);
+        let spots: Vec<(i32, i32)> = packing.placed.iter().map(|p| (p.rect.x, p.rect.y)).collect();
+        // the 6x4 opens the first shelf, the 6x3 doesn't fit beside it, the 4x3 does
+        assert_eq!(spots, vec![(0, 0), (0, 4), (6, 0)]);
+    }
+}
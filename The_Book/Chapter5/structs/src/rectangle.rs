@@ -0,0 +1,240 @@
+use std::convert::TryFrom;
+
+// A rectangle placed somewhere on a plane, like a window on a screen:
+// (x, y) is the top left corner and y grows downwards.
+// The edges are half open, a rectangle covers x..x + width and y..y + height,
+// so two rectangles that only share an edge don't overlap.
+//
+// Positions are i32 and sizes u32. Anything computed from both (the right and
+// bottom edges, areas) is done in i64/u64 so it can't overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rectangle {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rectangle {
+    // associated functions, often used for constructors that will return a new instance
+    // a rectangle with its top left corner at the origin
+    pub fn new(width: u32, height: u32) -> Rectangle {
+        Rectangle { x: 0, y: 0, width, height }
+    }
+
+    pub fn at(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle { x, y, width, height }
+    }
+
+    pub fn square(size: u32) -> Rectangle {
+        Rectangle::new(size, size)
+    }
+
+    // self is Rectangle due to this method's being inside impl Rectangle context
+    // u32 * u32 always fits in a u64, so this can't overflow
+    pub fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    pub fn perimeter(&self) -> u64 {
+        2 * (self.width as u64 + self.height as u64)
+    }
+
+    // a rectangle without width or height covers no points at all
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn left(&self) -> i64 {
+        self.x as i64
+    }
+
+    pub fn top(&self) -> i64 {
+        self.y as i64
+    }
+
+    // the first column to the right that is not covered any more
+    pub fn right(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    // the first row below that is not covered any more
+    pub fn bottom(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+
+    // Only compares sizes, not positions: can `other` fit strictly inside this
+    // rectangle if it is moved there
+    pub fn can_hold(&self, other: &Rectangle) -> bool {
+        self.width > other.width && self.height > other.height
+    }
+
+    // Like can_hold, but `other` may be just as wide or high: it fits exactly
+    pub fn can_fit(&self, other: &Rectangle) -> bool {
+        self.width >= other.width && self.height >= other.height
+    }
+
+    pub fn contains_point(&self, x: i32, y: i32) -> bool {
+        let (x, y) = (x as i64, y as i64);
+        self.left() <= x && x < self.right() && self.top() <= y && y < self.bottom()
+    }
+
+    // `other` lies completely within this rectangle where it is now.
+    // An empty rectangle is contained anywhere inside the bounds, edges included.
+    pub fn contains(&self, other: &Rectangle) -> bool {
+        self.left() <= other.left()
+            && other.right() <= self.right()
+            && self.top() <= other.top()
+            && other.bottom() <= self.bottom()
+    }
+
+    // the two rectangles share at least one point, touching edges don't count
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    // the overlapping part, None when the rectangles don't overlap
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if left >= right || top >= bottom {
+            return None;
+        }
+
+        // the overlap lies inside both rectangles, so it fits their types
+        Some(Rectangle::at(left as i32, top as i32, (right - left) as u32, (bottom - top) as u32))
+    }
+
+    // The smallest rectangle covering both. Empty rectangles are ignored.
+    // None when the result would be wider or higher than a u32 can hold.
+    pub fn union(&self, other: &Rectangle) -> Option<Rectangle> {
+        if other.is_empty() {
+            return Some(*self);
+        }
+        if self.is_empty() {
+            return Some(*other);
+        }
+
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        let width = u32::try_from(right - left).ok()?;
+        let height = u32::try_from(bottom - top).ok()?;
+        Some(Rectangle::at(left as i32, top as i32, width, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn larger_can_hold_smaller() {
+        let larger = Rectangle::new(8, 7);
+        let smaller = Rectangle::new(5, 1);
+        assert!(larger.can_hold(&smaller));
+        assert!(!smaller.can_hold(&larger));
+        // strictly larger in both directions
+        assert!(!larger.can_hold(&Rectangle::new(8, 1)));
+        assert!(larger.can_fit(&Rectangle::new(8, 1)));
+        assert!(larger.can_fit(&larger));
+        assert!(!smaller.can_fit(&larger));
+    }
+
+    #[test]
+    fn area_and_perimeter() {
+        let rect = Rectangle::new(30, 50);
+        assert_eq!(rect.area(), 1500);
+        assert_eq!(rect.perimeter(), 160);
+        assert_eq!(Rectangle::square(3).area(), 9);
+        assert_eq!(Rectangle::new(0, 50).area(), 0);
+    }
+
+    #[test]
+    fn area_does_not_overflow() {
+        let huge = Rectangle::new(u32::MAX, u32::MAX);
+        assert_eq!(huge.area(), u32::MAX as u64 * u32::MAX as u64);
+        assert_eq!(huge.perimeter(), 4 * u32::MAX as u64);
+    }
+
+    #[test]
+    fn contains_point_uses_half_open_edges() {
+        let rect = Rectangle::at(10, 20, 5, 5);
+        assert!(rect.contains_point(10, 20));
+        assert!(rect.contains_point(14, 24));
+        assert!(!rect.contains_point(15, 24));
+        assert!(!rect.contains_point(14, 25));
+        assert!(!rect.contains_point(9, 20));
+        assert!(!Rectangle::at(0, 0, 0, 10).contains_point(0, 0));
+    }
+
+    #[test]
+    fn contains_other_rectangles() {
+        let outer = Rectangle::at(0, 0, 10, 10);
+        assert!(outer.contains(&Rectangle::at(2, 2, 8, 8)));
+        assert!(outer.contains(&outer));
+        assert!(!outer.contains(&Rectangle::at(2, 2, 9, 8)));
+        assert!(!outer.contains(&Rectangle::at(-1, 0, 2, 2)));
+    }
+
+    #[test]
+    fn overlapping_rectangles_intersect() {
+        let a = Rectangle::at(0, 0, 10, 10);
+        let b = Rectangle::at(5, 5, 10, 10);
+        assert!(a.intersects(&b));
+        assert_eq!(a.intersection(&b), Some(Rectangle::at(5, 5, 5, 5)));
+        assert_eq!(b.intersection(&a), a.intersection(&b));
+
+        let inner = Rectangle::at(2, 3, 4, 4);
+        assert_eq!(a.intersection(&inner), Some(inner));
+    }
+
+    #[test]
+    fn touching_edges_do_not_intersect() {
+        let a = Rectangle::at(0, 0, 10, 10);
+        assert!(!a.intersects(&Rectangle::at(10, 0, 10, 10)));
+        assert!(!a.intersects(&Rectangle::at(0, 10, 10, 10)));
+        assert!(!a.intersects(&Rectangle::at(10, 10, 1, 1)));
+        assert_eq!(a.intersection(&Rectangle::at(20, 20, 1, 1)), None);
+    }
+
+    #[test]
+    fn zero_size_rectangles_never_intersect() {
+        let a = Rectangle::at(0, 0, 10, 10);
+        assert!(!a.intersects(&Rectangle::at(5, 5, 0, 0)));
+        assert!(!a.intersects(&Rectangle::at(5, 5, 0, 3)));
+        assert!(!Rectangle::at(5, 5, 0, 0).intersects(&a));
+    }
+
+    #[test]
+    fn union_covers_both() {
+        let a = Rectangle::at(0, 0, 10, 10);
+        let b = Rectangle::at(20, -5, 5, 5);
+        let both = a.union(&b).unwrap();
+        assert_eq!(both, Rectangle::at(0, -5, 25, 15));
+        assert!(both.contains(&a) && both.contains(&b));
+        assert_eq!(a.union(&Rectangle::at(100, 100, 0, 0)), Some(a));
+        assert_eq!(Rectangle::default().union(&b), Some(b));
+    }
+
+    #[test]
+    fn edges_at_the_limits_of_i32() {
+        let far = Rectangle::at(i32::MAX, i32::MAX, u32::MAX, u32::MAX);
+        assert_eq!(far.right(), i32::MAX as i64 + u32::MAX as i64);
+        assert!(far.contains_point(i32::MAX, i32::MAX));
+
+        let near = Rectangle::at(i32::MIN, i32::MIN, 1, 1);
+        assert!(!far.intersects(&near));
+        // the bounding box of both is far wider than u32::MAX
+        assert_eq!(far.union(&near), None);
+        assert_eq!(
+            Rectangle::at(i32::MIN, 0, 1, 1).union(&Rectangle::at(i32::MAX - 1, 0, 1, 1)),
+            Some(Rectangle::at(i32::MIN, 0, u32::MAX, 1))
+        );
+    }
+}
@@ -0,0 +1,392 @@
+use std::f64::consts::PI;
+
+use crate::rectangle::Rectangle;
+
+// A point on the plane, like Point in Rust By Example, y grows downwards like for Rectangle
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+// The smallest axis aligned box around a shape, specified by where the top left
+// and bottom right corners are in space (the Rectangle of Rust By Example)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BoundingBox {
+    pub top_left: Point,
+    pub bottom_right: Point,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> f64 {
+        self.bottom_right.x - self.top_left.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.bottom_right.y - self.top_left.y
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new(
+            (self.top_left.x + self.bottom_right.x) / 2.0,
+            (self.top_left.y + self.bottom_right.y) / 2.0,
+        )
+    }
+
+    // the smallest box around both
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            top_left: Point::new(self.top_left.x.min(other.top_left.x), self.top_left.y.min(other.top_left.y)),
+            bottom_right: Point::new(
+                self.bottom_right.x.max(other.bottom_right.x),
+                self.bottom_right.y.max(other.bottom_right.y),
+            ),
+        }
+    }
+}
+
+// What every shape can do. Traits let rectangles, circles and polygons share this
+// behavior, either generically (fn f<S: Shape>) or through trait objects (Box<dyn Shape>).
+// No method returns Self or takes generic parameters, which keeps the trait usable as dyn Shape.
+pub trait Shape {
+    fn area(&self) -> f64;
+
+    fn perimeter(&self) -> f64;
+
+    fn bounding_box(&self) -> BoundingBox;
+
+    // move the shape by dx to the right and dy down
+    fn translate(&mut self, dx: f64, dy: f64);
+
+    // Grow (factor > 1) or shrink (factor < 1) the shape around the center of its
+    // bounding box. Negative factors are taken as positive.
+    fn scale(&mut self, factor: f64);
+}
+
+// a Box<dyn Shape> (or a Box of any shape) is a shape too, so the generic
+// functions below also work on collections of trait objects
+impl<S: Shape + ?Sized> Shape for Box<S> {
+    fn area(&self) -> f64 {
+        (**self).area()
+    }
+
+    fn perimeter(&self) -> f64 {
+        (**self).perimeter()
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        (**self).bounding_box()
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        (**self).translate(dx, dy)
+    }
+
+    fn scale(&mut self, factor: f64) {
+        (**self).scale(factor)
+    }
+}
+
+// Rectangle lives on a whole pixel grid, so translate and scale round to the nearest pixel
+impl Shape for Rectangle {
+    fn area(&self) -> f64 {
+        Rectangle::area(self) as f64
+    }
+
+    fn perimeter(&self) -> f64 {
+        Rectangle::perimeter(self) as f64
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            top_left: Point::new(self.left() as f64, self.top() as f64),
+            bottom_right: Point::new(self.right() as f64, self.bottom() as f64),
+        }
+    }
+
+    // `as` saturates, a rectangle moved past the edge of i32 stays at the edge
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.x = (self.x as f64 + dx).round() as i32;
+        self.y = (self.y as f64 + dy).round() as i32;
+    }
+
+    fn scale(&mut self, factor: f64) {
+        let center = Shape::bounding_box(self).center();
+        let width = (self.width as f64 * factor.abs()).round();
+        let height = (self.height as f64 * factor.abs()).round();
+        self.x = (center.x - width / 2.0).round() as i32;
+        self.y = (center.y - height / 2.0).round() as i32;
+        self.width = width as u32;
+        self.height = height as u32;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Square {
+    pub top_left: Point,
+    pub side: f64,
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+
+    fn perimeter(&self) -> f64 {
+        4.0 * self.side
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            top_left: self.top_left,
+            bottom_right: Point::new(self.top_left.x + self.side, self.top_left.y + self.side),
+        }
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.top_left.x += dx;
+        self.top_left.y += dy;
+    }
+
+    fn scale(&mut self, factor: f64) {
+        let center = self.bounding_box().center();
+        self.side *= factor.abs();
+        self.top_left = Point::new(center.x - self.side / 2.0, center.y - self.side / 2.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * PI * self.radius
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            top_left: Point::new(self.center.x - self.radius, self.center.y - self.radius),
+            bottom_right: Point::new(self.center.x + self.radius, self.center.y + self.radius),
+        }
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.center.x += dx;
+        self.center.y += dy;
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.radius *= factor.abs();
+    }
+}
+
+// A closed polygon, the last vertex connects back to the first.
+// The edges shouldn't cross each other, the area of a self-intersecting polygon isn't meaningful.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    // None for fewer than three vertices, that is a point or a line but not a polygon
+    pub fn new(vertices: Vec<Point>) -> Option<Polygon> {
+        if vertices.len() < 3 {
+            return None;
+        }
+        Some(Polygon { vertices })
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    // each vertex paired with the next one, wrapping around at the end
+    fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        self.vertices.iter().zip(self.vertices.iter().cycle().skip(1))
+    }
+}
+
+impl Shape for Polygon {
+    // the shoelace formula, works whichever way round the vertices go
+    fn area(&self) -> f64 {
+        let twice_area: f64 = self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum();
+        twice_area.abs() / 2.0
+    }
+
+    fn perimeter(&self) -> f64 {
+        self.edges().map(|(a, b)| a.distance(b)).sum()
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let first = BoundingBox { top_left: self.vertices[0], bottom_right: self.vertices[0] };
+        self.vertices.iter().fold(first, |bounds, vertex| {
+            bounds.union(&BoundingBox { top_left: *vertex, bottom_right: *vertex })
+        })
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        for vertex in &mut self.vertices {
+            vertex.x += dx;
+            vertex.y += dy;
+        }
+    }
+
+    fn scale(&mut self, factor: f64) {
+        let center = self.bounding_box().center();
+        for vertex in &mut self.vertices {
+            vertex.x = center.x + (vertex.x - center.x) * factor.abs();
+            vertex.y = center.y + (vertex.y - center.y) * factor.abs();
+        }
+    }
+}
+
+// Generic functions: monomorphized for each type of shape they are called with.
+// Thanks to the impl for Box<S> they take a &[Box<dyn Shape>] too.
+pub fn total_area<S: Shape>(shapes: &[S]) -> f64 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+pub fn largest<S: Shape>(shapes: &[S]) -> Option<&S> {
+    shapes.iter().max_by(|a, b| a.area().total_cmp(&b.area()))
+}
+
+// the box around all of the shapes, None when there are none
+pub fn bounding_box_of<S: Shape>(shapes: &[S]) -> Option<BoundingBox> {
+    shapes
+        .iter()
+        .map(|shape| shape.bounding_box())
+        .reduce(|all, bounds| all.union(&bounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn corners(bounds: BoundingBox) -> (f64, f64, f64, f64) {
+        (bounds.top_left.x, bounds.top_left.y, bounds.bottom_right.x, bounds.bottom_right.y)
+    }
+
+    fn triangle() -> Polygon {
+        Polygon::new(vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(0.0, 3.0)]).unwrap()
+    }
+
+    #[test]
+    fn rectangle_is_a_shape() {
+        let mut rect = Rectangle::at(10, 20, 30, 50);
+        assert_eq!(Shape::area(&rect), 1500.0);
+        assert_eq!(Shape::perimeter(&rect), 160.0);
+        assert_eq!(corners(rect.bounding_box()), (10.0, 20.0, 40.0, 70.0));
+
+        rect.translate(-10.4, 0.6);
+        assert_eq!((rect.x, rect.y), (0, 21));
+
+        rect.scale(2.0);
+        assert_eq!(rect, Rectangle::at(-15, -4, 60, 100));
+        rect.scale(0.5);
+        assert_eq!(rect, Rectangle::at(0, 21, 30, 50));
+    }
+
+    #[test]
+    fn square_is_a_shape() {
+        let mut square = Square { top_left: Point::new(1.0, 1.0), side: 2.0 };
+        assert_eq!(square.area(), 4.0);
+        assert_eq!(square.perimeter(), 8.0);
+        square.scale(2.0);
+        assert_eq!(corners(square.bounding_box()), (0.0, 0.0, 4.0, 4.0));
+        square.translate(1.0, -1.0);
+        assert_eq!(square.top_left, Point::new(1.0, -1.0));
+    }
+
+    #[test]
+    fn circle_is_a_shape() {
+        let mut circle = Circle { center: Point::new(0.0, 0.0), radius: 1.0 };
+        assert!(close(circle.area(), PI));
+        assert!(close(circle.perimeter(), 2.0 * PI));
+        circle.scale(-3.0);
+        circle.translate(3.0, 3.0);
+        assert_eq!(corners(circle.bounding_box()), (0.0, 0.0, 6.0, 6.0));
+    }
+
+    #[test]
+    fn polygon_is_a_shape() {
+        let mut triangle = triangle();
+        assert_eq!(triangle.area(), 6.0);
+        assert_eq!(triangle.perimeter(), 12.0);
+        assert_eq!(corners(triangle.bounding_box()), (0.0, 0.0, 4.0, 3.0));
+
+        triangle.scale(2.0);
+        assert_eq!(triangle.area(), 24.0);
+        assert_eq!(corners(triangle.bounding_box()), (-2.0, -1.5, 6.0, 4.5));
+
+        triangle.translate(2.0, 1.5);
+        assert_eq!(triangle.vertices()[0], Point::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn polygon_area_ignores_orientation() {
+        let clockwise = Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 2.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 0.0),
+        ])
+        .unwrap();
+        assert_eq!(clockwise.area(), 4.0);
+        assert!(Polygon::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)]).is_none());
+    }
+
+    #[test]
+    fn shapes_as_trait_objects() {
+        let mut shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new(Rectangle::new(2, 3)),
+            Box::new(Square { top_left: Point::new(-1.0, -1.0), side: 1.0 }),
+            Box::new(Circle { center: Point::new(10.0, 10.0), radius: 2.0 }),
+            Box::new(triangle()),
+        ];
+
+        assert!(close(total_area(&shapes), 6.0 + 1.0 + 4.0 * PI + 6.0));
+        assert!(close(largest(&shapes).unwrap().area(), 4.0 * PI));
+        assert_eq!(corners(bounding_box_of(&shapes).unwrap()), (-1.0, -1.0, 12.0, 12.0));
+
+        for shape in shapes.iter_mut() {
+            shape.translate(1.0, 1.0);
+        }
+        assert_eq!(corners(bounding_box_of(&shapes).unwrap()), (0.0, 0.0, 13.0, 13.0));
+    }
+
+    #[test]
+    fn shapes_generically() {
+        let circles = [
+            Circle { center: Point::default(), radius: 1.0 },
+            Circle { center: Point::default(), radius: 3.0 },
+        ];
+        assert_eq!(largest(&circles).unwrap().radius, 3.0);
+        assert!(close(total_area(&circles), 10.0 * PI));
+
+        let none: [Square; 0] = [];
+        assert_eq!(largest(&none), None);
+        assert_eq!(bounding_box_of(&none), None);
+        assert_eq!(total_area(&none), 0.0);
+    }
+}
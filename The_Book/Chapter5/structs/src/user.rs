@@ -0,0 +1,357 @@
+use std::fmt;
+
+pub mod patch;
+pub mod store;
+
+pub use self::patch::UserPatch;
+
+// A user account. Unlike the User in main.rs the fields are private: the only ways to get
+// a User are build_user and UserBuilder, which validate the username and email first,
+// so every User around has valid ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    username: String,
+    email: String,
+    sign_in_count: u64,
+    active: bool,
+}
+
+pub const USERNAME_MAX: usize = 32;
+// the limits for addresses that mail servers actually accept
+pub const EMAIL_MAX: usize = 254;
+const LOCAL_PART_MAX: usize = 64;
+const DOMAIN_LABEL_MAX: usize = 63;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsernameError {
+    Empty,
+    TooLong(usize),
+    // letters, digits, _ and - only
+    InvalidChar(char),
+}
+
+impl fmt::Display for UsernameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UsernameError::Empty => write!(f, "the username is empty"),
+            UsernameError::TooLong(len) => {
+                write!(f, "the username has {} characters, at most {} are allowed", len, USERNAME_MAX)
+            }
+            UsernameError::InvalidChar(c) => {
+                write!(f, "'{}' can't be used in a username, only letters, digits, _ and -", c)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailError {
+    Empty,
+    TooLong(usize),
+    // there has to be exactly one @
+    MissingAt,
+    TooManyAts,
+    // the part before the @ is empty, too long or has dots in the wrong places
+    InvalidLocalPart,
+    // the part after the @ isn't a domain name like example.com
+    InvalidDomain,
+    InvalidChar(char),
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmailError::Empty => write!(f, "the email address is empty"),
+            EmailError::TooLong(len) => {
+                write!(f, "the email address has {} characters, at most {} are allowed", len, EMAIL_MAX)
+            }
+            EmailError::MissingAt => write!(f, "the email address has no @"),
+            EmailError::TooManyAts => write!(f, "the email address has more than one @"),
+            EmailError::InvalidLocalPart => write!(f, "the part of the email address before the @ is not valid"),
+            EmailError::InvalidDomain => write!(f, "the part of the email address after the @ is not a domain"),
+            EmailError::InvalidChar(c) => write!(f, "'{}' can't be used in an email address", c),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserError {
+    Username(UsernameError),
+    Email(EmailError),
+    // UserBuilder::build without a username or email
+    Missing(&'static str),
+    // deactivated users can't sign in
+    Inactive,
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UserError::Username(e) => write!(f, "invalid username: {}", e),
+            UserError::Email(e) => write!(f, "invalid email: {}", e),
+            UserError::Missing(field) => write!(f, "a user needs a {}", field),
+            UserError::Inactive => write!(f, "the account is not active"),
+        }
+    }
+}
+
+impl std::error::Error for UserError {}
+
+impl From<UsernameError> for UserError {
+    fn from(e: UsernameError) -> UserError {
+        UserError::Username(e)
+    }
+}
+
+impl From<EmailError> for UserError {
+    fn from(e: EmailError) -> UserError {
+        UserError::Email(e)
+    }
+}
+
+// Surrounding whitespace is dropped, otherwise the username is kept as typed
+pub fn validate_username(username: &str) -> Result<String, UsernameError> {
+    let username = username.trim();
+    if username.is_empty() {
+        return Err(UsernameError::Empty);
+    }
+    let len = username.chars().count();
+    if len > USERNAME_MAX {
+        return Err(UsernameError::TooLong(len));
+    }
+    if let Some(c) = username.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-')) {
+        return Err(UsernameError::InvalidChar(c));
+    }
+    Ok(username.to_string())
+}
+
+// Checks the common shape of an address, local@example.com, not everything RFC 5322
+// allows (no quoted local parts, no IP address domains). The domain is case insensitive
+// and gets lowercased, the local part is kept as typed.
+pub fn validate_email(email: &str) -> Result<String, EmailError> {
+    let email = email.trim();
+    if email.is_empty() {
+        return Err(EmailError::Empty);
+    }
+    let len = email.chars().count();
+    if len > EMAIL_MAX {
+        return Err(EmailError::TooLong(len));
+    }
+
+    let mut parts = email.split('@');
+    // split always yields at least one part
+    let local = parts.next().unwrap_or_default();
+    let domain = parts.next().ok_or(EmailError::MissingAt)?;
+    if parts.next().is_some() {
+        return Err(EmailError::TooManyAts);
+    }
+
+    const LOCAL_SPECIAL: &str = ".!#$%&'*+/=?^_`{|}~-";
+    if let Some(c) = local.chars().find(|c| !(c.is_ascii_alphanumeric() || LOCAL_SPECIAL.contains(*c))) {
+        return Err(EmailError::InvalidChar(c));
+    }
+    if local.is_empty()
+        || local.len() > LOCAL_PART_MAX
+        || local.starts_with('.')
+        || local.ends_with('.')
+        || local.contains("..")
+    {
+        return Err(EmailError::InvalidLocalPart);
+    }
+
+    if let Some(c) = domain.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '.')) {
+        return Err(EmailError::InvalidChar(c));
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_label = |label: &&str| {
+        !label.is_empty() && label.len() <= DOMAIN_LABEL_MAX && !label.starts_with('-') && !label.ends_with('-')
+    };
+    // at least a name and a top level domain
+    if labels.len() < 2 || !labels.iter().all(valid_label) {
+        return Err(EmailError::InvalidDomain);
+    }
+
+    Ok(format!("{}@{}", local, domain.to_ascii_lowercase()))
+}
+
+// the validated version of build_user in main.rs: an active user that hasn't signed in yet
+pub fn build_user(email: &str, username: &str) -> Result<User, UserError> {
+    User::builder().email(email).username(username).build()
+}
+
+impl User {
+    pub fn builder() -> UserBuilder {
+        UserBuilder::default()
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    pub fn sign_in_count(&self) -> u64 {
+        self.sign_in_count
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn set_email(&mut self, email: &str) -> Result<(), EmailError> {
+        self.email = validate_email(email)?;
+        Ok(())
+    }
+
+    // counts the sign in and returns how many there have been so far
+    pub fn sign_in(&mut self) -> Result<u64, UserError> {
+        if !self.active {
+            return Err(UserError::Inactive);
+        }
+        self.sign_in_count = self.sign_in_count.saturating_add(1);
+        Ok(self.sign_in_count)
+    }
+
+    pub fn deactivate(&mut self) {
+        self.active = false;
+    }
+
+    pub fn activate(&mut self) {
+        self.active = true;
+    }
+}
+
+// Collects the fields of a User one by one, build checks them all at once.
+//
+//   let user = User::builder().username("ferris").email("ferris@example.com").build()?;
+#[derive(Debug, Clone, Default)]
+pub struct UserBuilder {
+    username: Option<String>,
+    email: Option<String>,
+    sign_in_count: u64,
+    // None means active, the default for a new account
+    active: Option<bool>,
+}
+
+impl UserBuilder {
+    pub fn username(mut self, username: &str) -> UserBuilder {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> UserBuilder {
+        self.email = Some(email.to_string());
+        self
+    }
+
+    // for accounts that already exist, e.g. loaded from a file
+    pub fn sign_in_count(mut self, count: u64) -> UserBuilder {
+        self.sign_in_count = count;
+        self
+    }
+
+    pub fn active(mut self, active: bool) -> UserBuilder {
+        self.active = Some(active);
+        self
+    }
+
+    pub fn build(self) -> Result<User, UserError> {
+        let username = self.username.ok_or(UserError::Missing("username"))?;
+        let email = self.email.ok_or(UserError::Missing("email"))?;
+
+        Ok(User {
+            username: validate_username(&username)?,
+            email: validate_email(&email)?,
+            sign_in_count: self.sign_in_count,
+            active: self.active.unwrap_or(true),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_valid_users() {
+        let user = build_user(" someone@Example.COM ", "someusername").unwrap();
+        assert_eq!(user.username(), "someusername");
+        assert_eq!(user.email(), "someone@example.com");
+        assert_eq!(user.sign_in_count(), 0);
+        assert!(user.is_active());
+
+        let user = User::builder()
+            .username("old-timer_1")
+            .email("o.t+rust@mail.example.org")
+            .sign_in_count(41)
+            .active(false)
+            .build()
+            .unwrap();
+        assert_eq!(user.sign_in_count(), 41);
+        assert!(!user.is_active());
+    }
+
+    #[test]
+    fn builder_needs_username_and_email() {
+        assert_eq!(
+            User::builder().email("a@example.com").build(),
+            Err(UserError::Missing("username"))
+        );
+        assert_eq!(User::builder().username("a").build(), Err(UserError::Missing("email")));
+        assert_eq!(
+            User::builder().username("a b").email("a@example.com").build(),
+            Err(UserError::Username(UsernameError::InvalidChar(' ')))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_usernames() {
+        assert_eq!(validate_username("  "), Err(UsernameError::Empty));
+        assert_eq!(validate_username(&"a".repeat(33)), Err(UsernameError::TooLong(33)));
+        assert_eq!(validate_username("ferris!"), Err(UsernameError::InvalidChar('!')));
+        assert_eq!(validate_username("fërris"), Err(UsernameError::InvalidChar('ë')));
+        assert_eq!(validate_username(&"a".repeat(32)), Ok("a".repeat(32)));
+    }
+
+    #[test]
+    fn rejects_bad_emails() {
+        assert_eq!(validate_email(""), Err(EmailError::Empty));
+        assert_eq!(validate_email("someone.example.com"), Err(EmailError::MissingAt));
+        assert_eq!(validate_email("a@b@example.com"), Err(EmailError::TooManyAts));
+        assert_eq!(validate_email("@example.com"), Err(EmailError::InvalidLocalPart));
+        assert_eq!(validate_email(".a@example.com"), Err(EmailError::InvalidLocalPart));
+        assert_eq!(validate_email("a..b@example.com"), Err(EmailError::InvalidLocalPart));
+        assert_eq!(validate_email("a,b@example.com"), Err(EmailError::InvalidChar(',')));
+        assert_eq!(validate_email("a@localhost"), Err(EmailError::InvalidDomain));
+        assert_eq!(validate_email("a@example..com"), Err(EmailError::InvalidDomain));
+        assert_eq!(validate_email("a@-example.com"), Err(EmailError::InvalidDomain));
+        assert_eq!(validate_email("a@exa_mple.com"), Err(EmailError::InvalidChar('_')));
+        let long = format!("{}@{}.com", "a".repeat(64), "b".repeat(200));
+        assert_eq!(validate_email(&long), Err(EmailError::TooLong(269)));
+    }
+
+    #[test]
+    fn signing_in_counts() {
+        let mut user = build_user("someone@example.com", "someone").unwrap();
+        assert_eq!(user.sign_in(), Ok(1));
+        assert_eq!(user.sign_in(), Ok(2));
+
+        user.deactivate();
+        assert_eq!(user.sign_in(), Err(UserError::Inactive));
+        assert_eq!(user.sign_in_count(), 2);
+
+        user.activate();
+        assert_eq!(user.sign_in(), Ok(3));
+    }
+
+    #[test]
+    fn changing_the_email_validates_it() {
+        let mut user = build_user("someone@example.com", "someone").unwrap();
+        assert_eq!(user.set_email("another"), Err(EmailError::MissingAt));
+        assert_eq!(user.email(), "someone@example.com");
+        user.set_email("another@example.com").unwrap();
+        assert_eq!(user.email(), "another@example.com");
+    }
+}
@@ -0,0 +1,121 @@
+use super::{validate_email, validate_username, User, UserError};
+
+// A partial update of a User, like the body of a PATCH request: None leaves a field as it is.
+//
+//   let patch = UserPatch { email: Some("new@example.com".to_string()), ..UserPatch::default() };
+//   let updated = patch.apply(&user)?;
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserPatch {
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub sign_in_count: Option<u64>,
+    pub active: Option<bool>,
+}
+
+impl UserPatch {
+    // The fields that differ between `old` and `new`, so that diff(old, new).apply(old) is new
+    pub fn diff(old: &User, new: &User) -> UserPatch {
+        // Some(value) only where the two differ
+        fn changed<T: PartialEq + Clone>(old: &T, new: &T) -> Option<T> {
+            if old == new {
+                None
+            } else {
+                Some(new.clone())
+            }
+        }
+
+        UserPatch {
+            username: changed(&old.username, &new.username),
+            email: changed(&old.email, &new.email),
+            sign_in_count: changed(&old.sign_in_count, &new.sign_in_count),
+            active: changed(&old.active, &new.active),
+        }
+    }
+
+    // nothing would change
+    pub fn is_empty(&self) -> bool {
+        *self == UserPatch::default()
+    }
+
+    // Returns the patched user, `user` itself is left alone. Everything is validated
+    // before anything is merged, so a patch is applied either completely or not at all.
+    pub fn apply(&self, user: &User) -> Result<User, UserError> {
+        let username = match &self.username {
+            Some(username) => validate_username(username)?,
+            None => user.username.clone(),
+        };
+        let email = match &self.email {
+            Some(email) => validate_email(email)?,
+            None => user.email.clone(),
+        };
+
+        Ok(User {
+            username,
+            email,
+            sign_in_count: self.sign_in_count.unwrap_or(user.sign_in_count),
+            active: self.active.unwrap_or(user.active),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::{build_user, EmailError};
+
+    fn user() -> User {
+        build_user("someone@example.com", "someusername").unwrap()
+    }
+
+    #[test]
+    fn empty_patch_changes_nothing() {
+        let patch = UserPatch::default();
+        assert!(patch.is_empty());
+        assert_eq!(patch.apply(&user()), Ok(user()));
+    }
+
+    #[test]
+    fn applies_only_the_given_fields() {
+        let patch = UserPatch {
+            email: Some("Another@EXAMPLE.com".to_string()),
+            active: Some(false),
+            ..UserPatch::default()
+        };
+        let patched = patch.apply(&user()).unwrap();
+        assert_eq!(patched.email(), "Another@example.com");
+        assert!(!patched.is_active());
+        assert_eq!(patched.username(), "someusername");
+        assert_eq!(patched.sign_in_count(), 0);
+    }
+
+    #[test]
+    fn invalid_patches_change_nothing() {
+        let patch = UserPatch {
+            username: Some("newname".to_string()),
+            email: Some("not an address".to_string()),
+            ..UserPatch::default()
+        };
+        assert_eq!(patch.apply(&user()), Err(UserError::Email(EmailError::MissingAt)));
+    }
+
+    #[test]
+    fn diff_then_apply_gives_the_new_user() {
+        let old = user();
+        let mut new = UserPatch { username: Some("anotheruser123".to_string()), ..UserPatch::default() }
+            .apply(&old)
+            .unwrap();
+        new.sign_in().unwrap();
+
+        let patch = UserPatch::diff(&old, &new);
+        assert_eq!(
+            patch,
+            UserPatch {
+                username: Some("anotheruser123".to_string()),
+                sign_in_count: Some(1),
+                ..UserPatch::default()
+            }
+        );
+        assert_eq!(patch.apply(&old), Ok(new.clone()));
+        assert!(UserPatch::diff(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use super::{User, UserError};
+
+// Users are kept in a CSV file with a header line:
+//
+//   username,email,sign_in_count,active
+//   someusername,someone@example.com,3,true
+//
+// Validation doesn't let commas, quotes or line breaks into usernames and emails,
+// so no field ever needs quoting.
+const HEADER: &str = "username,email,sign_in_count,active";
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(PathBuf, io::Error),
+    // the first line isn't the header we write
+    BadHeader(String),
+    BadLine { line: usize, content: String },
+    InvalidUser { line: usize, error: UserError },
+    DuplicateUsername { line: usize, username: String },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Io(path, e) => write!(f, "could not read {}: {}", path.display(), e),
+            StoreError::BadHeader(content) => write!(f, "expected the header '{}', got '{}'", HEADER, content),
+            StoreError::BadLine { line, content } => {
+                write!(f, "line {}: expected username,email,sign_in_count,active, got '{}'", line, content)
+            }
+            StoreError::InvalidUser { line, error } => write!(f, "line {}: {}", line, error),
+            StoreError::DuplicateUsername { line, username } => {
+                write!(f, "line {}: the username {} is already taken", line, username)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+// A missing file means there are no users yet. A broken line is an error rather than
+// skipped: dropping it would lose an account on the next save.
+pub fn load(path: &Path) -> Result<Vec<User>, StoreError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(StoreError::Io(path.to_path_buf(), e)),
+    };
+    parse(&contents)
+}
+
+pub fn parse(contents: &str) -> Result<Vec<User>, StoreError> {
+    let mut lines = contents.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == HEADER => {}
+        Some((_, header)) => return Err(StoreError::BadHeader(header.to_string())),
+        None => return Ok(Vec::new()),
+    }
+
+    let mut users = Vec::new();
+    let mut usernames = HashSet::new();
+    for (i, line) in lines {
+        let line_number = i + 1;
+        let content = line.trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        let bad_line = || StoreError::BadLine { line: line_number, content: content.to_string() };
+        let fields: Vec<&str> = content.split(',').collect();
+        if fields.len() != 4 {
+            return Err(bad_line());
+        }
+        let sign_in_count: u64 = fields[2].parse().map_err(|_| bad_line())?;
+        let active: bool = fields[3].parse().map_err(|_| bad_line())?;
+
+        // going through the builder validates the username and email again
+        let user = User::builder()
+            .username(fields[0])
+            .email(fields[1])
+            .sign_in_count(sign_in_count)
+            .active(active)
+            .build()
+            .map_err(|error| StoreError::InvalidUser { line: line_number, error })?;
+
+        if !usernames.insert(user.username().to_string()) {
+            return Err(StoreError::DuplicateUsername { line: line_number, username: user.username().to_string() });
+        }
+        users.push(user);
+    }
+
+    Ok(users)
+}
+
+pub fn to_csv(users: &[User]) -> String {
+    let mut csv = format!("{}\n", HEADER);
+    for user in users {
+        csv.push_str(&format!("{},{},{},{}\n", user.username, user.email, user.sign_in_count, user.active));
+    }
+    csv
+}
+
+// The users go to users.csv.tmp next to the file and replace it once they are all written
+pub fn save(path: &Path, users: &[User]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(to_csv(users).as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::{build_user, UsernameError};
+
+    fn users() -> Vec<User> {
+        let mut someone = build_user("someone@example.com", "someusername").unwrap();
+        someone.sign_in().unwrap();
+        let mut another = build_user("another@example.com", "anotheruser123").unwrap();
+        another.deactivate();
+        vec![someone, another]
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let csv = to_csv(&users());
+        assert_eq!(
+            csv,
+            "username,email,sign_in_count,active\n\
+             someusername,someone@example.com,1,true\n\
+             anotheruser123,another@example.com,0,false\n"
+        );
+        assert_eq!(parse(&csv).unwrap(), users());
+        assert_eq!(parse("").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("structs-users-{}.csv", std::process::id()));
+        assert_eq!(load(&path).unwrap(), Vec::new());
+
+        save(&path, &users()).unwrap();
+        assert_eq!(load(&path).unwrap(), users());
+        assert!(!path.with_extension("csv.tmp").exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn broken_files_are_errors() {
+        assert!(matches!(parse("name,mail\n"), Err(StoreError::BadHeader(_))));
+        assert!(matches!(
+            parse("username,email,sign_in_count,active\nferris,ferris@example.com,1\n"),
+            Err(StoreError::BadLine { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("username,email,sign_in_count,active\nferris,ferris@example.com,-1,true\n"),
+            Err(StoreError::BadLine { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("username,email,sign_in_count,active\nfer ris,ferris@example.com,1,true\n"),
+            Err(StoreError::InvalidUser { line: 2, error: UserError::Username(UsernameError::InvalidChar(' ')) })
+        ));
+        assert!(matches!(
+            parse("username,email,sign_in_count,active\na,a@example.com,1,true\n\na,b@example.com,2,true\n"),
+            Err(StoreError::DuplicateUsername { line: 4, .. })
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+// Longest prefix match: the trie of RoutingTable against a linear scan of every route.
+// Run with `cargo bench`, it prints the time per lookup for a few table sizes.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use enums::cidr::Network;
+use enums::ip::IpAddr;
+use enums::route::{LinearTable, RoutingTable};
+
+const LOOKUPS: usize = 100_000;
+
+// a small xorshift, so the benchmark needs no dependencies and is the same every run
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn v4(&mut self) -> IpAddr {
+        IpAddr::V4((self.next() as u32).into())
+    }
+
+    fn v6(&mut self) -> IpAddr {
+        // all under 2000::/8, like most routes on the internet
+        IpAddr::V6((0x20 << 120 | (self.next() as u128) << 56).into())
+    }
+}
+
+// prefix lengths roughly as on the internet: mostly /16 to /24 and /32 to /48
+fn routes(random: &mut Random, count: usize) -> Vec<Network> {
+    (0..count)
+        .map(|i| {
+            if i % 2 == 0 {
+                Network::containing(random.v4(), 16 + (random.next() % 9) as u8).unwrap()
+            } else {
+                Network::containing(random.v6(), 32 + (random.next() % 17) as u8).unwrap()
+            }
+        })
+        .collect()
+}
+
+fn time<F: FnMut(&IpAddr) -> Option<u32>>(addresses: &[IpAddr], mut lookup: F) -> (Duration, u64) {
+    let start = Instant::now();
+    let mut found = 0;
+    for address in addresses {
+        if let Some(value) = lookup(black_box(address)) {
+            found += value as u64;
+        }
+    }
+    (start.elapsed(), found)
+}
+
+fn main() {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    let addresses: Vec<IpAddr> =
+        (0..LOOKUPS).map(|i| if i % 2 == 0 { random.v4() } else { random.v6() }).collect();
+
+    println!("{:>8} {:>12} {:>12}", "routes", "trie", "linear");
+    for &count in [10, 100, 1_000, 10_000].iter() {
+        let mut trie = RoutingTable::new();
+        let mut linear = LinearTable::new();
+        for (i, network) in routes(&mut random, count).into_iter().enumerate() {
+            trie.insert(network, i as u32);
+            linear.insert(network, i as u32);
+        }
+
+        let (trie_time, trie_found) = time(&addresses, |address| trie.lookup(address).map(|(_, v)| *v));
+        let (linear_time, linear_found) = time(&addresses, |address| linear.lookup(address).map(|(_, v)| *v));
+        // both have to find the same routes, or the timing means nothing
+        assert_eq!(trie_found, linear_found);
+
+        let per_lookup = |time: Duration| time.as_nanos() as f64 / LOOKUPS as f64;
+        println!(
+            "{:>8} {:>9.1} ns {:>9.1} ns",
+            count,
+            per_lookup(trie_time),
+            per_lookup(linear_time)
+        );
+    }
+}
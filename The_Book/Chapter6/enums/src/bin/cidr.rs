@@ -0,0 +1,23 @@
+// A subnet calculator: cidr info 10.0.0.0/8, see USAGE for the other commands
+use std::env;
+use std::io;
+use std::process;
+
+use enums::cidr::cli::{self, Config};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let config = Config::new(&args).unwrap_or_else(|err| {
+        eprintln!("Problem parsing arguments: {}", err);
+        eprintln!("{}", cli::USAGE);
+        process::exit(1);
+    });
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(e) = cli::run(config, stdin.lock(), stdout.lock()) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,30 @@
+// Turtle graphics from messages: turtle drawing.svg < drawing.txt, see USAGE
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+
+use enums::turtle::cli::{self, Config};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let config = Config::new(&args).unwrap_or_else(|err| {
+        eprintln!("Problem parsing arguments: {}", err);
+        eprintln!("{}", cli::USAGE);
+        process::exit(1);
+    });
+
+    // drawn in memory first, so a bad line leaves an existing file as it was
+    let stdin = io::stdin();
+    let mut picture = Vec::new();
+    if let Err(e) = cli::run(&config, stdin.lock(), &mut picture) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+
+    if let Err(e) = fs::write(&config.output, picture) {
+        eprintln!("Problem writing {}: {}", config.output, e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,296 @@
+use std::fmt;
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::message::{Handler, Message, MessageKind};
+
+// Publish/subscribe for Messages inside one program. Components subscribe a Handler to the
+// variants they want, and every message published is handed to each of them through
+// Message::call, in the order they subscribed.
+//
+// Quit is special: it shuts the bus down. Every subscriber gets it, whatever it subscribed
+// to, so that Handler::quit can clean up; then the subscribers are dropped and nothing
+// more can be published.
+#[derive(Default)]
+pub struct Bus {
+    subscriptions: Vec<Subscription>,
+    next_id: usize,
+    closed: bool,
+}
+
+struct Subscription {
+    id: SubscriptionId,
+    kinds: Vec<MessageKind>,
+    handler: Box<dyn Handler + Send>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusError {
+    // Quit was published, or the background thread has stopped
+    Closed,
+}
+
+impl fmt::Display for BusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BusError::Closed => write!(f, "the message bus has shut down"),
+        }
+    }
+}
+
+impl std::error::Error for BusError {}
+
+impl Bus {
+    pub fn new() -> Bus {
+        Bus::default()
+    }
+
+    pub fn subscribe<H: Handler + Send + 'static>(&mut self, kinds: &[MessageKind], handler: H) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscriptions.push(Subscription { id, kinds: kinds.to_vec(), handler: Box::new(handler) });
+        id
+    }
+
+    // false when there was no such subscription
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.subscriptions.len();
+        self.subscriptions.retain(|subscription| subscription.id != id);
+        self.subscriptions.len() != before
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    // Hands the message to its subscribers right away and returns how many there were
+    pub fn publish(&mut self, message: &Message) -> Result<usize, BusError> {
+        if self.closed {
+            return Err(BusError::Closed);
+        }
+        if *message == Message::Quit {
+            return Ok(self.shut_down());
+        }
+
+        let kind = message.kind();
+        let mut delivered = 0;
+        for subscription in self.subscriptions.iter_mut().filter(|s| s.kinds.contains(&kind)) {
+            message.call(subscription.handler.as_mut());
+            delivered += 1;
+        }
+        Ok(delivered)
+    }
+
+    fn shut_down(&mut self) -> usize {
+        self.closed = true;
+        let subscriptions = std::mem::take(&mut self.subscriptions);
+        let count = subscriptions.len();
+        // one by one, each handler is dropped right after its quit
+        for mut subscription in subscriptions {
+            Message::Quit.call(subscription.handler.as_mut());
+        }
+        count
+    }
+
+    // Moves the bus to a thread of its own: publishing only queues the message on a channel
+    // and the thread delivers it. Subscribe everything before, the thread owns the bus.
+    pub fn spawn(mut self) -> BusHandle {
+        let (sender, receiver) = mpsc::channel::<Message>();
+        let thread = thread::spawn(move || {
+            for message in receiver {
+                // a message sent after Quit finds the bus closed, there is no one left to tell
+                let _ = self.publish(&message);
+                if self.closed {
+                    return;
+                }
+            }
+            // every publisher is gone without a Quit, shut down all the same
+            if !self.closed {
+                self.shut_down();
+            }
+        });
+
+        BusHandle { publisher: Publisher { sender }, thread: Some(thread) }
+    }
+}
+
+// Sends messages to a bus running on its own thread. Clone it for other threads.
+#[derive(Clone)]
+pub struct Publisher {
+    sender: Sender<Message>,
+}
+
+impl Publisher {
+    // Err once the bus has shut down
+    pub fn publish(&self, message: Message) -> Result<(), BusError> {
+        self.sender.send(message).map_err(|_| BusError::Closed)
+    }
+}
+
+// The owner of the background thread. Dropping it shuts the bus down and waits for it,
+// so no message that was published gets lost.
+pub struct BusHandle {
+    publisher: Publisher,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BusHandle {
+    pub fn publish(&self, message: Message) -> Result<(), BusError> {
+        self.publisher.publish(message)
+    }
+
+    pub fn publisher(&self) -> Publisher {
+        self.publisher.clone()
+    }
+
+    // publishes Quit and waits until every message before it was delivered and every
+    // subscriber has shut down
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        // the bus may have been closed already by a Quit from a publisher
+        let _ = self.publisher.publish(Message::Quit);
+        if let Some(thread) = self.thread.take() {
+            // a handler that panicked already stopped the thread, don't panic again in drop
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for BusHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    // writes everything it gets to a log shared with the test
+    struct Logger {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Logger {
+        fn log(&self, entry: String) {
+            self.log.lock().unwrap().push(format!("{}: {}", self.name, entry));
+        }
+    }
+
+    impl Handler for Logger {
+        fn quit(&mut self) {
+            self.log(String::from("quit"));
+        }
+
+        fn move_to(&mut self, x: i32, y: i32) {
+            self.log(format!("move {} {}", x, y));
+        }
+
+        fn write(&mut self, text: &str) {
+            self.log(format!("write {}", text));
+        }
+    }
+
+    fn logger(name: &'static str, log: &Arc<Mutex<Vec<String>>>) -> Logger {
+        Logger { name, log: Arc::clone(log) }
+    }
+
+    fn entries(log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+        log.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn delivers_by_variant() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = Bus::new();
+        bus.subscribe(&[MessageKind::Move], logger("a", &log));
+        bus.subscribe(&[MessageKind::Move, MessageKind::Write], logger("b", &log));
+
+        assert_eq!(bus.publish(&Message::Move { x: 1, y: 2 }), Ok(2));
+        assert_eq!(bus.publish(&Message::Write(String::from("hi"))), Ok(1));
+        assert_eq!(bus.publish(&Message::ChangeColor(0, 0, 0)), Ok(0));
+        assert_eq!(entries(&log), ["a: move 1 2", "b: move 1 2", "b: write hi"]);
+    }
+
+    #[test]
+    fn unsubscribe_stops_delivery() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = Bus::new();
+        let a = bus.subscribe(&MessageKind::ALL, logger("a", &log));
+        bus.subscribe(&MessageKind::ALL, logger("b", &log));
+
+        assert!(bus.unsubscribe(a));
+        assert!(!bus.unsubscribe(a));
+        bus.publish(&Message::Write(String::from("hi"))).unwrap();
+        assert_eq!(entries(&log), ["b: write hi"]);
+    }
+
+    #[test]
+    fn quit_shuts_everyone_down() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = Bus::new();
+        bus.subscribe(&[MessageKind::Move], logger("a", &log));
+        bus.subscribe(&[MessageKind::Write], logger("b", &log));
+
+        assert_eq!(bus.publish(&Message::Quit), Ok(2));
+        assert!(bus.is_closed());
+        assert_eq!(bus.publish(&Message::Move { x: 0, y: 0 }), Err(BusError::Closed));
+        assert_eq!(bus.publish(&Message::Quit), Err(BusError::Closed));
+        assert_eq!(entries(&log), ["a: quit", "b: quit"]);
+    }
+
+    #[test]
+    fn background_thread_delivers_in_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = Bus::new();
+        bus.subscribe(&MessageKind::ALL, logger("a", &log));
+        let handle = bus.spawn();
+
+        let publisher = handle.publisher();
+        let other = thread::spawn(move || {
+            for x in 0..3 {
+                publisher.publish(Message::Move { x, y: 0 }).unwrap();
+            }
+        });
+        other.join().unwrap();
+        handle.publish(Message::Write(String::from("done"))).unwrap();
+        handle.shutdown();
+
+        assert_eq!(entries(&log), ["a: move 0 0", "a: move 1 0", "a: move 2 0", "a: write done", "a: quit"]);
+    }
+
+    #[test]
+    fn quit_from_a_publisher_closes_the_thread() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = Bus::new();
+        bus.subscribe(&[MessageKind::Write], logger("a", &log));
+        let handle = bus.spawn();
+        let publisher = handle.publisher();
+
+        publisher.publish(Message::Quit).unwrap();
+        // dropping the handle waits for the thread, which has stopped by then
+        drop(handle);
+        assert_eq!(publisher.publish(Message::Write(String::from("late"))), Err(BusError::Closed));
+        assert_eq!(entries(&log), ["a: quit"]);
+    }
+
+    #[test]
+    fn dropping_the_handle_shuts_down() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = Bus::new();
+        bus.subscribe(&[MessageKind::Write], logger("a", &log));
+        {
+            let handle = bus.spawn();
+            handle.publish(Message::Write(String::from("hi"))).unwrap();
+        }
+        assert_eq!(entries(&log), ["a: write hi", "a: quit"]);
+    }
+}
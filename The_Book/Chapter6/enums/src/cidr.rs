@@ -0,0 +1,431 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ip::{AddrParseError, IpAddr, IpAddrKind, Ipv4Addr, Ipv6Addr};
+
+pub mod cli;
+
+// A block of addresses in CIDR notation, 10.0.0.0/8: the first prefix_len bits are
+// the network, the rest pick a host in it. The host bits of `address` are always zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Network {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CidrError {
+    Address(AddrParseError),
+    InvalidPrefix(String),
+    PrefixTooLong { prefix_len: u8, max: u8 },
+    // 10.0.0.1/8, the address has bits set beyond the prefix
+    HostBitsSet,
+    // subnets need a longer prefix than their network
+    PrefixTooShort { prefix_len: u8, min: u8 },
+}
+
+impl fmt::Display for CidrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CidrError::Address(e) => fmt::Display::fmt(e, f),
+            CidrError::InvalidPrefix(prefix) => write!(f, "'{}' is not a prefix length", prefix),
+            CidrError::PrefixTooLong { prefix_len, max } => {
+                write!(f, "the prefix length is {}, at most {} is possible", prefix_len, max)
+            }
+            CidrError::HostBitsSet => write!(f, "the address has host bits set, did you mean the network it is in?"),
+            CidrError::PrefixTooShort { prefix_len, min } => {
+                write!(f, "the prefix length is {}, it has to be at least {}", prefix_len, min)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CidrError {}
+
+impl From<AddrParseError> for CidrError {
+    fn from(e: AddrParseError) -> CidrError {
+        CidrError::Address(e)
+    }
+}
+
+// Both kinds of address are handled as u128 with a width, so the arithmetic is written once
+
+pub fn width(kind: IpAddrKind) -> u8 {
+    match kind {
+        IpAddrKind::V4 => 32,
+        IpAddrKind::V6 => 128,
+    }
+}
+
+pub fn to_bits(address: &IpAddr) -> u128 {
+    match address {
+        IpAddr::V4(addr) => u32::from(*addr) as u128,
+        IpAddr::V6(addr) => u128::from(*addr),
+    }
+}
+
+// only the lowest 32 bits are used for V4
+pub fn from_bits(kind: IpAddrKind, bits: u128) -> IpAddr {
+    match kind {
+        IpAddrKind::V4 => IpAddr::V4(Ipv4Addr::from(bits as u32)),
+        IpAddrKind::V6 => IpAddr::V6(Ipv6Addr::from(bits)),
+    }
+}
+
+// the lowest `count` bits set
+fn low_bits(count: u8) -> u128 {
+    match 1u128.checked_shl(count as u32) {
+        Some(bit) => bit - 1,
+        None => u128::MAX,
+    }
+}
+
+impl Network {
+    pub fn new(address: IpAddr, prefix_len: u8) -> Result<Network, CidrError> {
+        let network = Network::containing(address, prefix_len)?;
+        if network.address != address {
+            return Err(CidrError::HostBitsSet);
+        }
+        Ok(network)
+    }
+
+    // the network of the given size that `address` is in, its host bits are cleared
+    pub fn containing(address: IpAddr, prefix_len: u8) -> Result<Network, CidrError> {
+        let max = width(address.kind());
+        if prefix_len > max {
+            return Err(CidrError::PrefixTooLong { prefix_len, max });
+        }
+        let bits = to_bits(&address) & !low_bits(max - prefix_len);
+        Ok(Network { address: from_bits(address.kind(), bits), prefix_len })
+    }
+
+    pub fn kind(&self) -> IpAddrKind {
+        self.address.kind()
+    }
+
+    // the first address, all host bits zero
+    pub fn address(&self) -> IpAddr {
+        self.address
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    fn host_bits(&self) -> u8 {
+        width(self.kind()) - self.prefix_len
+    }
+
+    fn first_bits(&self) -> u128 {
+        to_bits(&self.address)
+    }
+
+    fn last_bits(&self) -> u128 {
+        self.first_bits() | low_bits(self.host_bits())
+    }
+
+    // 255.0.0.0 for a /8
+    pub fn netmask(&self) -> IpAddr {
+        let mask = low_bits(width(self.kind())) & !low_bits(self.host_bits());
+        from_bits(self.kind(), mask)
+    }
+
+    // the last address, all host bits one; the broadcast address of an IPv4 network
+    pub fn last(&self) -> IpAddr {
+        from_bits(self.kind(), self.last_bits())
+    }
+
+    // How many addresses the network has. ::/0 has one more than a u128 can count,
+    // it says u128::MAX.
+    pub fn size(&self) -> u128 {
+        low_bits(self.host_bits()).saturating_add(1)
+    }
+
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        address.kind() == self.kind() && {
+            let bits = to_bits(address);
+            self.first_bits() <= bits && bits <= self.last_bits()
+        }
+    }
+
+    // `other` lies completely within this network, a network contains itself
+    pub fn contains_network(&self, other: &Network) -> bool {
+        self.prefix_len <= other.prefix_len && self.contains(&other.address)
+    }
+
+    // the network one bit shorter, None for /0
+    pub fn supernet(&self) -> Option<Network> {
+        let prefix_len = self.prefix_len.checked_sub(1)?;
+        Network::containing(self.address, prefix_len).ok()
+    }
+
+    // Splits the network into networks with the longer prefix, in order
+    pub fn subnets(&self, prefix_len: u8) -> Result<Subnets, CidrError> {
+        let max = width(self.kind());
+        if prefix_len > max {
+            return Err(CidrError::PrefixTooLong { prefix_len, max });
+        }
+        if prefix_len < self.prefix_len {
+            return Err(CidrError::PrefixTooShort { prefix_len, min: self.prefix_len });
+        }
+
+        Ok(Subnets {
+            kind: self.kind(),
+            prefix_len,
+            next: Some(self.first_bits()),
+            // the start of the last subnet
+            last: self.last_bits() & !low_bits(max - prefix_len),
+        })
+    }
+
+    // The addresses that can be given to hosts. For IPv4 the first (network) and last
+    // (broadcast) addresses are left out, except in /31 and /32 where there is no room
+    // for them (RFC 3021). For IPv6 every address is a host.
+    pub fn hosts(&self) -> Hosts {
+        let (mut first, mut last) = (self.first_bits(), self.last_bits());
+        if self.kind() == IpAddrKind::V4 && self.prefix_len < 31 {
+            first += 1;
+            last -= 1;
+        }
+        Hosts { kind: self.kind(), next: Some(first), last }
+    }
+}
+
+// a single address is a network of its own, /32 or /128
+impl From<IpAddr> for Network {
+    fn from(address: IpAddr) -> Network {
+        Network { address, prefix_len: width(address.kind()) }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+// 10.0.0.0/8 or 2001:db8::/32; an address without a prefix is a network of one address
+impl FromStr for Network {
+    type Err = CidrError;
+
+    fn from_str(s: &str) -> Result<Network, CidrError> {
+        let s = s.trim();
+        match s.split_once('/') {
+            None => Ok(Network::from(s.parse::<IpAddr>()?)),
+            Some((address, prefix)) => {
+                let address: IpAddr = address.parse()?;
+                let prefix_len = match prefix.parse::<u8>() {
+                    Ok(prefix_len) if !prefix.starts_with('+') => prefix_len,
+                    _ => return Err(CidrError::InvalidPrefix(prefix.to_string())),
+                };
+                Network::new(address, prefix_len)
+            }
+        }
+    }
+}
+
+pub struct Subnets {
+    kind: IpAddrKind,
+    prefix_len: u8,
+    next: Option<u128>,
+    last: u128,
+}
+
+impl Iterator for Subnets {
+    type Item = Network;
+
+    fn next(&mut self) -> Option<Network> {
+        let bits = self.next?;
+        self.next = if bits == self.last {
+            None
+        } else {
+            // there is more than one subnet, so the step fits in a u128
+            Some(bits + low_bits(width(self.kind) - self.prefix_len) + 1)
+        };
+        Some(Network { address: from_bits(self.kind, bits), prefix_len: self.prefix_len })
+    }
+
+    // straight to the end, a /0 split into /128s has far too many subnets to walk through
+    fn last(self) -> Option<Network> {
+        self.next?;
+        Some(Network { address: from_bits(self.kind, self.last), prefix_len: self.prefix_len })
+    }
+}
+
+pub struct Hosts {
+    kind: IpAddrKind,
+    next: Option<u128>,
+    last: u128,
+}
+
+impl Iterator for Hosts {
+    type Item = IpAddr;
+
+    fn next(&mut self) -> Option<IpAddr> {
+        let bits = self.next?;
+        self.next = if bits == self.last { None } else { Some(bits + 1) };
+        Some(from_bits(self.kind, bits))
+    }
+
+    fn last(self) -> Option<IpAddr> {
+        self.next?;
+        Some(from_bits(self.kind, self.last))
+    }
+}
+
+// The fewest networks that cover exactly the same addresses: overlapping networks are
+// merged, neighbours joined into their supernet where they fill it. The result is sorted,
+// IPv4 before IPv6.
+pub fn aggregate(networks: &[Network]) -> Vec<Network> {
+    // as (kind, first, last) ranges, merging the ones that overlap or touch
+    let mut ranges: Vec<(IpAddrKind, u128, u128)> =
+        networks.iter().map(|net| (net.kind(), net.first_bits(), net.last_bits())).collect();
+    ranges.sort();
+
+    let mut merged: Vec<(IpAddrKind, u128, u128)> = Vec::new();
+    for (kind, first, last) in ranges {
+        match merged.last_mut() {
+            Some((prev_kind, _, prev_last)) if *prev_kind == kind && first <= prev_last.saturating_add(1) => {
+                *prev_last = (*prev_last).max(last);
+            }
+            _ => merged.push((kind, first, last)),
+        }
+    }
+
+    merged.into_iter().flat_map(|(kind, first, last)| range_to_networks(kind, first, last)).collect()
+}
+
+// the largest aligned blocks that fit, from first to last
+fn range_to_networks(kind: IpAddrKind, mut first: u128, last: u128) -> Vec<Network> {
+    let max = width(kind);
+    let mut networks = Vec::new();
+    loop {
+        // as large as the alignment of `first` allows, then smaller until it ends by `last`
+        let mut host_bits = (first.trailing_zeros() as u8).min(max);
+        while first | low_bits(host_bits) > last {
+            host_bits -= 1;
+        }
+        let block_last = first | low_bits(host_bits);
+        networks.push(Network { address: from_bits(kind, first), prefix_len: max - host_bits });
+
+        if block_last == last {
+            return networks;
+        }
+        first = block_last + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn net(s: &str) -> Network {
+        s.parse().unwrap()
+    }
+
+    fn addr(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_networks() {
+        let ten = net("10.0.0.0/8");
+        assert_eq!(ten.address(), addr("10.0.0.0"));
+        assert_eq!(ten.prefix_len(), 8);
+        assert_eq!(ten.to_string(), "10.0.0.0/8");
+        assert_eq!(net("2001:DB8::/32").to_string(), "2001:db8::/32");
+        assert_eq!(net("192.168.1.7"), Network::new(addr("192.168.1.7"), 32).unwrap());
+        assert_eq!(net("0.0.0.0/0").size(), 1 << 32);
+    }
+
+    #[test]
+    fn rejects_bad_networks() {
+        let err = |s: &str| s.parse::<Network>().unwrap_err();
+        assert_eq!(err("10.0.0.1/8"), CidrError::HostBitsSet);
+        assert_eq!(err("10.0.0.0/33"), CidrError::PrefixTooLong { prefix_len: 33, max: 32 });
+        assert_eq!(err("::/129"), CidrError::PrefixTooLong { prefix_len: 129, max: 128 });
+        assert_eq!(err("10.0.0.0/"), CidrError::InvalidPrefix("".to_string()));
+        assert_eq!(err("10.0.0.0/+8"), CidrError::InvalidPrefix("+8".to_string()));
+        assert_eq!(err("10.0.0.0/8/8"), CidrError::InvalidPrefix("8/8".to_string()));
+        assert_eq!(err("10.0.0/8"), CidrError::Address(AddrParseError::OctetCount(3)));
+        assert_eq!(Network::containing(addr("10.1.2.3"), 8), Ok(net("10.0.0.0/8")));
+    }
+
+    #[test]
+    fn masks_and_sizes() {
+        let lan = net("192.168.1.0/24");
+        assert_eq!(lan.netmask(), addr("255.255.255.0"));
+        assert_eq!(lan.last(), addr("192.168.1.255"));
+        assert_eq!(lan.size(), 256);
+        assert_eq!(net("0.0.0.0/0").netmask(), addr("0.0.0.0"));
+        assert_eq!(net("2001:db8::/32").netmask(), addr("ffff:ffff::"));
+        assert_eq!(net("2001:db8::/32").last(), addr("2001:db8:ffff:ffff:ffff:ffff:ffff:ffff"));
+        assert_eq!(net("::/0").size(), u128::MAX);
+        assert_eq!(net("::1/128").size(), 1);
+    }
+
+    #[test]
+    fn containment() {
+        let ten = net("10.0.0.0/8");
+        assert!(ten.contains(&addr("10.255.255.255")));
+        assert!(!ten.contains(&addr("11.0.0.0")));
+        assert!(!ten.contains(&addr("::ffff:10.0.0.1")));
+        assert!(ten.contains_network(&net("10.20.0.0/16")));
+        assert!(ten.contains_network(&ten));
+        assert!(!net("10.20.0.0/16").contains_network(&ten));
+        assert!(net("::/0").contains(&addr("2001:db8::1")));
+        assert!(!net("::/0").contains(&addr("1.2.3.4")));
+    }
+
+    #[test]
+    fn splits_into_subnets() {
+        let subnets: Vec<String> = net("10.0.0.0/8").subnets(10).unwrap().map(|n| n.to_string()).collect();
+        assert_eq!(subnets, ["10.0.0.0/10", "10.64.0.0/10", "10.128.0.0/10", "10.192.0.0/10"]);
+        assert_eq!(net("10.0.0.0/8").subnets(8).unwrap().collect::<Vec<_>>(), [net("10.0.0.0/8")]);
+        assert_eq!(net("10.0.0.0/8").subnets(24).unwrap().count(), 1 << 16);
+        assert_eq!(net("::/0").subnets(128).unwrap().last(), Some(net("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128")));
+        assert_eq!(net("::/0").subnets(1).unwrap().last(), Some(net("8000::/1")));
+        assert!(matches!(net("10.0.0.0/8").subnets(7), Err(CidrError::PrefixTooShort { .. })));
+        assert!(matches!(net("10.0.0.0/8").subnets(33), Err(CidrError::PrefixTooLong { .. })));
+
+        assert_eq!(net("10.64.0.0/10").supernet(), Some(net("10.0.0.0/9")));
+        assert_eq!(net("0.0.0.0/0").supernet(), None);
+    }
+
+    #[test]
+    fn lists_hosts() {
+        let hosts: Vec<String> = net("192.168.1.0/30").hosts().map(|a| a.to_string()).collect();
+        assert_eq!(hosts, ["192.168.1.1", "192.168.1.2"]);
+        assert_eq!(net("192.168.1.0/31").hosts().count(), 2);
+        assert_eq!(net("192.168.1.9/32").hosts().collect::<Vec<_>>(), [addr("192.168.1.9")]);
+        assert_eq!(net("255.255.255.254/31").hosts().last(), Some(addr("255.255.255.255")));
+        assert_eq!(net("2001:db8::/126").hosts().count(), 4);
+        assert_eq!(net("::/0").hosts().nth(2), Some(addr("::2")));
+        assert_eq!(net("::/0").hosts().last(), Some(addr("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")));
+    }
+
+    #[test]
+    fn aggregates_networks() {
+        let nets = |list: &[&str]| list.iter().map(|s| net(s)).collect::<Vec<_>>();
+
+        // neighbours join up, contained networks disappear
+        assert_eq!(
+            aggregate(&nets(&["10.0.1.0/24", "10.0.0.0/24", "10.0.0.128/25", "10.0.2.0/24"])),
+            nets(&["10.0.0.0/23", "10.0.2.0/24"])
+        );
+        // single addresses
+        assert_eq!(
+            aggregate(&nets(&["192.168.0.4", "192.168.0.5", "192.168.0.6", "192.168.0.7", "192.168.0.8"])),
+            nets(&["192.168.0.4/30", "192.168.0.8/32"])
+        );
+        // two halves make the whole space
+        assert_eq!(aggregate(&nets(&["128.0.0.0/1", "0.0.0.0/1"])), nets(&["0.0.0.0/0"]));
+        assert_eq!(aggregate(&nets(&["8000::/1", "::/1"])), nets(&["::/0"]));
+        // the kinds stay apart, even where the numbers would touch
+        assert_eq!(
+            aggregate(&nets(&["::/96", "0.0.0.0/1", "::1:0:0/96"])),
+            nets(&["0.0.0.0/1", "::/95"])
+        );
+        assert_eq!(aggregate(&[]), Vec::new());
+    }
+}
@@ -0,0 +1,207 @@
+use std::error::Error;
+use std::io::{BufRead, Write};
+
+use super::{aggregate, Network};
+use crate::ip::IpAddr;
+
+pub const USAGE: &str = "\
+usage: cidr info <network>
+       cidr contains <network> <address>...
+       cidr split <network> <prefix length>
+       cidr hosts <network>
+       cidr aggregate [<network>...]    (reads one network per line from stdin without any)";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    // netmask, first and last address and so on
+    Info(Network),
+    Contains { network: Network, addresses: Vec<IpAddr> },
+    Split { network: Network, prefix_len: u8 },
+    Hosts(Network),
+    // an empty list means the networks come from the input
+    Aggregate(Vec<Network>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub command: Command,
+}
+
+fn network(arg: Option<&String>) -> Result<Network, &'static str> {
+    arg.ok_or("a network is needed, like 10.0.0.0/8")?
+        .parse()
+        .map_err(|_| "the network has to look like 10.0.0.0/8 or 2001:db8::/32, without host bits set")
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, &'static str> {
+        // skip the program name
+        let mut args = args.iter().skip(1);
+
+        let command = match args.next().map(|arg| arg.as_str()) {
+            Some("info") => Command::Info(network(args.next())?),
+            Some("contains") => {
+                let network = network(args.next())?;
+                let addresses = args
+                    .by_ref()
+                    .map(|arg| arg.parse())
+                    .collect::<Result<Vec<IpAddr>, _>>()
+                    .map_err(|_| "the addresses have to look like 10.1.2.3 or 2001:db8::1")?;
+                if addresses.is_empty() {
+                    return Err("contains needs at least one address");
+                }
+                Command::Contains { network, addresses }
+            }
+            Some("split") => {
+                let network = network(args.next())?;
+                let prefix = args.next().ok_or("split needs the prefix length of the subnets")?;
+                // /10 and 10 both work
+                let prefix_len = prefix
+                    .trim_start_matches('/')
+                    .parse()
+                    .map_err(|_| "the prefix length has to be a number like 24")?;
+                Command::Split { network, prefix_len }
+            }
+            Some("hosts") => Command::Hosts(network(args.next())?),
+            Some("aggregate") => Command::Aggregate(
+                args.by_ref()
+                    .map(|arg| network(Some(arg)))
+                    .collect::<Result<Vec<Network>, _>>()?,
+            ),
+            Some(_) => return Err("unknown command"),
+            None => return Err("a command is needed"),
+        };
+
+        if args.next().is_some() {
+            return Err("too many arguments");
+        }
+        Ok(Config { command })
+    }
+}
+
+pub fn run<R: BufRead, W: Write>(config: Config, input: R, mut output: W) -> Result<(), Box<dyn Error>> {
+    match config.command {
+        Command::Info(network) => {
+            writeln!(output, "network   {}", network)?;
+            writeln!(output, "netmask   {}", network.netmask())?;
+            writeln!(output, "first     {}", network.address())?;
+            writeln!(output, "last      {}", network.last())?;
+            let mut hosts = network.hosts();
+            if let Some(first) = hosts.next() {
+                match hosts.last() {
+                    Some(last) => writeln!(output, "hosts     {} - {}", first, last)?,
+                    None => writeln!(output, "hosts     {}", first)?,
+                }
+            }
+            writeln!(output, "size      {}", network.size())?;
+            writeln!(output, "class     {:?}", network.address().classify())?;
+        }
+        Command::Contains { network, addresses } => {
+            for address in addresses {
+                let verb = if network.contains(&address) { "is" } else { "is not" };
+                writeln!(output, "{} {} in {}", address, verb, network)?;
+            }
+        }
+        Command::Split { network, prefix_len } => {
+            for subnet in network.subnets(prefix_len)? {
+                writeln!(output, "{}", subnet)?;
+            }
+        }
+        Command::Hosts(network) => {
+            for host in network.hosts() {
+                writeln!(output, "{}", host)?;
+            }
+        }
+        Command::Aggregate(mut networks) => {
+            if networks.is_empty() {
+                for (i, line) in input.lines().enumerate() {
+                    let line = line?;
+                    // blank lines and # comments, like in a file of allowed ranges
+                    let line = line.split('#').next().unwrap_or_default().trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    let network = line.parse().map_err(|e| format!("line {}: {}", i + 1, e))?;
+                    networks.push(network);
+                }
+            }
+            for network in aggregate(&networks) {
+                writeln!(output, "{}", network)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("cidr").chain(line.split_whitespace()).map(String::from).collect()
+    }
+
+    fn output(line: &str, input: &str) -> String {
+        let config = Config::new(&args(line)).unwrap();
+        let mut output = Vec::new();
+        run(config, input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn parses_commands() {
+        let ten: Network = "10.0.0.0/8".parse().unwrap();
+        assert_eq!(Config::new(&args("info 10.0.0.0/8")).unwrap().command, Command::Info(ten));
+        assert_eq!(
+            Config::new(&args("split 10.0.0.0/8 /10")).unwrap().command,
+            Command::Split { network: ten, prefix_len: 10 }
+        );
+        assert_eq!(Config::new(&args("aggregate")).unwrap().command, Command::Aggregate(Vec::new()));
+
+        assert_eq!(Config::new(&args("")), Err("a command is needed"));
+        assert_eq!(Config::new(&args("list")), Err("unknown command"));
+        assert_eq!(Config::new(&args("info")), Err("a network is needed, like 10.0.0.0/8"));
+        assert!(Config::new(&args("info 10.0.0.1/8")).is_err());
+        assert_eq!(Config::new(&args("contains 10.0.0.0/8")), Err("contains needs at least one address"));
+        assert_eq!(Config::new(&args("hosts 10.0.0.0/30 extra")), Err("too many arguments"));
+    }
+
+    #[test]
+    fn info() {
+        assert_eq!(
+            output("info 192.168.1.0/24", ""),
+            "network   192.168.1.0/24\n\
+             netmask   255.255.255.0\n\
+             first     192.168.1.0\n\
+             last      192.168.1.255\n\
+             hosts     192.168.1.1 - 192.168.1.254\n\
+             size      256\n\
+             class     Private\n"
+        );
+        assert!(output("info 10.0.0.7/32", "").contains("hosts     10.0.0.7\n"));
+        assert!(output("info ::/0", "").contains("hosts     :: - ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff\n"));
+    }
+
+    #[test]
+    fn contains_split_and_hosts() {
+        assert_eq!(
+            output("contains 10.0.0.0/8 10.1.2.3 11.0.0.1", ""),
+            "10.1.2.3 is in 10.0.0.0/8\n11.0.0.1 is not in 10.0.0.0/8\n"
+        );
+        assert_eq!(output("split 2001:db8::/32 33", ""), "2001:db8::/33\n2001:db8:8000::/33\n");
+        assert_eq!(output("hosts 10.0.0.0/30", ""), "10.0.0.1\n10.0.0.2\n");
+    }
+
+    #[test]
+    fn aggregates_arguments_or_input() {
+        assert_eq!(output("aggregate 10.0.1.0/24 10.0.0.0/24", "ignored"), "10.0.0.0/23\n");
+        assert_eq!(
+            output("aggregate", "# office\n10.0.0.1\n10.0.0.0  # router\n\n2001:db8::/33\n2001:db8:8000::/33\n"),
+            "10.0.0.0/31\n2001:db8::/32\n"
+        );
+
+        let config = Config::new(&args("aggregate")).unwrap();
+        let err = run(config, "10.0.0.0/8\nnope\n".as_bytes(), Vec::new()).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: an IPv4 address has 4 octets, not 1");
+    }
+}
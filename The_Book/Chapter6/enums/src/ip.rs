@@ -0,0 +1,579 @@
+use std::fmt;
+use std::str::FromStr;
+
+// The version of an address, without the address itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IpAddrKind {
+    V4,
+    V6,
+}
+
+// The last version from the chapter, with a real type for each kind of address:
+//
+//   struct Ipv4Addr {}
+//   struct Ipv6Addr {}
+//   enum IpAddr {
+//       V4(Ipv4Addr),
+//       V6(Ipv6Addr),
+//   }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IpAddr {
+    V4(Ipv4Addr),
+    V6(Ipv6Addr),
+}
+
+// four octets, like IpAddr1::V4(u8, u8, u8, u8)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ipv4Addr([u8; 4]);
+
+// eight 16 bit segments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ipv6Addr([u16; 8]);
+
+// What an address is used for, the most specific kind that applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddrClass {
+    // 0.0.0.0 or ::, no address at all
+    Unspecified,
+    // 127.0.0.0/8 or ::1, this machine
+    Loopback,
+    // 10/8, 172.16/12 and 192.168/16, or unique local fc00::/7
+    Private,
+    // 169.254/16 or fe80::/10, only valid on the local link
+    LinkLocal,
+    // 224/4 or ff00::/8
+    Multicast,
+    // 255.255.255.255
+    Broadcast,
+    // anything else, reachable on the internet
+    Global,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddrParseError {
+    Empty,
+    // a dotted quad needs exactly 4 octets
+    OctetCount(usize),
+    // not a number from 0 to 255, or with a leading zero
+    InvalidOctet(String),
+    // without :: an IPv6 address needs 8 segments, with :: fewer
+    SegmentCount(usize),
+    // not 1 to 4 hex digits
+    InvalidSegment(String),
+    // :: may appear only once, otherwise the length of each gap is unknown
+    DoubleColons,
+}
+
+impl fmt::Display for AddrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AddrParseError::Empty => write!(f, "the address is empty"),
+            AddrParseError::OctetCount(n) => write!(f, "an IPv4 address has 4 octets, not {}", n),
+            AddrParseError::InvalidOctet(octet) => write!(f, "'{}' is not an octet from 0 to 255", octet),
+            AddrParseError::SegmentCount(n) => write!(f, "an IPv6 address has 8 segments, not {}", n),
+            AddrParseError::InvalidSegment(segment) => {
+                write!(f, "'{}' is not a segment of 1 to 4 hex digits", segment)
+            }
+            AddrParseError::DoubleColons => write!(f, ":: can only be used once"),
+        }
+    }
+}
+
+impl std::error::Error for AddrParseError {}
+
+impl Ipv4Addr {
+    pub const UNSPECIFIED: Ipv4Addr = Ipv4Addr([0, 0, 0, 0]);
+    pub const LOCALHOST: Ipv4Addr = Ipv4Addr([127, 0, 0, 1]);
+    pub const BROADCAST: Ipv4Addr = Ipv4Addr([255, 255, 255, 255]);
+
+    pub const fn new(a: u8, b: u8, c: u8, d: u8) -> Ipv4Addr {
+        Ipv4Addr([a, b, c, d])
+    }
+
+    pub fn octets(&self) -> [u8; 4] {
+        self.0
+    }
+
+    pub fn is_unspecified(&self) -> bool {
+        *self == Ipv4Addr::UNSPECIFIED
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        self.0[0] == 127
+    }
+
+    pub fn is_private(&self) -> bool {
+        match self.0 {
+            [10, ..] => true,
+            [172, b, ..] => (16..=31).contains(&b),
+            [192, 168, ..] => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_link_local(&self) -> bool {
+        self.0[0] == 169 && self.0[1] == 254
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        (224..=239).contains(&self.0[0])
+    }
+
+    pub fn is_broadcast(&self) -> bool {
+        *self == Ipv4Addr::BROADCAST
+    }
+
+    pub fn classify(&self) -> AddrClass {
+        if self.is_unspecified() {
+            AddrClass::Unspecified
+        } else if self.is_loopback() {
+            AddrClass::Loopback
+        } else if self.is_private() {
+            AddrClass::Private
+        } else if self.is_link_local() {
+            AddrClass::LinkLocal
+        } else if self.is_multicast() {
+            AddrClass::Multicast
+        } else if self.is_broadcast() {
+            AddrClass::Broadcast
+        } else {
+            AddrClass::Global
+        }
+    }
+
+    // ::ffff:a.b.c.d, how IPv6 sockets see IPv4 peers
+    pub fn to_ipv6_mapped(&self) -> Ipv6Addr {
+        let [a, b, c, d] = self.0;
+        Ipv6Addr([0, 0, 0, 0, 0, 0xffff, u16::from_be_bytes([a, b]), u16::from_be_bytes([c, d])])
+    }
+}
+
+impl From<[u8; 4]> for Ipv4Addr {
+    fn from(octets: [u8; 4]) -> Ipv4Addr {
+        Ipv4Addr(octets)
+    }
+}
+
+impl From<u32> for Ipv4Addr {
+    fn from(bits: u32) -> Ipv4Addr {
+        Ipv4Addr(bits.to_be_bytes())
+    }
+}
+
+impl From<Ipv4Addr> for u32 {
+    fn from(addr: Ipv4Addr) -> u32 {
+        u32::from_be_bytes(addr.0)
+    }
+}
+
+impl fmt::Display for Ipv4Addr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d] = self.0;
+        write!(f, "{}.{}.{}.{}", a, b, c, d)
+    }
+}
+
+// dotted quad, 127.0.0.1
+impl FromStr for Ipv4Addr {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<Ipv4Addr, AddrParseError> {
+        if s.is_empty() {
+            return Err(AddrParseError::Empty);
+        }
+        let parts: Vec<&str> = s.split('.').collect();
+        if parts.len() != 4 {
+            return Err(AddrParseError::OctetCount(parts.len()));
+        }
+
+        let mut octets = [0; 4];
+        for (octet, part) in octets.iter_mut().zip(parts) {
+            let invalid = || AddrParseError::InvalidOctet(part.to_string());
+            // 010 could be read as octal 8 by other tools, so leading zeros are refused
+            if part.is_empty() || part.len() > 3 || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            if part.len() > 1 && part.starts_with('0') {
+                return Err(invalid());
+            }
+            *octet = part.parse().map_err(|_| invalid())?;
+        }
+        Ok(Ipv4Addr(octets))
+    }
+}
+
+impl Ipv6Addr {
+    pub const UNSPECIFIED: Ipv6Addr = Ipv6Addr([0; 8]);
+    pub const LOCALHOST: Ipv6Addr = Ipv6Addr([0, 0, 0, 0, 0, 0, 0, 1]);
+
+    pub fn segments(&self) -> [u16; 8] {
+        self.0
+    }
+
+    pub fn is_unspecified(&self) -> bool {
+        *self == Ipv6Addr::UNSPECIFIED
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        *self == Ipv6Addr::LOCALHOST
+    }
+
+    // unique local addresses, fc00::/7
+    pub fn is_private(&self) -> bool {
+        self.0[0] & 0xfe00 == 0xfc00
+    }
+
+    pub fn is_link_local(&self) -> bool {
+        self.0[0] & 0xffc0 == 0xfe80
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0xff00 == 0xff00
+    }
+
+    // the IPv4 address in ::ffff:a.b.c.d
+    pub fn to_ipv4_mapped(&self) -> Option<Ipv4Addr> {
+        match self.0 {
+            [0, 0, 0, 0, 0, 0xffff, high, low] => {
+                let [a, b] = high.to_be_bytes();
+                let [c, d] = low.to_be_bytes();
+                Some(Ipv4Addr([a, b, c, d]))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn classify(&self) -> AddrClass {
+        if self.is_unspecified() {
+            AddrClass::Unspecified
+        } else if self.is_loopback() {
+            AddrClass::Loopback
+        } else if self.is_private() {
+            AddrClass::Private
+        } else if self.is_link_local() {
+            AddrClass::LinkLocal
+        } else if self.is_multicast() {
+            AddrClass::Multicast
+        } else {
+            AddrClass::Global
+        }
+    }
+}
+
+impl From<[u16; 8]> for Ipv6Addr {
+    fn from(segments: [u16; 8]) -> Ipv6Addr {
+        Ipv6Addr(segments)
+    }
+}
+
+impl From<u128> for Ipv6Addr {
+    fn from(bits: u128) -> Ipv6Addr {
+        let mut segments = [0; 8];
+        for (i, segment) in segments.iter_mut().enumerate() {
+            *segment = (bits >> (112 - 16 * i)) as u16;
+        }
+        Ipv6Addr(segments)
+    }
+}
+
+impl From<Ipv6Addr> for u128 {
+    fn from(addr: Ipv6Addr) -> u128 {
+        addr.0.iter().fold(0, |bits, segment| bits << 16 | *segment as u128)
+    }
+}
+
+// The canonical text form of RFC 5952: lowercase hex without leading zeros, the longest
+// run of two or more zero segments (the first one on a tie) shortened to ::, and
+// IPv4 mapped addresses as ::ffff:a.b.c.d
+impl fmt::Display for Ipv6Addr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(v4) = self.to_ipv4_mapped() {
+            return write!(f, "::ffff:{}", v4);
+        }
+
+        // (start, length) of the longest run of zeros
+        let mut longest = (0, 0);
+        let mut run = (0, 0);
+        for (i, segment) in self.0.iter().enumerate() {
+            if *segment == 0 {
+                if run.1 == 0 {
+                    run.0 = i;
+                }
+                run.1 += 1;
+                if run.1 > longest.1 {
+                    longest = run;
+                }
+            } else {
+                run.1 = 0;
+            }
+        }
+
+        let write_segments = |f: &mut fmt::Formatter, segments: &[u16]| -> fmt::Result {
+            for (i, segment) in segments.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ":")?;
+                }
+                write!(f, "{:x}", segment)?;
+            }
+            Ok(())
+        };
+
+        // a single zero segment stays 0, :: would not save anything
+        if longest.1 < 2 {
+            return write_segments(f, &self.0);
+        }
+        let (start, end) = (longest.0, longest.0 + longest.1);
+        write_segments(f, &self.0[..start])?;
+        write!(f, "::")?;
+        write_segments(f, &self.0[end..])
+    }
+}
+
+// Any valid form: full, with :: in place of zero segments, and with an IPv4 address
+// for the last 32 bits (::ffff:192.0.2.1). Zone ids (fe80::1%eth0) are not supported.
+impl FromStr for Ipv6Addr {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<Ipv6Addr, AddrParseError> {
+        if s.is_empty() {
+            return Err(AddrParseError::Empty);
+        }
+
+        let halves: Vec<&str> = s.split("::").collect();
+        let (head, tail) = match halves[..] {
+            [all] => (parse_segments(all, true)?, None),
+            [head, tail] => (parse_segments(head, false)?, Some(parse_segments(tail, true)?)),
+            _ => return Err(AddrParseError::DoubleColons),
+        };
+
+        let mut segments = [0; 8];
+        match tail {
+            None if head.len() == 8 => segments.copy_from_slice(&head),
+            None => return Err(AddrParseError::SegmentCount(head.len())),
+            Some(tail) => {
+                // :: stands for at least one zero segment
+                let count = head.len() + tail.len();
+                if count > 7 {
+                    return Err(AddrParseError::SegmentCount(count));
+                }
+                segments[..head.len()].copy_from_slice(&head);
+                segments[8 - tail.len()..].copy_from_slice(&tail);
+            }
+        }
+        Ok(Ipv6Addr(segments))
+    }
+}
+
+// the colon separated segments on one side of ::, an IPv4 address counts as two
+// segments and may only come last
+fn parse_segments(s: &str, last: bool) -> Result<Vec<u16>, AddrParseError> {
+    let mut segments = Vec::new();
+    if s.is_empty() {
+        return Ok(segments);
+    }
+
+    let parts: Vec<&str> = s.split(':').collect();
+    for (i, part) in parts.iter().enumerate() {
+        if last && i == parts.len() - 1 && part.contains('.') {
+            let [a, b, c, d] = part.parse::<Ipv4Addr>()?.octets();
+            segments.push(u16::from_be_bytes([a, b]));
+            segments.push(u16::from_be_bytes([c, d]));
+            continue;
+        }
+
+        let invalid = || AddrParseError::InvalidSegment(part.to_string());
+        if part.is_empty() || part.len() > 4 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        segments.push(u16::from_str_radix(part, 16).map_err(|_| invalid())?);
+    }
+    Ok(segments)
+}
+
+impl IpAddr {
+    pub fn kind(&self) -> IpAddrKind {
+        match self {
+            IpAddr::V4(_) => IpAddrKind::V4,
+            IpAddr::V6(_) => IpAddrKind::V6,
+        }
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => addr.is_loopback(),
+            IpAddr::V6(addr) => addr.is_loopback(),
+        }
+    }
+
+    pub fn is_private(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => addr.is_private(),
+            IpAddr::V6(addr) => addr.is_private(),
+        }
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => addr.is_multicast(),
+            IpAddr::V6(addr) => addr.is_multicast(),
+        }
+    }
+
+    pub fn is_link_local(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => addr.is_link_local(),
+            IpAddr::V6(addr) => addr.is_link_local(),
+        }
+    }
+
+    // An IPv4 mapped IPv6 address is classified like the IPv4 address it carries
+    pub fn classify(&self) -> AddrClass {
+        match self {
+            IpAddr::V4(addr) => addr.classify(),
+            IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+                Some(v4) => v4.classify(),
+                None => addr.classify(),
+            },
+        }
+    }
+}
+
+impl From<Ipv4Addr> for IpAddr {
+    fn from(addr: Ipv4Addr) -> IpAddr {
+        IpAddr::V4(addr)
+    }
+}
+
+impl From<Ipv6Addr> for IpAddr {
+    fn from(addr: Ipv6Addr) -> IpAddr {
+        IpAddr::V6(addr)
+    }
+}
+
+impl fmt::Display for IpAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IpAddr::V4(addr) => fmt::Display::fmt(addr, f),
+            IpAddr::V6(addr) => fmt::Display::fmt(addr, f),
+        }
+    }
+}
+
+// IPv6 addresses always have a colon, IPv4 ones never
+impl FromStr for IpAddr {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<IpAddr, AddrParseError> {
+        if s.contains(':') {
+            s.parse().map(IpAddr::V6)
+        } else {
+            s.parse().map(IpAddr::V4)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v6(s: &str) -> Ipv6Addr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_dotted_quads() {
+        assert_eq!("127.0.0.1".parse(), Ok(Ipv4Addr::LOCALHOST));
+        assert_eq!("255.255.255.255".parse(), Ok(Ipv4Addr::BROADCAST));
+        assert_eq!("192.168.1.20".parse::<Ipv4Addr>().unwrap().to_string(), "192.168.1.20");
+        assert_eq!(u32::from(Ipv4Addr::new(10, 0, 0, 1)), 0x0a00_0001);
+        assert_eq!(Ipv4Addr::from(0x7f00_0001), Ipv4Addr::LOCALHOST);
+    }
+
+    #[test]
+    fn rejects_bad_dotted_quads() {
+        assert_eq!("".parse::<Ipv4Addr>(), Err(AddrParseError::Empty));
+        assert_eq!("1.2.3".parse::<Ipv4Addr>(), Err(AddrParseError::OctetCount(3)));
+        assert_eq!("1.2.3.4.5".parse::<Ipv4Addr>(), Err(AddrParseError::OctetCount(5)));
+        assert_eq!("1.2.3.256".parse::<Ipv4Addr>(), Err(AddrParseError::InvalidOctet("256".to_string())));
+        // the address from the chapter, with a leading zero
+        assert_eq!("127.0.0.01".parse::<Ipv4Addr>(), Err(AddrParseError::InvalidOctet("01".to_string())));
+        assert_eq!("1.2..4".parse::<Ipv4Addr>(), Err(AddrParseError::InvalidOctet("".to_string())));
+        assert_eq!("1.2.3.+4".parse::<Ipv4Addr>(), Err(AddrParseError::InvalidOctet("+4".to_string())));
+    }
+
+    #[test]
+    fn parses_ipv6() {
+        assert_eq!(v6("::1"), Ipv6Addr::LOCALHOST);
+        assert_eq!(v6("::"), Ipv6Addr::UNSPECIFIED);
+        assert_eq!(v6("2001:DB8:0:0:0:0:0:1").segments(), [0x2001, 0xdb8, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(v6("2001:db8::"), Ipv6Addr::from([0x2001, 0xdb8, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(v6("fe80::1:2"), Ipv6Addr::from([0xfe80, 0, 0, 0, 0, 0, 1, 2]));
+        assert_eq!(v6("::ffff:192.0.2.1").to_ipv4_mapped(), Some(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!(v6("1:2:3:4:5:6:1.2.3.4"), Ipv6Addr::from([1, 2, 3, 4, 5, 6, 0x0102, 0x0304]));
+        assert_eq!(u128::from(Ipv6Addr::LOCALHOST), 1);
+        assert_eq!(Ipv6Addr::from(u128::from(v6("2001:db8::ff00:42:8329"))), v6("2001:db8::ff00:42:8329"));
+    }
+
+    #[test]
+    fn rejects_bad_ipv6() {
+        assert_eq!("1::2::3".parse::<Ipv6Addr>(), Err(AddrParseError::DoubleColons));
+        assert_eq!("1:2:3:4:5:6:7".parse::<Ipv6Addr>(), Err(AddrParseError::SegmentCount(7)));
+        assert_eq!("1:2:3:4::5:6:7:8".parse::<Ipv6Addr>(), Err(AddrParseError::SegmentCount(8)));
+        assert_eq!("12345::".parse::<Ipv6Addr>(), Err(AddrParseError::InvalidSegment("12345".to_string())));
+        assert_eq!(":1::".parse::<Ipv6Addr>(), Err(AddrParseError::InvalidSegment("".to_string())));
+        assert_eq!("1:::2".parse::<Ipv6Addr>(), Err(AddrParseError::InvalidSegment("".to_string())));
+        assert_eq!("::g".parse::<Ipv6Addr>(), Err(AddrParseError::InvalidSegment("g".to_string())));
+        // an IPv4 address can only be the last part
+        assert_eq!("::1.2.3.4:1".parse::<Ipv6Addr>(), Err(AddrParseError::InvalidSegment("1.2.3.4".to_string())));
+    }
+
+    #[test]
+    fn formats_like_rfc_5952() {
+        let canonical = |s: &str| v6(s).to_string();
+        assert_eq!(canonical("2001:0DB8:0000:0000:0000:0000:0000:0001"), "2001:db8::1");
+        // a single zero segment is not shortened
+        assert_eq!(canonical("2001:db8:0:1:1:1:1:1"), "2001:db8:0:1:1:1:1:1");
+        // the longest run wins, then the first
+        assert_eq!(canonical("2001:0:0:1:0:0:0:1"), "2001:0:0:1::1");
+        assert_eq!(canonical("2001:db8:0:0:1:0:0:1"), "2001:db8::1:0:0:1");
+        assert_eq!(canonical("0:0:0:0:0:0:0:0"), "::");
+        assert_eq!(canonical("0:0:0:0:0:0:0:1"), "::1");
+        assert_eq!(canonical("1:0:0:0:0:0:0:0"), "1::");
+        assert_eq!(canonical("::ffff:c000:0201"), "::ffff:192.0.2.1");
+    }
+
+    #[test]
+    fn parses_either_kind() {
+        let home: IpAddr = "127.0.0.1".parse().unwrap();
+        let loopback: IpAddr = "::1".parse().unwrap();
+        assert_eq!(home.kind(), IpAddrKind::V4);
+        assert_eq!(loopback.kind(), IpAddrKind::V6);
+        assert!(home.is_loopback() && loopback.is_loopback());
+        assert_eq!(loopback.to_string(), "::1");
+    }
+
+    #[test]
+    fn classifies_addresses() {
+        let class = |s: &str| s.parse::<IpAddr>().unwrap().classify();
+        assert_eq!(class("0.0.0.0"), AddrClass::Unspecified);
+        assert_eq!(class("127.1.2.3"), AddrClass::Loopback);
+        assert_eq!(class("10.1.2.3"), AddrClass::Private);
+        assert_eq!(class("172.16.0.1"), AddrClass::Private);
+        assert_eq!(class("172.32.0.1"), AddrClass::Global);
+        assert_eq!(class("192.168.0.1"), AddrClass::Private);
+        assert_eq!(class("169.254.10.1"), AddrClass::LinkLocal);
+        assert_eq!(class("224.0.0.251"), AddrClass::Multicast);
+        assert_eq!(class("255.255.255.255"), AddrClass::Broadcast);
+        assert_eq!(class("8.8.8.8"), AddrClass::Global);
+
+        assert_eq!(class("::"), AddrClass::Unspecified);
+        assert_eq!(class("::1"), AddrClass::Loopback);
+        assert_eq!(class("fd12:3456::1"), AddrClass::Private);
+        assert_eq!(class("fe80::1"), AddrClass::LinkLocal);
+        assert_eq!(class("febf::1"), AddrClass::LinkLocal);
+        assert_eq!(class("fec0::1"), AddrClass::Global);
+        assert_eq!(class("ff02::fb"), AddrClass::Multicast);
+        assert_eq!(class("2001:db8::1"), AddrClass::Global);
+        assert_eq!(class("::ffff:192.168.0.1"), AddrClass::Private);
+    }
+}
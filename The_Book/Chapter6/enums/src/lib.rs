@@ -0,0 +1,6 @@
+pub mod bus;
+pub mod cidr;
+pub mod ip;
+pub mod message;
+pub mod route;
+pub mod turtle;
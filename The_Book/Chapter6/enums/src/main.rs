@@ -1,3 +1,12 @@
+// An attribute to hide warnings for unused code.
+#![allow(dead_code)]
+#![allow(unused_variables)]
+
+use enums::bus::Bus;
+use enums::ip;
+use enums::message;
+use enums::route::RoutingTable;
+
 /* Enums allow you define a type by enumerating its possible variants. Rust's
 enums are most similar to algebraic data types in functional languages.
 */
@@ -13,13 +22,13 @@ struct IpAddr {
     address: String,
 }
 
-// more concise way
-enum IpAddr {
+// more concise way (called IpAddr0 here, IpAddr is the struct above)
+enum IpAddr0 {
     V4(String),
     V6(String),
 }
 
-// let home = IpAddr::V4(String::from("127.0.0.01"));
+// let home = IpAddr0::V4(String::from("127.0.0.01"));
 
 enum IpAddr1 {
     V4(u8, u8, u8, u8),
@@ -32,7 +41,9 @@ enum IpAddr1 {
 //     V4(Ipv4Addr),
 //     V6(Ipv6Addr),
 // }
+// this one is real now, in the library (src/ip.rs)
 
+// only the kind of address here, the library has a real routing table (src/route.rs)
 fn route(ip_kind: IpAddrKind) {}
 
 fn main() {
@@ -51,11 +62,58 @@ fn main() {
         kind: IpAddrKind::V6,
         address: String::from("::1"),
     };
+
+    // the library parses and checks addresses instead of keeping any String
+    for address in ["127.0.0.1", "::1", "2001:0db8:0000:0000:0000:0000:0000:0001", "127.0.0.01"].iter() {
+        match address.parse::<ip::IpAddr>() {
+            Ok(addr) => println!("{} is {} ({:?})", address, addr, addr.classify()),
+            Err(e) => println!("{} is not an address: {}", address, e),
+        }
+    }
+
+    let mut table = RoutingTable::new();
+    for (network, next_hop) in [("0.0.0.0/0", "the internet"), ("127.0.0.0/8", "loopback"), ("::1/128", "loopback")].iter() {
+        table.insert(network.parse().expect("a valid network"), *next_hop);
+    }
+    for address in ["127.0.0.1", "::1", "8.8.8.8", "2001:db8::1"].iter() {
+        match table.lookup(&address.parse().expect("a valid address")) {
+            Some((network, next_hop)) => println!("{} goes to {} via {}", address, next_hop, network),
+            None => println!("no route to {}", address),
+        }
+    }
+
+    let m = Message::Write(String::from("Hello"));
+    m.call();
+
+    // the same message, ready to be sent as a line of text or as a binary frame
+    let m = message::Message::Write(String::from("Hello"));
+    println!("{} is {:?} as a frame", m, m.encode().unwrap());
+
+    // a bus on its own thread delivers Write messages to a printer, Quit stops it
+    let mut bus = Bus::new();
+    bus.subscribe(&[message::MessageKind::Write], Printer);
+    let bus = bus.spawn();
+    bus.publish(m).unwrap();
+    bus.shutdown();
+
+    options();
+}
+
+struct Printer;
+
+impl message::Handler for Printer {
+    fn quit(&mut self) {
+        println!("printer: shutting down");
+    }
+
+    fn write(&mut self, text: &str) {
+        println!("printer: {}", text);
+    }
 }
 
 enum Message {
     Quit,
-    Moev {x: i32, y: i32},
+    Move {x: i32, y: i32},
     Write(String),
     ChangeColor(i32, i32, i32),
 }
@@ -66,33 +124,33 @@ struct MoveMessage {
     y: i32,
 }
 struct WriteMessage(String); // tuple struct
-struct ChangeColorMessage(i32, i32, i32) // tuple struct
+struct ChangeColorMessage(i32, i32, i32); // tuple struct
 
 impl Message {
     fn call(&self) {
-        // method body would be defined here
+        // method body would be defined here,
+        // enums::message::Message::call hands each variant to a Handler
     }
 }
 
-let m = Message::Write(String::from("Hello"));
-m.call();
-
-// The Option Enum and its advantages over null values
-enum Option<T> {
-    Some<T>,
-    None,
-}
+// The Option Enum and its advantages over null values, as the standard library defines it
+// enum Option<T> {
+//     Some(T),
+//     None,
+// }
 
-let some_number = Some(5);
-let some_string = Some("a string");
+fn options() {
+    let some_number = Some(5);
+    let some_string = Some("a string");
 
-// When using None, we need to tell Rust what type of Option<T> we have
-let absent_number: Option<i32> = None;
+    // When using None, we need to tell Rust what type of Option<T> we have
+    let absent_number: Option<i32> = None;
 
-// following lines will fail
-let x: i8 = 5;
-let y: Option<i8> = Some(5);
-let sum = x + y; // no implementation for `i8 + Option<i8>`
+    // following lines will fail
+    let x: i8 = 5;
+    let y: Option<i8> = Some(5);
+    // let sum = x + y; // no implementation for `i8 + Option<i8>`
+}
 
 /*
 Each variant in Enums can have different types and amounts of associated data
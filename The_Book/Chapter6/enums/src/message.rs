@@ -0,0 +1,481 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+// The Message enum of the chapter, as something that can be sent between programs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Quit,
+    Move { x: i32, y: i32 },
+    Write(String),
+    ChangeColor(i32, i32, i32),
+}
+
+// The variant of a Message without its data, like IpAddrKind for addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    Quit,
+    Move,
+    Write,
+    ChangeColor,
+}
+
+impl MessageKind {
+    pub const ALL: [MessageKind; 4] = [MessageKind::Quit, MessageKind::Move, MessageKind::Write, MessageKind::ChangeColor];
+}
+
+// What `call` dispatches to, one method per variant. They all do nothing by default,
+// so a handler only implements the messages it cares about.
+pub trait Handler {
+    fn quit(&mut self) {}
+
+    fn move_to(&mut self, _x: i32, _y: i32) {}
+
+    fn write(&mut self, _text: &str) {}
+
+    fn change_color(&mut self, _r: i32, _g: i32, _b: i32) {}
+}
+
+impl Message {
+    pub fn kind(&self) -> MessageKind {
+        match self {
+            Message::Quit => MessageKind::Quit,
+            Message::Move { .. } => MessageKind::Move,
+            Message::Write(_) => MessageKind::Write,
+            Message::ChangeColor(..) => MessageKind::ChangeColor,
+        }
+    }
+
+    pub fn call(&self, handler: &mut dyn Handler) {
+        match self {
+            Message::Quit => handler.quit(),
+            Message::Move { x, y } => handler.move_to(*x, *y),
+            Message::Write(text) => handler.write(text),
+            Message::ChangeColor(r, g, b) => handler.change_color(*r, *g, *b),
+        }
+    }
+}
+
+// Binary frames: the length of the rest of the frame as a big endian u32, a tag byte for
+// the variant, then its fields. Numbers are big endian i32s, text is UTF-8 up to the end of the frame.
+//
+//   Quit               len=1   0x00
+//   Move { x, y }      len=9   0x01 x y
+//   Write(text)        len=1+n 0x02 text
+//   ChangeColor(r,g,b) len=13  0x03 r g b
+const TAG_QUIT: u8 = 0;
+const TAG_MOVE: u8 = 1;
+const TAG_WRITE: u8 = 2;
+const TAG_CHANGE_COLOR: u8 = 3;
+
+// the length field itself
+const HEADER_LEN: usize = 4;
+// a bigger frame is more likely a corrupt length than a real message
+pub const MAX_FRAME_LEN: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolError {
+    // the frame isn't complete yet, `needed` bytes are required in all
+    Truncated { needed: usize, available: usize },
+    TooLarge(usize),
+    // a frame needs at least its tag
+    EmptyFrame,
+    UnknownTag(u8),
+    // the fields of the variant don't fill the frame exactly
+    BadLength { tag: u8, len: usize },
+    InvalidUtf8,
+    // a line of the text encoding that isn't a message
+    BadLine(String),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::Truncated { needed, available } => {
+                write!(f, "truncated frame: {} bytes needed, {} available", needed, available)
+            }
+            ProtocolError::TooLarge(len) => write!(f, "a frame of {} bytes is over the limit of {}", len, MAX_FRAME_LEN),
+            ProtocolError::EmptyFrame => write!(f, "empty frame"),
+            ProtocolError::UnknownTag(tag) => write!(f, "unknown message tag {}", tag),
+            ProtocolError::BadLength { tag, len } => write!(f, "a frame with tag {} can't be {} bytes long", tag, len),
+            ProtocolError::InvalidUtf8 => write!(f, "the text is not valid UTF-8"),
+            ProtocolError::BadLine(line) => write!(f, "unexpected message: {}", line),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<ProtocolError> for io::Error {
+    fn from(e: ProtocolError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+fn read_i32(bytes: &[u8]) -> i32 {
+    i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+impl Message {
+    // The whole frame, length included. A Write text too long for the other side to
+    // decode is TooLarge here already.
+    pub fn encode(&self) -> Result<Vec<u8>, ProtocolError> {
+        let mut body = Vec::new();
+        match self {
+            Message::Quit => body.push(TAG_QUIT),
+            Message::Move { x, y } => {
+                body.push(TAG_MOVE);
+                body.extend_from_slice(&x.to_be_bytes());
+                body.extend_from_slice(&y.to_be_bytes());
+            }
+            Message::Write(text) => {
+                body.push(TAG_WRITE);
+                body.extend_from_slice(text.as_bytes());
+            }
+            Message::ChangeColor(r, g, b) => {
+                body.push(TAG_CHANGE_COLOR);
+                for component in [r, g, b].iter() {
+                    body.extend_from_slice(&component.to_be_bytes());
+                }
+            }
+        }
+
+        if body.len() > MAX_FRAME_LEN {
+            return Err(ProtocolError::TooLarge(body.len()));
+        }
+        let mut frame = Vec::with_capacity(HEADER_LEN + body.len());
+        // MAX_FRAME_LEN is far below u32::MAX
+        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&body);
+        Ok(frame)
+    }
+
+    // Decodes the frame at the start of `bytes` and returns the message with the number of
+    // bytes it took up. Truncated means the rest of the frame hasn't arrived yet: read more
+    // and try again.
+    pub fn decode(bytes: &[u8]) -> Result<(Message, usize), ProtocolError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(ProtocolError::Truncated { needed: HEADER_LEN, available: bytes.len() });
+        }
+        let len = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(ProtocolError::TooLarge(len));
+        }
+        let needed = HEADER_LEN + len;
+        if bytes.len() < needed {
+            return Err(ProtocolError::Truncated { needed, available: bytes.len() });
+        }
+
+        Ok((Message::decode_body(&bytes[HEADER_LEN..needed])?, needed))
+    }
+
+    // the frame without its length
+    fn decode_body(body: &[u8]) -> Result<Message, ProtocolError> {
+        let (&tag, fields) = body.split_first().ok_or(ProtocolError::EmptyFrame)?;
+        let expect_len = |len: usize| {
+            if fields.len() == len {
+                Ok(())
+            } else {
+                Err(ProtocolError::BadLength { tag, len: body.len() })
+            }
+        };
+
+        match tag {
+            TAG_QUIT => {
+                expect_len(0)?;
+                Ok(Message::Quit)
+            }
+            TAG_MOVE => {
+                expect_len(8)?;
+                Ok(Message::Move { x: read_i32(&fields[0..4]), y: read_i32(&fields[4..8]) })
+            }
+            TAG_WRITE => {
+                let text = String::from_utf8(fields.to_vec()).map_err(|_| ProtocolError::InvalidUtf8)?;
+                Ok(Message::Write(text))
+            }
+            TAG_CHANGE_COLOR => {
+                expect_len(12)?;
+                Ok(Message::ChangeColor(read_i32(&fields[0..4]), read_i32(&fields[4..8]), read_i32(&fields[8..12])))
+            }
+            _ => Err(ProtocolError::UnknownTag(tag)),
+        }
+    }
+
+    // Nothing is written for a message encode refuses, the error is InvalidData like in read_frame
+    pub fn write_frame<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.encode()?)
+    }
+
+    // The next message from a stream of frames, None when the stream ends between two frames.
+    // A stream that ends inside a frame is an UnexpectedEof error.
+    pub fn read_frame<R: Read>(mut reader: R) -> io::Result<Option<Message>> {
+        let mut header = [0; HEADER_LEN];
+        // the first byte tells a clean end apart from a cut off frame
+        if reader.read(&mut header[..1])? == 0 {
+            return Ok(None);
+        }
+        reader.read_exact(&mut header[1..])?;
+
+        let len = u32::from_be_bytes(header) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(ProtocolError::TooLarge(len).into());
+        }
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body)?;
+        Ok(Some(Message::decode_body(&body)?))
+    }
+}
+
+// The text encoding, one message per line:
+//
+//   QUIT
+//   MOVE <x> <y>
+//   WRITE <text>
+//   COLOR <r> <g> <b>
+//
+// The text of WRITE is the rest of the line, line breaks in it are written as \n and
+// backslashes as \\ so that a message always stays on one line.
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::Quit => write!(f, "QUIT"),
+            Message::Move { x, y } => write!(f, "MOVE {} {}", x, y),
+            Message::Write(text) => {
+                write!(f, "WRITE ")?;
+                for c in text.chars() {
+                    match c {
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\r' => write!(f, "\\r")?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                Ok(())
+            }
+            Message::ChangeColor(r, g, b) => write!(f, "COLOR {} {} {}", r, g, b),
+        }
+    }
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => unescaped.push('\\'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+impl FromStr for Message {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<Message, ProtocolError> {
+        let line = line.trim_end_matches(&['\r', '\n'][..]);
+        let error = || ProtocolError::BadLine(line.to_string());
+        let (command, rest) = match line.find(' ') {
+            Some(space) => (&line[..space], &line[space + 1..]),
+            None => (line, ""),
+        };
+
+        // exactly `count` numbers
+        let numbers = |count: usize| -> Result<Vec<i32>, ProtocolError> {
+            let numbers: Vec<i32> =
+                rest.split(' ').map(|n| n.parse()).collect::<Result<_, _>>().map_err(|_| error())?;
+            if numbers.len() != count {
+                return Err(error());
+            }
+            Ok(numbers)
+        };
+
+        match command {
+            "QUIT" if rest.is_empty() => Ok(Message::Quit),
+            "MOVE" => {
+                let n = numbers(2)?;
+                Ok(Message::Move { x: n[0], y: n[1] })
+            }
+            "WRITE" => unescape(rest).map(Message::Write).ok_or_else(error),
+            "COLOR" => {
+                let n = numbers(3)?;
+                Ok(Message::ChangeColor(n[0], n[1], n[2]))
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<Message> {
+        vec![
+            Message::Quit,
+            Message::Move { x: -3, y: i32::MAX },
+            Message::Write(String::from("Hello")),
+            Message::Write(String::from("two\nlines, a \\ and ünïcode")),
+            Message::Write(String::new()),
+            Message::ChangeColor(255, 0, -1),
+        ]
+    }
+
+    #[test]
+    fn binary_layout() {
+        assert_eq!(Message::Quit.encode().unwrap(), [0, 0, 0, 1, 0]);
+        assert_eq!(Message::Move { x: 1, y: -1 }.encode().unwrap(), [0, 0, 0, 9, 1, 0, 0, 0, 1, 255, 255, 255, 255]);
+        assert_eq!(Message::Write(String::from("Hi")).encode().unwrap(), [0, 0, 0, 3, 2, b'H', b'i']);
+        assert_eq!(Message::ChangeColor(0, 0, 0).encode().unwrap().len(), 4 + 13);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        for message in messages() {
+            let frame = message.encode().unwrap();
+            assert_eq!(Message::decode(&frame), Ok((message, frame.len())));
+        }
+    }
+
+    #[test]
+    fn decodes_one_frame_at_a_time() {
+        let mut stream = Vec::new();
+        for message in messages() {
+            stream.extend(message.encode().unwrap());
+        }
+
+        let mut decoded = Vec::new();
+        let mut rest = &stream[..];
+        while !rest.is_empty() {
+            let (message, used) = Message::decode(rest).unwrap();
+            decoded.push(message);
+            rest = &rest[used..];
+        }
+        assert_eq!(decoded, messages());
+    }
+
+    #[test]
+    fn frames_up_to_the_limit() {
+        // the tag takes one byte of the frame
+        let longest = Message::Write("x".repeat(MAX_FRAME_LEN - 1));
+        let frame = longest.encode().unwrap();
+        assert_eq!(frame.len(), HEADER_LEN + MAX_FRAME_LEN);
+        assert_eq!(Message::decode(&frame), Ok((longest.clone(), frame.len())));
+        let mut stream = Vec::new();
+        longest.write_frame(&mut stream).unwrap();
+        assert_eq!(Message::read_frame(&stream[..]).unwrap(), Some(longest));
+
+        let too_long = Message::Write("x".repeat(MAX_FRAME_LEN));
+        assert_eq!(too_long.encode(), Err(ProtocolError::TooLarge(MAX_FRAME_LEN + 1)));
+        let mut stream = Vec::new();
+        assert_eq!(too_long.write_frame(&mut stream).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(stream.is_empty());
+    }
+
+    #[test]
+    fn truncated_frames() {
+        let frame = Message::Move { x: 1, y: 2 }.encode().unwrap();
+        assert_eq!(Message::decode(&frame[..2]), Err(ProtocolError::Truncated { needed: 4, available: 2 }));
+        assert_eq!(Message::decode(&frame[..8]), Err(ProtocolError::Truncated { needed: 13, available: 8 }));
+        assert_eq!(Message::decode(&[]), Err(ProtocolError::Truncated { needed: 4, available: 0 }));
+    }
+
+    #[test]
+    fn broken_frames() {
+        assert_eq!(Message::decode(&[0, 0, 0, 0]), Err(ProtocolError::EmptyFrame));
+        assert_eq!(Message::decode(&[0, 0, 0, 1, 9]), Err(ProtocolError::UnknownTag(9)));
+        assert_eq!(Message::decode(&[0, 0, 0, 2, 0, 0]), Err(ProtocolError::BadLength { tag: 0, len: 2 }));
+        assert_eq!(Message::decode(&[0, 0, 0, 3, 1, 0, 0]), Err(ProtocolError::BadLength { tag: 1, len: 3 }));
+        assert_eq!(Message::decode(&[0, 0, 0, 2, 2, 0xff]), Err(ProtocolError::InvalidUtf8));
+        assert_eq!(Message::decode(&[0xff, 0, 0, 0]), Err(ProtocolError::TooLarge(0xff00_0000)));
+    }
+
+    #[test]
+    fn reads_and_writes_streams() {
+        let mut stream = Vec::new();
+        for message in messages() {
+            message.write_frame(&mut stream).unwrap();
+        }
+
+        let mut reader = &stream[..];
+        for message in messages() {
+            assert_eq!(Message::read_frame(&mut reader).unwrap(), Some(message));
+        }
+        assert_eq!(Message::read_frame(&mut reader).unwrap(), None);
+
+        // cut off in the middle of the last frame
+        let mut reader = &stream[..stream.len() - 2];
+        let error = loop {
+            match Message::read_frame(&mut reader) {
+                Ok(Some(_)) => continue,
+                Ok(None) => panic!("the stream ended cleanly"),
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let error = Message::read_frame(&[0, 0, 0, 1, 7][..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn text_round_trip() {
+        assert_eq!(Message::Move { x: 10, y: -20 }.to_string(), "MOVE 10 -20");
+        assert_eq!(Message::Write(String::from("a\\b\nc")).to_string(), "WRITE a\\\\b\\nc");
+        assert_eq!(Message::ChangeColor(1, 2, 3).to_string(), "COLOR 1 2 3");
+        for message in messages() {
+            let line = message.to_string();
+            assert!(!line.contains('\n'));
+            assert_eq!(line.parse(), Ok(message));
+        }
+        assert_eq!("WRITE spaces  stay ".parse(), Ok(Message::Write(String::from("spaces  stay "))));
+        assert_eq!("QUIT\r\n".parse(), Ok(Message::Quit));
+    }
+
+    #[test]
+    fn bad_lines() {
+        let lines = [
+            "", "quit", "QUIT now", "MOVE 1", "MOVE 1 2 3", "MOVE 1  2", "MOVE a b", "COLOR 1 2", "WRITE \\x", "WRITE \\",
+            "JUMP 1 2",
+        ];
+        for line in lines.iter() {
+            assert_eq!(line.parse::<Message>(), Err(ProtocolError::BadLine(line.to_string())), "{}", line);
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<String>,
+    }
+
+    impl Handler for Recorder {
+        fn quit(&mut self) {
+            self.calls.push(String::from("quit"));
+        }
+
+        fn move_to(&mut self, x: i32, y: i32) {
+            self.calls.push(format!("move {} {}", x, y));
+        }
+
+        fn write(&mut self, text: &str) {
+            self.calls.push(format!("write {}", text));
+        }
+    }
+
+    #[test]
+    fn call_dispatches_to_the_handler() {
+        let mut recorder = Recorder::default();
+        for message in messages() {
+            message.call(&mut recorder);
+        }
+        // ChangeColor falls through to the default, which does nothing
+        assert_eq!(recorder.calls.len(), 5);
+        assert_eq!(recorder.calls[..3], ["quit", "move -3 2147483647", "write Hello"]);
+    }
+}
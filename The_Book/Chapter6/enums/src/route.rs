@@ -0,0 +1,452 @@
+use std::mem;
+
+use crate::cidr::{from_bits, to_bits, width, Network};
+use crate::ip::{IpAddr, IpAddrKind};
+
+// What `fn route(ip_kind: IpAddrKind) {}` in the chapter could grow into: a routing table
+// that finds the most specific route (the longest matching prefix) for an address.
+//
+// Each kind of address has its own Patricia trie, a binary trie where chains of nodes
+// with a single child are merged into one node. A lookup follows at most one node per
+// bit of the address, however many routes there are.
+#[derive(Debug, Clone)]
+pub struct RoutingTable<T> {
+    v4: Option<Box<Node<T>>>,
+    v6: Option<Box<Node<T>>>,
+    len: usize,
+}
+
+// A prefix is stored left aligned in a u128, so bit 0 is the first bit of the address for
+// both kinds; the bits after `len` are zero.
+#[derive(Debug, Clone)]
+struct Node<T> {
+    key: u128,
+    len: u8,
+    // None for the nodes that only exist to branch
+    value: Option<T>,
+    children: [Option<Box<Node<T>>>; 2],
+}
+
+// the first `len` bits set
+fn mask(len: u8) -> u128 {
+    match len {
+        0 => 0,
+        len => u128::MAX << (128 - len as u32),
+    }
+}
+
+// bit `index` from the left, 0 or 1
+fn bit(key: u128, index: u8) -> usize {
+    (key >> (127 - index as u32)) as usize & 1
+}
+
+fn key(address: &IpAddr) -> u128 {
+    to_bits(address) << (128 - width(address.kind()) as u32)
+}
+
+fn network(kind: IpAddrKind, key: u128, len: u8) -> Network {
+    let address = from_bits(kind, key >> (128 - width(kind) as u32));
+    Network::new(address, len).expect("trie keys have no host bits")
+}
+
+impl<T> Node<T> {
+    fn leaf(key: u128, len: u8, value: T) -> Box<Node<T>> {
+        Box::new(Node { key, len, value: Some(value), children: [None, None] })
+    }
+
+    // how many leading bits this node has in common with the prefix
+    fn common_len(&self, key: u128, len: u8) -> u8 {
+        let differ = (self.key ^ key).leading_zeros() as u8;
+        differ.min(self.len).min(len)
+    }
+
+    fn matches(&self, key: u128) -> bool {
+        (self.key ^ key) & mask(self.len) == 0
+    }
+}
+
+fn insert<T>(slot: &mut Option<Box<Node<T>>>, key: u128, len: u8, value: T) -> Option<T> {
+    let node = match slot {
+        None => {
+            *slot = Some(Node::leaf(key, len, value));
+            return None;
+        }
+        Some(node) => node,
+    };
+
+    let common = node.common_len(key, len);
+    if common == node.len && common == len {
+        return node.value.replace(value);
+    }
+    if common == node.len {
+        // the new prefix is longer, it belongs below this node
+        return insert(&mut node.children[bit(key, node.len)], key, len, value);
+    }
+
+    // the prefixes part ways before the end of this node: a new node takes its place,
+    // either the new prefix itself or a branch above both
+    let old = slot.take().expect("slot was just matched");
+    let mut parent = if common == len {
+        Node::leaf(key, len, value)
+    } else {
+        let mut branch = Box::new(Node { key: key & mask(common), len: common, value: None, children: [None, None] });
+        branch.children[bit(key, common)] = Some(Node::leaf(key, len, value));
+        branch
+    };
+    let side = bit(old.key, common);
+    parent.children[side] = Some(old);
+    *slot = Some(parent);
+    None
+}
+
+fn remove<T>(slot: &mut Option<Box<Node<T>>>, key: u128, len: u8) -> Option<T> {
+    let node = slot.as_mut()?;
+    if !node.matches(key) || node.len > len {
+        return None;
+    }
+
+    let removed = if node.len == len {
+        node.value.take()
+    } else {
+        remove(&mut node.children[bit(key, node.len)], key, len)
+    };
+
+    if removed.is_some() {
+        compact(slot);
+    }
+    removed
+}
+
+// a node without a value is only kept while it branches
+fn compact<T>(slot: &mut Option<Box<Node<T>>>) {
+    if let Some(node) = slot {
+        if node.value.is_some() {
+            return;
+        }
+        match &mut node.children {
+            [Some(_), Some(_)] => {}
+            [child @ Some(_), None] | [None, child @ Some(_)] => {
+                let child = child.take();
+                *slot = child;
+            }
+            [None, None] => *slot = None,
+        }
+    }
+}
+
+impl<T> Default for RoutingTable<T> {
+    fn default() -> RoutingTable<T> {
+        RoutingTable { v4: None, v6: None, len: 0 }
+    }
+}
+
+impl<T> RoutingTable<T> {
+    pub fn new() -> RoutingTable<T> {
+        RoutingTable::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn root(&self, kind: IpAddrKind) -> &Option<Box<Node<T>>> {
+        match kind {
+            IpAddrKind::V4 => &self.v4,
+            IpAddrKind::V6 => &self.v6,
+        }
+    }
+
+    fn root_mut(&mut self, kind: IpAddrKind) -> &mut Option<Box<Node<T>>> {
+        match kind {
+            IpAddrKind::V4 => &mut self.v4,
+            IpAddrKind::V6 => &mut self.v6,
+        }
+    }
+
+    // Adds a route, or replaces the value of the same network and returns the old one
+    pub fn insert(&mut self, network: Network, value: T) -> Option<T> {
+        let key = key(&network.address());
+        let old = insert(self.root_mut(network.kind()), key, network.prefix_len(), value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove(&mut self, network: &Network) -> Option<T> {
+        let key = key(&network.address());
+        let removed = remove(self.root_mut(network.kind()), key, network.prefix_len());
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    // the value of exactly this network
+    pub fn get(&self, network: &Network) -> Option<&T> {
+        let (key, len) = (key(&network.address()), network.prefix_len());
+        let mut next = self.root(network.kind()).as_ref();
+        while let Some(node) = next {
+            if !node.matches(key) || node.len > len {
+                return None;
+            }
+            if node.len == len {
+                return node.value.as_ref();
+            }
+            next = node.children[bit(key, node.len)].as_ref();
+        }
+        None
+    }
+
+    // The most specific route to `address`, None when no route covers it
+    pub fn lookup(&self, address: &IpAddr) -> Option<(Network, &T)> {
+        let key = key(address);
+        let mut best = None;
+        let mut next = self.root(address.kind()).as_ref();
+        while let Some(node) = next {
+            if !node.matches(key) {
+                break;
+            }
+            if let Some(value) = &node.value {
+                best = Some((node.key, node.len, value));
+            }
+            if node.len == 128 {
+                break;
+            }
+            next = node.children[bit(key, node.len)].as_ref();
+        }
+        best.map(|(key, len, value)| (network(address.kind(), key, len), value))
+    }
+
+    // every route in order, IPv4 first and shorter prefixes before the longer ones they contain
+    pub fn routes(&self) -> Vec<(Network, &T)> {
+        fn walk<'a, T>(node: &'a Option<Box<Node<T>>>, kind: IpAddrKind, routes: &mut Vec<(Network, &'a T)>) {
+            if let Some(node) = node {
+                if let Some(value) = &node.value {
+                    routes.push((network(kind, node.key, node.len), value));
+                }
+                for child in &node.children {
+                    walk(child, kind, routes);
+                }
+            }
+        }
+
+        let mut routes = Vec::with_capacity(self.len);
+        walk(&self.v4, IpAddrKind::V4, &mut routes);
+        walk(&self.v6, IpAddrKind::V6, &mut routes);
+        routes
+    }
+
+    pub fn clear(&mut self) {
+        *self = RoutingTable::default();
+    }
+}
+
+// The obvious way: check every route. Here to compare the trie against, in the tests and the benchmark.
+#[derive(Debug, Clone)]
+pub struct LinearTable<T> {
+    routes: Vec<(Network, T)>,
+}
+
+impl<T> Default for LinearTable<T> {
+    fn default() -> LinearTable<T> {
+        LinearTable { routes: Vec::new() }
+    }
+}
+
+impl<T> LinearTable<T> {
+    pub fn new() -> LinearTable<T> {
+        LinearTable::default()
+    }
+
+    pub fn insert(&mut self, network: Network, value: T) -> Option<T> {
+        match self.routes.iter_mut().find(|(net, _)| *net == network) {
+            Some((_, old)) => Some(mem::replace(old, value)),
+            None => {
+                self.routes.push((network, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, network: &Network) -> Option<T> {
+        let index = self.routes.iter().position(|(net, _)| net == network)?;
+        Some(self.routes.swap_remove(index).1)
+    }
+
+    pub fn lookup(&self, address: &IpAddr) -> Option<(Network, &T)> {
+        self.routes
+            .iter()
+            .filter(|(net, _)| net.contains(address))
+            .max_by_key(|(net, _)| net.prefix_len())
+            .map(|(net, value)| (*net, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn net(s: &str) -> Network {
+        s.parse().unwrap()
+    }
+
+    fn addr(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn table() -> RoutingTable<&'static str> {
+        let mut table = RoutingTable::new();
+        for (network, hop) in [
+            ("0.0.0.0/0", "default"),
+            ("10.0.0.0/8", "corp"),
+            ("10.1.0.0/16", "lab"),
+            ("10.1.2.0/24", "rack"),
+            ("192.168.0.0/16", "home"),
+            ("::/0", "default6"),
+            ("2001:db8::/32", "doc"),
+            ("2001:db8:1::/48", "doc-lab"),
+        ]
+        .iter()
+        {
+            assert_eq!(table.insert(net(network), *hop), None);
+        }
+        table
+    }
+
+    fn hop<'a>(table: &'a RoutingTable<&'static str>, address: &str) -> Option<&'a str> {
+        table.lookup(&addr(address)).map(|(_, hop)| *hop)
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let table = table();
+        assert_eq!(table.len(), 8);
+        assert_eq!(hop(&table, "10.1.2.3"), Some("rack"));
+        assert_eq!(hop(&table, "10.1.3.3"), Some("lab"));
+        assert_eq!(hop(&table, "10.200.0.1"), Some("corp"));
+        assert_eq!(hop(&table, "8.8.8.8"), Some("default"));
+        assert_eq!(table.lookup(&addr("10.1.2.3")).unwrap().0, net("10.1.2.0/24"));
+
+        assert_eq!(hop(&table, "2001:db8:1::1"), Some("doc-lab"));
+        assert_eq!(hop(&table, "2001:db8:2::1"), Some("doc"));
+        assert_eq!(hop(&table, "fe80::1"), Some("default6"));
+    }
+
+    #[test]
+    fn no_route() {
+        let mut table = RoutingTable::new();
+        table.insert(net("10.0.0.0/8"), 1);
+        assert_eq!(table.lookup(&addr("11.0.0.1")), None);
+        assert_eq!(table.lookup(&addr("::ffff:10.0.0.1")), None);
+        assert!(RoutingTable::<u8>::new().lookup(&addr("10.0.0.1")).is_none());
+    }
+
+    #[test]
+    fn host_routes() {
+        let mut table = RoutingTable::new();
+        table.insert(net("10.0.0.1/32"), "one");
+        table.insert(net("10.0.0.0/31"), "pair");
+        table.insert(net("::1/128"), "loopback");
+        assert_eq!(table.lookup(&addr("10.0.0.1")).map(|r| *r.1), Some("one"));
+        assert_eq!(table.lookup(&addr("10.0.0.0")).map(|r| *r.1), Some("pair"));
+        assert_eq!(table.lookup(&addr("::1")).map(|r| *r.1), Some("loopback"));
+        assert_eq!(table.lookup(&addr("::2")), None);
+    }
+
+    #[test]
+    fn insert_replaces_and_get_is_exact() {
+        let mut table = table();
+        assert_eq!(table.insert(net("10.0.0.0/8"), "corp2"), Some("corp"));
+        assert_eq!(table.len(), 8);
+        assert_eq!(table.get(&net("10.0.0.0/8")), Some(&"corp2"));
+        assert_eq!(table.get(&net("10.0.0.0/9")), None);
+        assert_eq!(table.get(&net("10.1.2.0/24")), Some(&"rack"));
+    }
+
+    #[test]
+    fn remove_routes() {
+        let mut table = table();
+        assert_eq!(table.remove(&net("10.1.0.0/16")), Some("lab"));
+        assert_eq!(table.remove(&net("10.1.0.0/16")), None);
+        assert_eq!(table.remove(&net("172.16.0.0/12")), None);
+        assert_eq!(table.len(), 7);
+        assert_eq!(hop(&table, "10.1.3.3"), Some("corp"));
+        assert_eq!(hop(&table, "10.1.2.3"), Some("rack"));
+
+        for (network, _) in table.clone().routes() {
+            table.remove(&network).unwrap();
+        }
+        assert!(table.is_empty());
+        assert!(table.v4.is_none() && table.v6.is_none());
+    }
+
+    #[test]
+    fn routes_in_order() {
+        let networks: Vec<String> = table().routes().iter().map(|(net, _)| net.to_string()).collect();
+        assert_eq!(
+            networks,
+            [
+                "0.0.0.0/0",
+                "10.0.0.0/8",
+                "10.1.0.0/16",
+                "10.1.2.0/24",
+                "192.168.0.0/16",
+                "::/0",
+                "2001:db8::/32",
+                "2001:db8:1::/48"
+            ]
+        );
+    }
+
+    // a small xorshift, enough to make up routes and addresses
+    fn random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn agrees_with_a_linear_scan() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        let mut trie = RoutingTable::new();
+        let mut linear = LinearTable::new();
+
+        for i in 0..2000 {
+            let bits = random(&mut state);
+            let network = if i % 2 == 0 {
+                let address = IpAddr::V4((bits as u32).into());
+                Network::containing(address, (bits >> 32) as u8 % 33).unwrap()
+            } else {
+                // few distinct top bits, so that the prefixes nest
+                let address = IpAddr::V6(((bits as u128) << 64 | bits as u128 >> 8).into());
+                Network::containing(address, (bits >> 40) as u8 % 129).unwrap()
+            };
+            assert_eq!(trie.insert(network, i), linear.insert(network, i));
+
+            // and take some out again
+            if i % 7 == 0 {
+                assert_eq!(trie.remove(&network), linear.remove(&network));
+            }
+        }
+
+        for _ in 0..5000 {
+            let bits = random(&mut state);
+            for address in [
+                IpAddr::V4((bits as u32).into()),
+                IpAddr::V6(((bits as u128) << 64).into()),
+            ]
+            .iter()
+            {
+                let from_trie = trie.lookup(address).map(|(net, value)| (net, *value));
+                let from_scan = linear.lookup(address).map(|(net, value)| (net, *value));
+                assert_eq!(from_trie, from_scan, "{}", address);
+            }
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use std::fmt::Write as _;
+
+use crate::message::{Handler, Message};
+
+pub mod cli;
+
+// Turtle graphics driven by Messages: Move { x, y } draws a line from the pen to (x, y),
+// ChangeColor picks the color of what comes next and Write puts a text label where the
+// pen is. Quit finishes the drawing, whatever comes after it is ignored.
+//
+// (0, 0) is the top left corner and y grows downwards, like in SVG and PPM. The pen starts
+// at (0, 0) in black on a white canvas. Points off the canvas are fine, what falls outside
+// is simply not visible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pen: (i32, i32),
+    color: Rgb,
+    strokes: Vec<Stroke>,
+    finished: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+pub const BLACK: Rgb = Rgb(0, 0, 0);
+pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+// The widest and tallest canvas, 4096 x 4096 pixels are about 50 MB once rasterized
+pub const MAX_SIZE: u32 = 4096;
+
+// What has been drawn, in order, so both renderers paint the same picture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stroke {
+    Line { from: (i32, i32), to: (i32, i32), color: Rgb },
+    // `at` is the left end of the baseline
+    Label { at: (i32, i32), text: String, color: Rgb },
+}
+
+impl Canvas {
+    // a width or height over MAX_SIZE is cut down to MAX_SIZE
+    pub fn new(width: u32, height: u32) -> Canvas {
+        let (width, height) = (width.min(MAX_SIZE), height.min(MAX_SIZE));
+        Canvas { width, height, pen: (0, 0), color: BLACK, strokes: Vec::new(), finished: false }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pen(&self) -> (i32, i32) {
+        self.pen
+    }
+
+    pub fn color(&self) -> Rgb {
+        self.color
+    }
+
+    pub fn strokes(&self) -> &[Stroke] {
+        &self.strokes
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn execute(&mut self, message: &Message) {
+        message.call(self);
+    }
+
+    // Executes messages up to and including the first Quit, returns how many that was
+    pub fn run<'a, I: IntoIterator<Item = &'a Message>>(&mut self, messages: I) -> usize {
+        let mut count = 0;
+        for message in messages {
+            if self.finished {
+                break;
+            }
+            self.execute(message);
+            count += 1;
+        }
+        count
+    }
+
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        // writing to a String can't fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            self.width, self.height
+        );
+        let _ = writeln!(svg, r#"  <rect width="100%" height="100%" fill="{}"/>"#, css(WHITE));
+        for stroke in &self.strokes {
+            let _ = match stroke {
+                Stroke::Line { from, to, color } => writeln!(
+                    svg,
+                    r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}"/>"#,
+                    from.0,
+                    from.1,
+                    to.0,
+                    to.1,
+                    css(*color)
+                ),
+                Stroke::Label { at, text, color } => writeln!(
+                    svg,
+                    r#"  <text x="{}" y="{}" fill="{}" font-family="monospace" font-size="{}">{}</text>"#,
+                    at.0,
+                    at.1,
+                    css(*color),
+                    GLYPH_HEIGHT + 2,
+                    escape(text)
+                ),
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // Binary PPM (P6): a short text header, then three bytes per pixel row by row
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for Rgb(r, g, b) in self.rasterize() {
+            ppm.extend_from_slice(&[r, g, b]);
+        }
+        ppm
+    }
+
+    // The pixels of the picture, row by row
+    pub fn rasterize(&self) -> Vec<Rgb> {
+        let mut raster = Raster { width: self.width, height: self.height, pixels: Vec::new() };
+        // can't overflow even with a 32 bit usize, the sides are at most MAX_SIZE
+        let pixels = (self.width as usize)
+            .checked_mul(self.height as usize)
+            .expect("the canvas is at most MAX_SIZE wide and high");
+        raster.pixels = vec![WHITE; pixels];
+        for stroke in &self.strokes {
+            match stroke {
+                Stroke::Line { from, to, color } => raster.line(*from, *to, *color),
+                Stroke::Label { at, text, color } => raster.text(*at, text, *color),
+            }
+        }
+        raster.pixels
+    }
+}
+
+impl Handler for Canvas {
+    fn quit(&mut self) {
+        self.finished = true;
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) {
+        if self.finished {
+            return;
+        }
+        self.strokes.push(Stroke::Line { from: self.pen, to: (x, y), color: self.color });
+        self.pen = (x, y);
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.finished {
+            return;
+        }
+        self.strokes.push(Stroke::Label { at: self.pen, text: text.to_string(), color: self.color });
+    }
+
+    fn change_color(&mut self, r: i32, g: i32, b: i32) {
+        if self.finished {
+            return;
+        }
+        // the message carries i32s, anything beyond a byte is as far as the color goes
+        let channel = |value: i32| value.clamp(0, 255) as u8;
+        self.color = Rgb(channel(r), channel(g), channel(b));
+    }
+}
+
+fn css(Rgb(r, g, b): Rgb) -> String {
+    format!("rgb({},{},{})", r, g, b)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct Raster {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgb>,
+}
+
+impl Raster {
+    fn plot(&mut self, x: i64, y: i64, color: Rgb) {
+        if x >= 0 && y >= 0 && x < i64::from(self.width) && y < i64::from(self.height) {
+            self.pixels[y as usize * self.width as usize + x as usize] = color;
+        }
+    }
+
+    fn line(&mut self, from: (i32, i32), to: (i32, i32), color: Rgb) {
+        // a line to (i32::MAX, 0) would take forever one pixel at a time, so only the part
+        // that crosses the canvas is drawn
+        let (from, to) = match self.clip(from, to) {
+            Some(ends) => ends,
+            None => return,
+        };
+
+        // Bresenham's algorithm
+        let (mut x, mut y) = from;
+        let dx = (to.0 - x).abs();
+        let dy = -(to.1 - y).abs();
+        let step_x = if x < to.0 { 1 } else { -1 };
+        let step_y = if y < to.1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.plot(x, y, color);
+            if (x, y) == to {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    // Liang-Barsky: the ends of the segment moved onto the edges of the canvas, None when
+    // it doesn't cross the canvas at all
+    fn clip(&self, from: (i32, i32), to: (i32, i32)) -> Option<((i64, i64), (i64, i64))> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let (x0, y0) = (f64::from(from.0), f64::from(from.1));
+        let (dx, dy) = (f64::from(to.0) - x0, f64::from(to.1) - y0);
+        let (max_x, max_y) = (f64::from(self.width - 1), f64::from(self.height - 1));
+
+        let mut start: f64 = 0.0;
+        let mut end: f64 = 1.0;
+        for (p, q) in [(-dx, x0), (dx, max_x - x0), (-dy, y0), (dy, max_y - y0)].iter() {
+            if *p == 0.0 {
+                // parallel to this edge and outside of it
+                if *q < 0.0 {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if *p < 0.0 {
+                    start = start.max(t);
+                } else {
+                    end = end.min(t);
+                }
+            }
+        }
+        if start > end {
+            return None;
+        }
+
+        let point = |t: f64| ((x0 + t * dx).round() as i64, (y0 + t * dy).round() as i64);
+        Some((point(start), point(end)))
+    }
+
+    fn text(&mut self, at: (i32, i32), text: &str, color: Rgb) {
+        let (x, baseline) = (i64::from(at.0), i64::from(at.1));
+        for (i, c) in text.chars().enumerate() {
+            let left = x + i as i64 * (GLYPH_WIDTH + 1);
+            let top = baseline - GLYPH_HEIGHT;
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                        self.plot(left + column, top + row as i64, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+const GLYPH_WIDTH: i64 = 3;
+const GLYPH_HEIGHT: i64 = 5;
+
+// A 3x5 pixel font, one row of three bits per byte with the leftmost pixel in the highest
+// bit. Lower case is drawn as upper case and a character missing here as a checkerboard.
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        _ => [0b101, 0b010, 0b101, 0b010, 0b101],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drawn(canvas: &Canvas) -> Vec<(usize, usize)> {
+        let width = canvas.width() as usize;
+        canvas
+            .rasterize()
+            .iter()
+            .enumerate()
+            .filter(|(_, pixel)| **pixel != WHITE)
+            .map(|(i, _)| (i % width, i / width))
+            .collect()
+    }
+
+    #[test]
+    fn messages_move_the_pen() {
+        let mut canvas = Canvas::new(10, 10);
+        let messages = vec![
+            Message::Move { x: 3, y: 0 },
+            Message::ChangeColor(255, 0, 300),
+            Message::Write(String::from("hi")),
+            Message::Move { x: 3, y: 4 },
+            Message::Quit,
+            Message::Move { x: 9, y: 9 },
+        ];
+
+        assert_eq!(canvas.run(&messages), 5);
+        assert!(canvas.is_finished());
+        assert_eq!(canvas.pen(), (3, 4));
+        assert_eq!(canvas.color(), Rgb(255, 0, 255));
+        assert_eq!(
+            canvas.strokes(),
+            [
+                Stroke::Line { from: (0, 0), to: (3, 0), color: BLACK },
+                Stroke::Label { at: (3, 0), text: String::from("hi"), color: Rgb(255, 0, 255) },
+                Stroke::Line { from: (3, 0), to: (3, 4), color: Rgb(255, 0, 255) },
+            ]
+        );
+    }
+
+    #[test]
+    fn canvas_size_is_capped() {
+        let canvas = Canvas::new(100_000, u32::MAX);
+        assert_eq!((canvas.width(), canvas.height()), (MAX_SIZE, MAX_SIZE));
+        assert_eq!(Canvas::new(MAX_SIZE, 1).rasterize().len(), MAX_SIZE as usize);
+    }
+
+    #[test]
+    fn rasterizes_lines() {
+        let mut canvas = Canvas::new(5, 5);
+        canvas.execute(&Message::Move { x: 4, y: 4 });
+        assert_eq!(drawn(&canvas), [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+
+        let mut canvas = Canvas::new(5, 3);
+        canvas.execute(&Message::Move { x: 4, y: 2 });
+        assert_eq!(drawn(&canvas), [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn clips_lines_to_the_canvas() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.run(&[
+            Message::Move { x: -10, y: 2 },
+            Message::Move { x: i32::MAX, y: 2 },
+            // entirely outside
+            Message::Move { x: i32::MAX, y: -5 },
+        ]);
+        assert_eq!(drawn(&canvas), [(0, 0), (0, 2), (1, 2), (2, 2), (3, 2)]);
+
+        let mut empty = Canvas::new(0, 0);
+        empty.execute(&Message::Move { x: 5, y: 5 });
+        assert!(empty.rasterize().is_empty());
+    }
+
+    #[test]
+    fn draws_labels_with_the_pixel_font() {
+        let mut canvas = Canvas::new(8, 6);
+        canvas.run(&[Message::Move { x: 0, y: 5 }, Message::ChangeColor(255, 255, 255), Message::Write(String::from("1i"))]);
+        let pixels = canvas.rasterize();
+        // the line is painted over by the white label where they meet
+        let row = |y: usize| -> String {
+            pixels[y * 8..(y + 1) * 8].iter().map(|pixel| if *pixel == WHITE { '.' } else { '#' }).collect()
+        };
+        assert_eq!(row(0), "#.......");
+        assert_eq!(row(1), "........");
+        assert_eq!(row(2), "#.......");
+        assert_eq!(row(5), "#.......");
+        assert_eq!(glyph('i'), glyph('I'));
+    }
+
+    #[test]
+    fn renders_svg() {
+        let mut canvas = Canvas::new(20, 10);
+        canvas.run(&[Message::Move { x: 5, y: 5 }, Message::ChangeColor(0, 128, 0), Message::Write(String::from("a<b"))]);
+        assert_eq!(
+            canvas.to_svg(),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"10\" viewBox=\"0 0 20 10\">\n\
+             \x20 <rect width=\"100%\" height=\"100%\" fill=\"rgb(255,255,255)\"/>\n\
+             \x20 <line x1=\"0\" y1=\"0\" x2=\"5\" y2=\"5\" stroke=\"rgb(0,0,0)\"/>\n\
+             \x20 <text x=\"5\" y=\"5\" fill=\"rgb(0,128,0)\" font-family=\"monospace\" font-size=\"7\">a&lt;b</text>\n\
+             </svg>\n"
+        );
+    }
+
+    #[test]
+    fn renders_ppm() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.run(&[Message::ChangeColor(1, 2, 3), Message::Move { x: 0, y: 0 }]);
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend_from_slice(&[1, 2, 3, 255, 255, 255]);
+        assert_eq!(canvas.to_ppm(), expected);
+    }
+}
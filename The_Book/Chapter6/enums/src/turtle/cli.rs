@@ -0,0 +1,139 @@
+use std::error::Error;
+use std::io::{BufRead, Write};
+
+use super::{Canvas, MAX_SIZE};
+use crate::message::Message;
+
+pub const USAGE: &str = "\
+usage: turtle <output.svg|output.ppm> [<width> <height>]    (at most 4096 by 4096)
+
+Reads messages from stdin, one per line, and draws them:
+  MOVE <x> <y>      draw a line from the pen to x y
+  COLOR <r> <g> <b> the color of what comes next
+  WRITE <text>      a label at the pen
+  QUIT              the end of the drawing";
+
+pub const DEFAULT_SIZE: u32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Svg,
+    Ppm,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub output: String,
+    pub format: Format,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, &'static str> {
+        // skip the program name
+        let mut args = args.iter().skip(1);
+
+        let output = args.next().ok_or("the file to draw in is needed")?.clone();
+        let format = if output.ends_with(".svg") {
+            Format::Svg
+        } else if output.ends_with(".ppm") {
+            Format::Ppm
+        } else {
+            return Err("the output file has to end in .svg or .ppm");
+        };
+
+        let (width, height) = match (args.next(), args.next()) {
+            (None, _) => (DEFAULT_SIZE, DEFAULT_SIZE),
+            (Some(_), None) => return Err("the height is missing"),
+            (Some(width), Some(height)) => {
+                let size = |arg: &String| arg.parse().map_err(|_| "the size has to be two numbers like 200 100");
+                (size(width)?, size(height)?)
+            }
+        };
+        if width > MAX_SIZE || height > MAX_SIZE {
+            return Err("the canvas can be at most 4096 by 4096");
+        }
+
+        if args.next().is_some() {
+            return Err("too many arguments");
+        }
+        Ok(Config { output, format, width, height })
+    }
+}
+
+// Draws the messages of `input` and writes the picture to `output` in the format of the config
+pub fn run<R: BufRead, W: Write>(config: &Config, input: R, mut output: W) -> Result<(), Box<dyn Error>> {
+    let mut canvas = Canvas::new(config.width, config.height);
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        // blank lines and # comments, so a drawing can be kept in a file
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let message: Message = line.parse().map_err(|e| format!("line {}: {}", i + 1, e))?;
+        canvas.execute(&message);
+        if canvas.is_finished() {
+            break;
+        }
+    }
+
+    match config.format {
+        Format::Svg => output.write_all(canvas.to_svg().as_bytes())?,
+        Format::Ppm => output.write_all(&canvas.to_ppm())?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("turtle").chain(line.split_whitespace()).map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_arguments() {
+        assert_eq!(
+            Config::new(&args("out.svg")),
+            Ok(Config { output: String::from("out.svg"), format: Format::Svg, width: 200, height: 200 })
+        );
+        assert_eq!(
+            Config::new(&args("out.ppm 30 20")),
+            Ok(Config { output: String::from("out.ppm"), format: Format::Ppm, width: 30, height: 20 })
+        );
+
+        assert_eq!(Config::new(&args("")), Err("the file to draw in is needed"));
+        assert_eq!(Config::new(&args("out.png")), Err("the output file has to end in .svg or .ppm"));
+        assert_eq!(Config::new(&args("out.svg 30")), Err("the height is missing"));
+        assert!(Config::new(&args("out.svg 30 tall")).is_err());
+        assert!(Config::new(&args("out.ppm 4096 4096")).is_ok());
+        assert_eq!(Config::new(&args("out.ppm 100000 100000")), Err("the canvas can be at most 4096 by 4096"));
+        assert_eq!(Config::new(&args("out.ppm 1 4097")), Err("the canvas can be at most 4096 by 4096"));
+        assert_eq!(Config::new(&args("out.svg 1 2 3")), Err("too many arguments"));
+    }
+
+    #[test]
+    fn draws_the_input() {
+        let config = Config::new(&args("out.ppm 2 2")).unwrap();
+        let mut output = Vec::new();
+        run(&config, "# a dot\n\nCOLOR 255 0 0\nMOVE 1 1\nQUIT\nnot read any more\n".as_bytes(), &mut output).unwrap();
+
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend_from_slice(&[255, 0, 0, 255, 255, 255, 255, 255, 255, 255, 0, 0]);
+        assert_eq!(output, expected);
+
+        let config = Config::new(&args("out.svg")).unwrap();
+        let mut output = Vec::new();
+        run(&config, "WRITE hello".as_bytes(), &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains(">hello</text>"));
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        let config = Config::new(&args("out.svg")).unwrap();
+        let err = run(&config, "MOVE 1 1\nJUMP\n".as_bytes(), Vec::new()).unwrap_err();
+        assert!(err.to_string().starts_with("line 2: "));
+    }
+}
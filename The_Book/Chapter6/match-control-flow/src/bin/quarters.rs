@@ -0,0 +1,22 @@
+// Keeps track of a state quarter collection: quarters my-quarters.txt add NY, see USAGE
+use std::env;
+use std::io;
+use std::process;
+
+use match_control_flow::collection::cli::{self, Config};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let config = Config::new(&args).unwrap_or_else(|err| {
+        eprintln!("Problem parsing arguments: {}", err);
+        eprintln!("{}", cli::USAGE);
+        process::exit(1);
+    });
+
+    let stdout = io::stdout();
+    if let Err(e) = cli::run(config, stdout.lock()) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::str::FromStr;
+
+use crate::state::UsState;
+
+// The coins of the chapter. A quarter knows the state on its back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Coin {
+    Penny,
+    Nickel,
+    Dime,
+    Quarter(UsState),
+}
+
+pub fn value_in_cents(coin: Coin) -> u8 {
+    match coin {
+        Coin::Penny => 1,
+        Coin::Nickel => 5,
+        Coin::Dime => 10,
+        Coin::Quarter(_) => 25,
+    }
+}
+
+impl Coin {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Coin::Penny => "penny",
+            Coin::Nickel => "nickel",
+            Coin::Dime => "dime",
+            Coin::Quarter(_) => "quarter",
+        }
+    }
+
+    pub fn plural(&self) -> &'static str {
+        match self {
+            Coin::Penny => "pennies",
+            Coin::Nickel => "nickels",
+            Coin::Dime => "dimes",
+            Coin::Quarter(_) => "quarters",
+        }
+    }
+}
+
+// penny, quarter (Alaska)
+impl fmt::Display for Coin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Coin::Quarter(state) => write!(f, "quarter ({})", state),
+            coin => f.write_str(coin.name()),
+        }
+    }
+}
+
+// Making change
+
+// The US coins in cents, largest first
+pub const US_DENOMINATIONS: [u32; 4] = [25, 10, 5, 1];
+
+// How many of each coin to hand out. Any state quarter will do, so there is no Coin here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Change {
+    pub quarters: u32,
+    pub dimes: u32,
+    pub nickels: u32,
+    pub pennies: u32,
+}
+
+impl Change {
+    pub fn coin_count(&self) -> u64 {
+        u64::from(self.quarters) + u64::from(self.dimes) + u64::from(self.nickels) + u64::from(self.pennies)
+    }
+
+    pub fn cents(&self) -> u64 {
+        25 * u64::from(self.quarters) + 10 * u64::from(self.dimes) + 5 * u64::from(self.nickels) + u64::from(self.pennies)
+    }
+}
+
+// The fewest US coins for an amount. Taking the largest coin that fits is optimal for
+// 25, 10, 5 and 1, which optimal_change checks in the tests.
+pub fn make_change(cents: u32) -> Change {
+    Change { quarters: cents / 25, dimes: cents % 25 / 10, nickels: cents % 25 % 10 / 5, pennies: cents % 5 }
+}
+
+// The values of the denominations that are used, largest first, without duplicates or zeros
+fn sorted(denominations: &[u32]) -> Vec<u32> {
+    let mut sorted: Vec<u32> = denominations.iter().copied().filter(|d| *d > 0).collect();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    sorted.dedup();
+    sorted
+}
+
+// Change for any set of denominations as (denomination, count) pairs, largest first.
+//
+// The greedy way takes as many of the largest denomination as fit, then goes on with the
+// next. That is fast but not always the fewest coins: with 4, 3 and 1, 6 becomes 4+1+1
+// instead of 3+3, and with 5 and 3, 9 is not made at all although 3+3+3 would do.
+// None when the amount can't be made this way.
+pub fn greedy_change(amount: u32, denominations: &[u32]) -> Option<Vec<(u32, u32)>> {
+    let mut rest = amount;
+    let mut change = Vec::new();
+    for denomination in sorted(denominations) {
+        let count = rest / denomination;
+        if count > 0 {
+            change.push((denomination, count));
+            rest %= denomination;
+        }
+    }
+    if rest == 0 {
+        Some(change)
+    } else {
+        None
+    }
+}
+
+// The largest amount optimal_change works out, in units of the gcd of the denominations.
+// Its tables take about 8 bytes per unit, 8 MB at the most.
+pub const MAX_OPTIMAL_AMOUNT: u32 = 1_000_000;
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// The fewest coins for the amount, found by dynamic programming over every amount up to
+// it. That takes time and memory in proportion to the amount, so it is counted in the
+// largest unit all the denominations are made of (5 for 25, 10 and 5). None when no
+// combination makes the amount, and when it is more than MAX_OPTIMAL_AMOUNT of those units.
+pub fn optimal_change(amount: u32, denominations: &[u32]) -> Option<Vec<(u32, u32)>> {
+    let denominations = sorted(denominations);
+    let unit = denominations.iter().fold(0, |unit, &d| gcd(unit, d));
+    if unit == 0 {
+        // no denominations at all
+        return if amount == 0 { Some(Vec::new()) } else { None };
+    }
+    // is_multiple_of is too new for the compilers this still builds with
+    #[allow(clippy::manual_is_multiple_of)]
+    if amount % unit != 0 || amount / unit > MAX_OPTIMAL_AMOUNT {
+        return None;
+    }
+    let amount = (amount / unit) as usize;
+    let denominations: Vec<u32> = denominations.iter().map(|d| d / unit).collect();
+
+    // fewest[a] is the fewest coins for a, last[a] the coin added last to get there
+    let mut fewest: Vec<Option<u32>> = vec![None; amount + 1];
+    let mut last = vec![0u32; amount + 1];
+    fewest[0] = Some(0);
+    for a in 1..=amount {
+        for &denomination in &denominations {
+            let d = denomination as usize;
+            if d > a {
+                continue;
+            }
+            if let Some(count) = fewest[a - d] {
+                match fewest[a] {
+                    Some(best) if best <= count + 1 => {}
+                    _ => {
+                        fewest[a] = Some(count + 1);
+                        last[a] = denomination;
+                    }
+                }
+            }
+        }
+    }
+    fewest[amount]?;
+
+    let mut counts: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut a = amount;
+    while a > 0 {
+        *counts.entry(last[a]).or_insert(0) += 1;
+        a -= last[a] as usize;
+    }
+    Some(counts.into_iter().rev().map(|(denomination, count)| (denomination * unit, count)).collect())
+}
+
+// Counting coins
+
+// A bag of coins, counted per coin
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Bag {
+    counts: BTreeMap<Coin, u64>,
+}
+
+impl Bag {
+    pub fn new() -> Bag {
+        Bag::default()
+    }
+
+    pub fn add(&mut self, coin: Coin) {
+        self.add_many(coin, 1);
+    }
+
+    pub fn add_many(&mut self, coin: Coin, count: u64) {
+        if count > 0 {
+            let total = self.counts.entry(coin).or_insert(0);
+            *total = total.saturating_add(count);
+        }
+    }
+
+    pub fn count(&self, coin: Coin) -> u64 {
+        self.counts.get(&coin).copied().unwrap_or(0)
+    }
+
+    pub fn coin_count(&self) -> u64 {
+        self.counts.values().fold(0, |sum, count| sum.saturating_add(*count))
+    }
+
+    pub fn total_cents(&self) -> u64 {
+        self.counts
+            .iter()
+            .fold(0, |sum, (coin, count)| sum.saturating_add(u64::from(value_in_cents(*coin)).saturating_mul(*count)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    // Pennies first, quarters last, with their counts
+    pub fn iter(&self) -> impl Iterator<Item = (Coin, u64)> + '_ {
+        self.counts.iter().map(|(coin, count)| (*coin, *count))
+    }
+}
+
+impl FromIterator<Coin> for Bag {
+    fn from_iter<I: IntoIterator<Item = Coin>>(coins: I) -> Bag {
+        let mut bag = Bag::new();
+        for coin in coins {
+            bag.add(coin);
+        }
+        bag
+    }
+}
+
+// 2 pennies, 1 quarter (Alaska), the same list FromStr reads
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (coin, count)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            let name = if count == 1 { coin.name() } else { coin.plural() };
+            write!(f, "{} {}", count, name)?;
+            if let Coin::Quarter(state) = coin {
+                write!(f, " ({})", state)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Reading coins from text
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCoinError {
+    // nothing between two commas
+    Empty,
+    UnknownCoin(String),
+    BadCount(String),
+    // "quarter" alone, without the state in parentheses
+    MissingState,
+    UnknownState(String),
+}
+
+impl fmt::Display for ParseCoinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseCoinError::Empty => write!(f, "a coin is missing in the list"),
+            ParseCoinError::UnknownCoin(coin) => write!(f, "'{}' is not a coin", coin),
+            ParseCoinError::BadCount(count) => write!(f, "'{}' is not a number of coins", count),
+            ParseCoinError::MissingState => write!(f, "a quarter needs its state, like quarter (Alaska)"),
+            ParseCoinError::UnknownState(state) => write!(f, "'{}' is not a US state", state),
+        }
+    }
+}
+
+impl std::error::Error for ParseCoinError {}
+
+// One coin by name, in any case and singular or plural: penny, Dimes, quarter (Alaska)
+impl FromStr for Coin {
+    type Err = ParseCoinError;
+
+    fn from_str(s: &str) -> Result<Coin, ParseCoinError> {
+        let s = s.trim();
+        let (name, state) = match s.find('(') {
+            Some(open) => {
+                let state = s[open + 1..].strip_suffix(')').ok_or_else(|| ParseCoinError::UnknownCoin(s.to_string()))?;
+                (s[..open].trim_end(), Some(state.trim()))
+            }
+            None => (s, None),
+        };
+
+        let coin = match name.to_ascii_lowercase().as_str() {
+            "" => return Err(ParseCoinError::Empty),
+            "penny" | "pennies" => Coin::Penny,
+            "nickel" | "nickels" => Coin::Nickel,
+            "dime" | "dimes" => Coin::Dime,
+            "quarter" | "quarters" => {
+                let state = state.ok_or(ParseCoinError::MissingState)?;
+                return state
+                    .parse()
+                    .map(Coin::Quarter)
+                    .map_err(|_| ParseCoinError::UnknownState(state.to_string()));
+            }
+            _ => return Err(ParseCoinError::UnknownCoin(s.to_string())),
+        };
+        match state {
+            // only quarters have a state
+            Some(_) => Err(ParseCoinError::UnknownCoin(s.to_string())),
+            None => Ok(coin),
+        }
+    }
+}
+
+// A list of coins separated by commas, each with an optional count in front:
+//   3 quarters (Alaska), 2 dimes, penny
+// An empty text is an empty bag.
+impl FromStr for Bag {
+    type Err = ParseCoinError;
+
+    fn from_str(s: &str) -> Result<Bag, ParseCoinError> {
+        let mut bag = Bag::new();
+        if s.trim().is_empty() {
+            return Ok(bag);
+        }
+        for item in s.split(',') {
+            let item = item.trim();
+            let (count, coin) = match item.split_once(char::is_whitespace) {
+                Some((count, coin)) if count.starts_with(|c: char| c.is_ascii_digit()) => {
+                    let count = count.parse().map_err(|_| ParseCoinError::BadCount(count.to_string()))?;
+                    (count, coin)
+                }
+                _ => (1, item),
+            };
+            bag.add_many(coin.parse()?, count);
+        }
+        Ok(bag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        assert_eq!(value_in_cents(Coin::Penny), 1);
+        assert_eq!(value_in_cents(Coin::Nickel), 5);
+        assert_eq!(value_in_cents(Coin::Dime), 10);
+        assert_eq!(value_in_cents(Coin::Quarter(UsState::Alaska)), 25);
+    }
+
+    #[test]
+    fn makes_us_change() {
+        assert_eq!(make_change(0), Change::default());
+        assert_eq!(make_change(41), Change { quarters: 1, dimes: 1, nickels: 1, pennies: 1 });
+        assert_eq!(make_change(99), Change { quarters: 3, dimes: 2, nickels: 0, pennies: 4 });
+        assert_eq!(make_change(u32::MAX).cents(), u64::from(u32::MAX));
+    }
+
+    #[test]
+    fn greedy_is_optimal_for_us_coins() {
+        for cents in 0..500 {
+            let change = make_change(cents);
+            let optimal = optimal_change(cents, &US_DENOMINATIONS).unwrap();
+            let count: u32 = optimal.iter().map(|(_, count)| count).sum();
+            assert_eq!(change.cents(), u64::from(cents));
+            assert_eq!(change.coin_count(), u64::from(count), "{} cents", cents);
+            assert_eq!(greedy_change(cents, &US_DENOMINATIONS), Some(optimal));
+        }
+    }
+
+    #[test]
+    fn greedy_and_optimal_differ() {
+        assert_eq!(greedy_change(6, &[1, 3, 4]), Some(vec![(4, 1), (1, 2)]));
+        assert_eq!(optimal_change(6, &[4, 3, 1]), Some(vec![(3, 2)]));
+
+        assert_eq!(greedy_change(9, &[5, 3]), None);
+        assert_eq!(optimal_change(9, &[5, 3]), Some(vec![(3, 3)]));
+
+        assert_eq!(greedy_change(3, &[10, 5]), None);
+        assert_eq!(optimal_change(3, &[10, 5]), None);
+        assert_eq!(optimal_change(7, &[]), None);
+        assert_eq!(optimal_change(0, &[]), Some(Vec::new()));
+        // duplicates and zeros don't matter
+        assert_eq!(optimal_change(20, &[10, 0, 10, 1]), Some(vec![(10, 2)]));
+        // counted in units of 5
+        assert_eq!(optimal_change(65, &[25, 10]), Some(vec![(25, 1), (10, 4)]));
+        assert_eq!(optimal_change(64, &[25, 10]), None);
+    }
+
+    #[test]
+    fn optimal_change_for_huge_amounts() {
+        assert_eq!(optimal_change(u32::MAX, &US_DENOMINATIONS), None);
+        assert_eq!(optimal_change(MAX_OPTIMAL_AMOUNT + 1, &US_DENOMINATIONS), None);
+        assert_eq!(optimal_change(MAX_OPTIMAL_AMOUNT, &US_DENOMINATIONS), Some(vec![(25, MAX_OPTIMAL_AMOUNT / 25)]));
+        // a million units of a thousand
+        let amount = MAX_OPTIMAL_AMOUNT * 1000;
+        assert_eq!(optimal_change(amount, &[5000, 2000, 1000]), Some(vec![(5000, amount / 5000)]));
+    }
+
+    #[test]
+    fn counts_a_bag() {
+        let mut bag: Bag = vec![Coin::Penny, Coin::Quarter(UsState::Alaska), Coin::Penny].into_iter().collect();
+        bag.add_many(Coin::Dime, 3);
+        bag.add_many(Coin::Nickel, 0);
+
+        assert_eq!(bag.count(Coin::Penny), 2);
+        assert_eq!(bag.count(Coin::Nickel), 0);
+        assert_eq!(bag.coin_count(), 6);
+        assert_eq!(bag.total_cents(), 57);
+        assert_eq!(bag.to_string(), "2 pennies, 3 dimes, 1 quarter (Alaska)");
+    }
+
+    #[test]
+    fn parses_coins() {
+        assert_eq!("Penny".parse(), Ok(Coin::Penny));
+        assert_eq!(" dimes ".parse(), Ok(Coin::Dime));
+        assert_eq!("quarter (alabama)".parse(), Ok(Coin::Quarter(UsState::Alabama)));
+        assert_eq!("quarter(Alaska)".parse(), Ok(Coin::Quarter(UsState::Alaska)));
+
+        assert_eq!("quarter".parse::<Coin>(), Err(ParseCoinError::MissingState));
+        assert_eq!("quarter (Guam)".parse::<Coin>(), Err(ParseCoinError::UnknownState(String::from("Guam"))));
+        assert_eq!("quarter (NY)".parse(), Ok(Coin::Quarter(UsState::NewYork)));
+        assert_eq!("dime (Alaska)".parse::<Coin>(), Err(ParseCoinError::UnknownCoin(String::from("dime (Alaska)"))));
+        assert_eq!("euro".parse::<Coin>(), Err(ParseCoinError::UnknownCoin(String::from("euro"))));
+    }
+
+    #[test]
+    fn parses_bags() {
+        let bag: Bag = "3 quarters (Alaska), 2 dimes, penny, 1 penny".parse().unwrap();
+        assert_eq!(bag.count(Coin::Quarter(UsState::Alaska)), 3);
+        assert_eq!(bag.count(Coin::Dime), 2);
+        assert_eq!(bag.count(Coin::Penny), 2);
+        assert_eq!(bag.total_cents(), 97);
+        assert_eq!(bag.to_string().parse(), Ok(bag));
+
+        assert_eq!("".parse(), Ok(Bag::new()));
+        assert_eq!("dime,,dime".parse::<Bag>(), Err(ParseCoinError::Empty));
+        assert_eq!("3x dimes".parse::<Bag>(), Err(ParseCoinError::BadCount(String::from("3x"))));
+        assert_eq!("2 euros".parse::<Bag>(), Err(ParseCoinError::UnknownCoin(String::from("euros"))));
+    }
+}
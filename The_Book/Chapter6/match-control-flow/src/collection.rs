@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::coin::Coin;
+use crate::state::{UsState, FIRST_RELEASE_YEAR, LAST_RELEASE_YEAR};
+
+pub mod cli;
+
+// Which state quarters someone owns, with how many of each so duplicates can be traded.
+// The file stores one state per line: abbreviation, a tab, then the count.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Collection {
+    counts: BTreeMap<UsState, u32>,
+    // how many lines of the loaded file weren't a state and a count, save leaves them out
+    skipped: usize,
+}
+
+// What adding a coin did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Added {
+    // the first quarter of this state
+    New(UsState),
+    // there was one already, this is how many there are now
+    Duplicate(UsState, u32),
+    // pennies, nickels and dimes don't go into the collection
+    NotAQuarter,
+}
+
+impl Collection {
+    pub fn new() -> Collection {
+        Collection::default()
+    }
+
+    pub fn add(&mut self, coin: Coin) -> Added {
+        match coin {
+            Coin::Quarter(state) => {
+                let count = self.counts.entry(state).or_insert(0);
+                *count = count.saturating_add(1);
+                match *count {
+                    1 => Added::New(state),
+                    count => Added::Duplicate(state, count),
+                }
+            }
+            _ => Added::NotAQuarter,
+        }
+    }
+
+    // Takes out one quarter of the state, false when there was none
+    pub fn remove(&mut self, state: UsState) -> bool {
+        match self.counts.get_mut(&state) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                true
+            }
+            Some(_) => {
+                self.counts.remove(&state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn count(&self, state: UsState) -> u32 {
+        self.counts.get(&state).copied().unwrap_or(0)
+    }
+
+    pub fn contains(&self, state: UsState) -> bool {
+        self.counts.contains_key(&state)
+    }
+
+    pub fn owned(&self) -> Vec<UsState> {
+        self.counts.keys().copied().collect()
+    }
+
+    pub fn missing(&self) -> Vec<UsState> {
+        UsState::ALL.iter().copied().filter(|state| !self.contains(*state)).collect()
+    }
+
+    // The states there is more than one quarter of, with how many are spare
+    pub fn duplicates(&self) -> Vec<(UsState, u32)> {
+        self.counts.iter().filter(|(_, count)| **count > 1).map(|(state, count)| (*state, count - 1)).collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.counts.len() == UsState::ALL.len()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    // No file yet means nothing collected yet. A state that shows up on several lines has
+    // its counts added up, lines that make no sense are counted in skipped.
+    pub fn load(path: &Path) -> io::Result<Collection> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Collection::default()),
+            Err(e) => return Err(e),
+        };
+
+        let contents = String::from_utf8_lossy(&bytes);
+        let mut collection = Collection::default();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            match Collection::from_line(line) {
+                Some((state, count)) => {
+                    let total = collection.counts.entry(state).or_insert(0);
+                    *total = total.saturating_add(count);
+                }
+                None => collection.skipped += 1,
+            }
+        }
+
+        Ok(collection)
+    }
+
+    fn from_line(line: &str) -> Option<(UsState, u32)> {
+        let mut fields = line.split('\t');
+        let state = fields.next()?.parse().ok()?;
+        let count = fields.next()?.trim().parse().ok()?;
+        if fields.next().is_some() || count == 0 {
+            return None;
+        }
+        Some((state, count))
+    }
+
+    // The counts are written to coins.txt.tmp when saving to coins.txt, then renamed over it
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut file = fs::File::create(&tmp)?;
+            for (state, count) in &self.counts {
+                writeln!(file, "{}\t{}", state.abbreviation(), count)?;
+            }
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, path)
+    }
+
+    // How far along the collection is, year by year, with what is missing and what can be traded
+    pub fn write_report<W: Write>(&self, mut output: W) -> io::Result<()> {
+        let missing = self.missing();
+        writeln!(output, "{} of {} state quarters", self.counts.len(), UsState::ALL.len())?;
+        for year in FIRST_RELEASE_YEAR..=LAST_RELEASE_YEAR {
+            let states = UsState::released_in(year);
+            let owned = states.iter().filter(|state| self.contains(**state)).count();
+            write!(output, "{}  {}/{}", year, owned, states.len())?;
+            let missing: Vec<&str> = states.iter().filter(|state| !self.contains(**state)).map(|state| state.name()).collect();
+            if !missing.is_empty() {
+                write!(output, "  missing {}", missing.join(", "))?;
+            }
+            writeln!(output)?;
+        }
+
+        if missing.is_empty() {
+            writeln!(output, "The collection is complete!")?;
+        }
+        let duplicates = self.duplicates();
+        if !duplicates.is_empty() {
+            let spare: Vec<String> = duplicates.iter().map(|(state, count)| format!("{} x{}", state, count)).collect();
+            writeln!(output, "to trade: {}", spare.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+    use std::process;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("match-control-flow-{}-{}", process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn adds_and_removes_quarters() {
+        let mut collection = Collection::new();
+        assert_eq!(collection.add(Coin::Quarter(UsState::Alaska)), Added::New(UsState::Alaska));
+        assert_eq!(collection.add(Coin::Quarter(UsState::Alaska)), Added::Duplicate(UsState::Alaska, 2));
+        assert_eq!(collection.add(Coin::Dime), Added::NotAQuarter);
+        assert_eq!(collection.add(Coin::Quarter(UsState::Ohio)), Added::New(UsState::Ohio));
+
+        assert_eq!(collection.owned(), [UsState::Alaska, UsState::Ohio]);
+        assert_eq!(collection.duplicates(), [(UsState::Alaska, 1)]);
+        assert_eq!(collection.missing().len(), 48);
+        assert!(!collection.missing().contains(&UsState::Ohio));
+
+        assert!(collection.remove(UsState::Alaska));
+        assert_eq!(collection.count(UsState::Alaska), 1);
+        assert!(collection.remove(UsState::Alaska));
+        assert!(!collection.contains(UsState::Alaska));
+        assert!(!collection.remove(UsState::Alaska));
+    }
+
+    #[test]
+    fn completes() {
+        let mut collection = Collection::new();
+        for state in UsState::ALL.iter() {
+            assert!(!collection.is_complete());
+            collection.add(Coin::Quarter(*state));
+        }
+        assert!(collection.is_complete());
+        assert!(collection.missing().is_empty());
+    }
+
+    #[test]
+    fn survives_a_round_trip() {
+        let path = temp_file("round-trip");
+        assert_eq!(Collection::load(&path).unwrap(), Collection::new());
+
+        let mut collection = Collection::new();
+        collection.add(Coin::Quarter(UsState::NewYork));
+        collection.add(Coin::Quarter(UsState::NewYork));
+        collection.add(Coin::Quarter(UsState::Hawaii));
+        collection.save(&path).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "HI\t1\nNY\t2\n");
+        assert_eq!(Collection::load(&path).unwrap(), collection);
+        fs::remove_file(&path).unwrap();
+
+        // coins.txt saves through coins.txt.tmp, not the coins.tmp that coins.csv would share
+        let txt = temp_file("coins.txt");
+        let tmp = txt.with_file_name(format!("{}.tmp", txt.file_name().unwrap().to_str().unwrap()));
+        let shared = txt.with_extension("tmp");
+        fs::write(&shared, "someone else's").unwrap();
+        collection.save(&txt).unwrap();
+        assert_eq!(Collection::load(&txt).unwrap(), collection);
+        assert!(!tmp.exists());
+        assert_eq!(fs::read_to_string(&shared).unwrap(), "someone else's");
+        fs::remove_file(&txt).unwrap();
+        fs::remove_file(&shared).unwrap();
+    }
+
+    #[test]
+    fn skips_broken_lines() {
+        let path = temp_file("broken");
+        fs::write(&path, "AK\t1\nXX\t2\nOH\ttwo\nOH\t0\nTX\t1\t1\n\nak\t2\n").unwrap();
+
+        let collection = Collection::load(&path).unwrap();
+        assert_eq!(collection.skipped(), 4);
+        assert_eq!(collection.owned(), [UsState::Alaska]);
+        assert_eq!(collection.count(UsState::Alaska), 3);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reports_by_year() {
+        let mut collection = Collection::new();
+        for state in UsState::released_in(1999) {
+            collection.add(Coin::Quarter(state));
+        }
+        collection.add(Coin::Quarter(UsState::Alaska));
+        collection.add(Coin::Quarter(UsState::Alaska));
+
+        let mut output = Vec::new();
+        collection.write_report(&mut output).unwrap();
+        let report = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "6 of 50 state quarters");
+        assert_eq!(lines[1], "1999  5/5");
+        assert_eq!(lines[10], "2008  1/5  missing Arizona, Hawaii, New Mexico, Oklahoma");
+        assert_eq!(lines[11], "to trade: Alaska x1");
+        assert_eq!(lines.len(), 12);
+    }
+}
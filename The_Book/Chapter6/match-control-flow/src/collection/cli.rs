@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+use super::{Added, Collection};
+use crate::coin::Coin;
+use crate::state::UsState;
+
+pub const USAGE: &str = "\
+usage: quarters <file> add <state>...       (a name like \"New York\" or an abbreviation like NY)
+       quarters <file> remove <state>...
+       quarters <file> missing
+       quarters <file> report";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Add(Vec<UsState>),
+    Remove(Vec<UsState>),
+    Missing,
+    Report,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub path: String,
+    pub command: Command,
+}
+
+fn states(args: &[String]) -> Result<Vec<UsState>, &'static str> {
+    if args.is_empty() {
+        return Err("at least one state is needed");
+    }
+    args.iter()
+        .map(|arg| arg.parse())
+        .collect::<Result<Vec<UsState>, _>>()
+        .map_err(|_| "the states have to be names like \"New York\" or abbreviations like NY")
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, &'static str> {
+        if args.len() < 3 {
+            return Err("the collection file and a command are needed");
+        }
+        let path = args[1].clone();
+        let rest = &args[3..];
+
+        let command = match args[2].as_str() {
+            "add" => Command::Add(states(rest)?),
+            "remove" => Command::Remove(states(rest)?),
+            "missing" | "report" if !rest.is_empty() => return Err("too many arguments"),
+            "missing" => Command::Missing,
+            "report" => Command::Report,
+            _ => return Err("unknown command"),
+        };
+        Ok(Config { path, command })
+    }
+}
+
+pub fn run<W: Write>(config: Config, mut output: W) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(&config.path);
+    let mut collection = Collection::load(path)?;
+    if collection.skipped() > 0 {
+        writeln!(output, "skipped {} broken lines of {}", collection.skipped(), config.path)?;
+    }
+
+    match config.command {
+        Command::Add(states) => {
+            for state in states {
+                match collection.add(Coin::Quarter(state)) {
+                    Added::New(state) => writeln!(output, "{}: new!", state)?,
+                    Added::Duplicate(state, count) => writeln!(output, "{}: {} now", state, count)?,
+                    Added::NotAQuarter => unreachable!("only quarters are added"),
+                }
+            }
+            collection.save(path)?;
+        }
+        Command::Remove(states) => {
+            for state in states {
+                if collection.remove(state) {
+                    writeln!(output, "{}: {} left", state, collection.count(state))?;
+                } else {
+                    writeln!(output, "{}: there was none", state)?;
+                }
+            }
+            collection.save(path)?;
+        }
+        Command::Missing => {
+            for state in collection.missing() {
+                writeln!(output, "{} ({}, {})", state, state.abbreviation(), state.release_year())?;
+            }
+        }
+        Command::Report => collection.write_report(&mut output)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    fn args(path: &str, line: &[&str]) -> Vec<String> {
+        ["quarters", path].iter().chain(line.iter()).map(|arg| arg.to_string()).collect()
+    }
+
+    fn output(path: &str, line: &[&str]) -> String {
+        let mut output = Vec::new();
+        run(Config::new(&args(path, line)).unwrap(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            Config::new(&args("q.txt", &["add", "New York", "ak"])).unwrap().command,
+            Command::Add(vec![UsState::NewYork, UsState::Alaska])
+        );
+        assert_eq!(Config::new(&args("q.txt", &["report"])).unwrap().command, Command::Report);
+
+        assert_eq!(Config::new(&args("q.txt", &[])), Err("the collection file and a command are needed"));
+        assert_eq!(Config::new(&args("q.txt", &["add"])), Err("at least one state is needed"));
+        assert!(Config::new(&args("q.txt", &["add", "Guam"])).is_err());
+        assert_eq!(Config::new(&args("q.txt", &["missing", "AK"])), Err("too many arguments"));
+        assert_eq!(Config::new(&args("q.txt", &["sell"])), Err("unknown command"));
+    }
+
+    #[test]
+    fn keeps_the_collection_in_the_file() {
+        let path = env::temp_dir().join(format!("match-control-flow-{}-cli", process::id()));
+        let _ = fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+
+        assert_eq!(output(path, &["add", "AK", "alaska", "Ohio"]), "Alaska: new!\nAlaska: 2 now\nOhio: new!\n");
+        assert_eq!(output(path, &["remove", "OH", "TX"]), "Ohio: 0 left\nTexas: there was none\n");
+        assert!(output(path, &["report"]).starts_with("1 of 50 state quarters\n"));
+
+        let missing = output(path, &["missing"]);
+        assert_eq!(missing.lines().count(), 49);
+        assert!(missing.starts_with("Alabama (AL, 2003)\nArizona (AZ, 2008)\n"));
+        fs::remove_file(path).unwrap();
+    }
+}
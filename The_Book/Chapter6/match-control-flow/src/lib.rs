@@ -0,0 +1,4 @@
+pub mod coin;
+pub mod collection;
+pub mod money;
+pub mod state;
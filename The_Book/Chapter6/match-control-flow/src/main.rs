@@ -1,3 +1,14 @@
+// An attribute to hide warnings for unused code.
+#![allow(dead_code)]
+#![allow(unused_variables)]
+// the long forms are what the chapter shows, clippy would shorten them
+#![allow(clippy::manual_map)]
+#![allow(clippy::single_match)]
+
+use match_control_flow::coin::{self, Bag};
+use match_control_flow::collection::Collection;
+use match_control_flow::money::{Currency, Money, RateTable};
+
 /* Match is a control flow operator that allows you to compare a value against a
 series of patterns and then execute code based on which pattern matches.
 
@@ -21,7 +32,8 @@ fn value_in_cents(coin: Coin) -> u8 {
     }
 }
 
-fn value_in_cents(coin: Coin) -> u8 {
+// the same with a block in an arm (called value_in_cents1 here, each function needs its own name)
+fn value_in_cents1(coin: Coin) -> u8 {
     match coin {
         Coin::Penny => {
             println!("Lucky Penny");
@@ -42,26 +54,26 @@ enum UsState {
     // --skip--
 }
 
-enum Coin {
+// Coin1 here, Coin is the enum above. The library has this one, with all the states (src/coin.rs)
+enum Coin1 {
     Penny,
     Nickel,
     Dime,
     Quarter(UsState),
 }
 
-fn value_in_cents(coin: Coin) -> u8 {
+fn value_in_cents2(coin: Coin1) -> u8 {
     match coin {
-        Coin::Penny => 1,
-        Coin::Nickel => 5,
-        Coin::Dime => 10,
-        Coin::Quarter(state) => {
+        Coin1::Penny => 1,
+        Coin1::Nickel => 5,
+        Coin1::Dime => 10,
+        Coin1::Quarter(state) => {
             println!("State quarter from {:?}!", state);
             25
+        }
     }
 }
 
-value_in_cents(Coin::Quarter(UsState::Alaska))
-
 // Matching with Option<T>
 
 fn plus_one(x:Option<i32>) -> Option<i32> {
@@ -71,45 +83,77 @@ fn plus_one(x:Option<i32>) -> Option<i32> {
     }
 }
 
-let five = Some(5);
-let six = plus_one(five);
-let none = plus_one(None);
+fn main() {
+    value_in_cents2(Coin1::Quarter(UsState::Alaska));
 
-let some_u8_value = 0u8;
-match some_u8_value {
-    1 => println!("one"),
-    3 => println!("three");
-    _ => (),
-}
+    let five = Some(5);
+    let six = plus_one(five);
+    let none = plus_one(None);
 
-// concise control flow with if let
-let some_u8_value = Some(0u8);
-match some_u8_value {
-    Some(3) => println!("three"),
-    _ => (),
-}
-// ==
-let some_u8_value = Some(0u8);
-if let Some(3) = some_u8_value {
-    println!("three");
-}
+    let some_u8_value = 0u8;
+    match some_u8_value {
+        1 => println!("one"),
+        3 => println!("three"),
+        _ => (),
+    }
 
-let mut count = 0;
-match coin {
-    Coin::Quarter(state) => println!("State quarter from {:?}!", state),
-    // _ placeholder: matches any value
-    _ => count += 1,
-}
+    // concise control flow with if let
+    let some_u8_value = Some(0u8);
+    match some_u8_value {
+        Some(3) => println!("three"),
+        _ => (),
+    }
+    // ==
+    let some_u8_value = Some(0u8);
+    if let Some(3) = some_u8_value {
+        println!("three");
+    }
 
-let mut count = 0;
-if let Coin::Quarter(state) = coin {
-    println!("State quarter from {:?}!", state);
-} else { // same as the block of code that would go with the _ case in the match expression
-    count += 1;
-}
+    let coin = Coin1::Quarter(UsState::Alabama);
+    let mut count = 0;
+    match coin {
+        Coin1::Quarter(state) => println!("State quarter from {:?}!", state),
+        // _ placeholder: matches any value
+        _ => count += 1,
+    }
+    println!("{} coins that are not quarters", count);
+
+    let coin = Coin1::Penny;
+    let mut count = 0;
+    if let Coin1::Quarter(state) = coin {
+        println!("State quarter from {:?}!", state);
+    } else { // same as the block of code that would go with the _ case in the match expression
+        count += 1;
+    }
+    println!("{} coins that are not quarters", count);
 
-fn main() {
     println!("Hello, world!");
+
+    // the library counts a bag of coins and makes change for it
+    let bag: Bag = "3 quarters (Alaska), 2 dimes, penny".parse().expect("a list of coins");
+    println!("{} make {} cents", bag, bag.total_cents());
+    println!("{:?}", coin::make_change(99));
+    println!("6 with 4, 3 and 1: greedy {:?}, optimal {:?}", coin::greedy_change(6, &[4, 3, 1]), coin::optimal_change(6, &[4, 3, 1]));
+
+    // the quarters of the bag start a collection
+    let mut collection = Collection::new();
+    for (coin, count) in bag.iter() {
+        for _ in 0..count {
+            println!("{:?}", collection.add(coin));
+        }
+    }
+    println!("{} states missing", collection.missing().len());
+
+    // the bag in euros, with a rate the user gives
+    let rates: RateTable = "USD EUR 0.92".parse().expect("a rate table");
+    let dollars = bag.total().expect("a bag of coins");
+    match rates.convert(dollars, Currency::Eur) {
+        Ok(euros) => println!("{} is {}", dollars, euros),
+        Err(e) => println!("{}", e),
+    }
+    if let Err(e) = dollars.checked_add(Money::new(Currency::Gbp, 100)) {
+        println!("{}", e);
+    }
 }
 
 /*
@@ -0,0 +1,603 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::coin::{self, Bag, Coin};
+
+// Coin only knows US cents and value_in_cents fits them in a u8. Money is an amount in the
+// minor units of a currency (cents, pence), kept as an integer so that 0.10 + 0.20 is
+// exactly 0.30, and tagged with its currency so dollars never get added to euros.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Currency {
+    Usd,
+    Eur,
+    Gbp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Form {
+    Coin,
+    Note,
+}
+
+// A coin or a note of a currency: 25¢ coin, €5 note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Denomination {
+    pub currency: Currency,
+    pub minor_units: u32,
+    pub form: Form,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    currency: Currency,
+    minor_units: i64,
+}
+
+// A conversion rate as an exact fraction, 0.8571 is 8571/10000
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rate {
+    numerator: u128,
+    denominator: u128,
+}
+
+// Rates between currencies, as the user gives them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateTable {
+    rates: HashMap<(Currency, Currency), Rate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoneyError {
+    CurrencyMismatch { expected: Currency, found: Currency },
+    // beyond what an i64 of minor units holds
+    Overflow,
+    // split into zero parts
+    NoParts,
+    NoRate { from: Currency, to: Currency },
+    UnknownCurrency(String),
+    // not a number, or more decimals than the currency has minor units
+    InvalidAmount(String),
+    InvalidRate(String),
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoneyError::CurrencyMismatch { expected, found } => {
+                write!(f, "can't mix {} with {}, convert it first", found, expected)
+            }
+            MoneyError::Overflow => write!(f, "the amount is too large"),
+            MoneyError::NoParts => write!(f, "money can't be split into zero parts"),
+            MoneyError::NoRate { from, to } => write!(f, "there is no rate from {} to {}", from, to),
+            MoneyError::UnknownCurrency(currency) => write!(f, "'{}' is not a currency, try USD, EUR or GBP", currency),
+            MoneyError::InvalidAmount(amount) => write!(f, "'{}' is not an amount of money", amount),
+            MoneyError::InvalidRate(rate) => write!(f, "'{}' is not a rate, like USD EUR 0.92", rate),
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+impl Currency {
+    pub const ALL: [Currency; 3] = [Currency::Usd, Currency::Eur, Currency::Gbp];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::Usd => "$",
+            Currency::Eur => "€",
+            Currency::Gbp => "£",
+        }
+    }
+
+    // how many decimals the minor unit is, 2 for all three
+    pub fn decimals(&self) -> u32 {
+        match self {
+            Currency::Usd | Currency::Eur | Currency::Gbp => 2,
+        }
+    }
+
+    fn minor_per_major(&self) -> i64 {
+        10i64.pow(self.decimals())
+    }
+
+    // The coins and notes in circulation, in minor units
+    fn coin_and_note_values(&self) -> (&'static [u32], &'static [u32]) {
+        match self {
+            Currency::Usd => (&[1, 5, 10, 25], &[100, 200, 500, 1000, 2000, 5000, 10000]),
+            Currency::Eur => (&[1, 2, 5, 10, 20, 50, 100, 200], &[500, 1000, 2000, 5000, 10000, 20000, 50000]),
+            Currency::Gbp => (&[1, 2, 5, 10, 20, 50, 100, 200], &[500, 1000, 2000, 5000]),
+        }
+    }
+
+    // Coins then notes, smallest first
+    pub fn denominations(&self) -> Vec<Denomination> {
+        let (coins, notes) = self.coin_and_note_values();
+        let coins = coins.iter().map(|value| (value, Form::Coin));
+        let notes = notes.iter().map(|value| (value, Form::Note));
+        coins
+            .chain(notes)
+            .map(|(value, form)| Denomination { currency: *self, minor_units: *value, form })
+            .collect()
+    }
+
+    // The fewest coins and notes that make the amount, largest first. All three currencies
+    // are systems where taking the largest that fits is the optimal way. None for negative
+    // amounts and amounts in another currency.
+    pub fn break_into(&self, amount: Money) -> Option<Vec<(Denomination, u64)>> {
+        if amount.currency != *self || amount.minor_units < 0 {
+            return None;
+        }
+        let mut rest = amount.minor_units as u64;
+        let mut parts = Vec::new();
+        for denomination in self.denominations().into_iter().rev() {
+            let value = u64::from(denomination.minor_units);
+            if rest >= value {
+                parts.push((denomination, rest / value));
+                rest %= value;
+            }
+        }
+        Some(parts)
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+// the ISO code in any case or the symbol
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Currency, MoneyError> {
+        let s = s.trim();
+        Currency::ALL
+            .iter()
+            .find(|currency| currency.code().eq_ignore_ascii_case(s) || currency.symbol() == s)
+            .copied()
+            .ok_or_else(|| MoneyError::UnknownCurrency(s.to_string()))
+    }
+}
+
+impl Denomination {
+    pub fn value(&self) -> Money {
+        Money::new(self.currency, i64::from(self.minor_units))
+    }
+}
+
+// $5 note, 25¢ coin, 50p coin
+impl fmt::Display for Denomination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let form = match self.form {
+            Form::Coin => "coin",
+            Form::Note => "note",
+        };
+        let minor_per_major = self.currency.minor_per_major() as u32;
+        if self.minor_units < minor_per_major {
+            let sign = match self.currency {
+                Currency::Usd => "¢",
+                Currency::Eur => "c",
+                Currency::Gbp => "p",
+            };
+            write!(f, "{}{} {}", self.minor_units, sign, form)
+        } else {
+            write!(f, "{}{} {}", self.currency.symbol(), self.minor_units / minor_per_major, form)
+        }
+    }
+}
+
+// a US coin is just a denomination of the dollar, whichever state is on its back
+impl From<Coin> for Denomination {
+    fn from(coin: Coin) -> Denomination {
+        Denomination { currency: Currency::Usd, minor_units: u32::from(coin::value_in_cents(coin)), form: Form::Coin }
+    }
+}
+
+impl Coin {
+    pub fn value(&self) -> Money {
+        Denomination::from(*self).value()
+    }
+}
+
+impl Bag {
+    // total_cents as dollars, Err only for a bag worth more than an i64 of cents
+    pub fn total(&self) -> Result<Money, MoneyError> {
+        let cents = i64::try_from(self.total_cents()).map_err(|_| MoneyError::Overflow)?;
+        Ok(Money::new(Currency::Usd, cents))
+    }
+}
+
+impl Money {
+    pub fn new(currency: Currency, minor_units: i64) -> Money {
+        Money { currency, minor_units }
+    }
+
+    pub fn zero(currency: Currency) -> Money {
+        Money::new(currency, 0)
+    }
+
+    // whole dollars, euros or pounds
+    pub fn from_major(currency: Currency, major: i64) -> Result<Money, MoneyError> {
+        let minor_units = major.checked_mul(currency.minor_per_major()).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(currency, minor_units))
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minor_units == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.minor_units < 0
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency == other.currency {
+            Ok(())
+        } else {
+            Err(MoneyError::CurrencyMismatch { expected: self.currency, found: other.currency })
+        }
+    }
+
+    // There is no `+` for Money: it would have to panic on different currencies, so
+    // adding gives a Result instead, like the checked_ methods of the integers
+    pub fn checked_add(self, other: Money) -> Result<Money, MoneyError> {
+        self.same_currency(&other)?;
+        let minor_units = self.minor_units.checked_add(other.minor_units).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(self.currency, minor_units))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, MoneyError> {
+        self.same_currency(&other)?;
+        let minor_units = self.minor_units.checked_sub(other.minor_units).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(self.currency, minor_units))
+    }
+
+    pub fn checked_mul(self, factor: i64) -> Result<Money, MoneyError> {
+        let minor_units = self.minor_units.checked_mul(factor).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(self.currency, minor_units))
+    }
+
+    pub fn checked_neg(self) -> Result<Money, MoneyError> {
+        let minor_units = self.minor_units.checked_neg().ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(self.currency, minor_units))
+    }
+
+    // Divides into parts that differ by at most one minor unit and add up to exactly the
+    // amount: $10 in three is $3.34, $3.33 and $3.33, the first parts get the extra cents.
+    pub fn split(self, parts: u32) -> Result<Vec<Money>, MoneyError> {
+        if parts == 0 {
+            return Err(MoneyError::NoParts);
+        }
+        let parts = i64::from(parts);
+        let share = self.minor_units.div_euclid(parts);
+        let extra = self.minor_units.rem_euclid(parts);
+        Ok((0..parts)
+            .map(|i| Money::new(self.currency, if i < extra { share + 1 } else { share }))
+            .collect())
+    }
+
+    // Adds up amounts that all have to be in `currency`
+    pub fn sum<I: IntoIterator<Item = Money>>(currency: Currency, amounts: I) -> Result<Money, MoneyError> {
+        amounts.into_iter().try_fold(Money::zero(currency), Money::checked_add)
+    }
+}
+
+// Amounts of different currencies have no order
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Money) -> Option<Ordering> {
+        if self.currency == other.currency {
+            Some(self.minor_units.cmp(&other.minor_units))
+        } else {
+            None
+        }
+    }
+}
+
+// $12.34, -€0.50
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.minor_units < 0 { "-" } else { "" };
+        // unsigned_abs so that i64::MIN prints too
+        let amount = self.minor_units.unsigned_abs();
+        let minor_per_major = self.currency.minor_per_major() as u64;
+        write!(
+            f,
+            "{}{}{}.{:0width$}",
+            sign,
+            self.currency.symbol(),
+            amount / minor_per_major,
+            amount % minor_per_major,
+            width = self.currency.decimals() as usize
+        )
+    }
+}
+
+// What Display writes, or the code instead of the symbol in front or behind:
+// $12.34, -€0.5, USD 12, 12.34 GBP
+impl FromStr for Money {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Money, MoneyError> {
+        let invalid = || MoneyError::InvalidAmount(s.trim().to_string());
+        let text = s.trim();
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, text),
+        };
+
+        // split the currency from the number, whichever side it is on
+        let number_start = text.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (currency, number) = if number_start > 0 {
+            (&text[..number_start], &text[number_start..])
+        } else {
+            let number_end = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+            (&text[number_end..], &text[..number_end])
+        };
+        let currency: Currency = currency.parse()?;
+        let number = number.trim();
+
+        let (major, minor) = match number.split_once('.') {
+            Some((major, minor)) => (major, minor),
+            None => (number, ""),
+        };
+        let decimals = currency.decimals() as usize;
+        let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        if !digits(major) || (!minor.is_empty() && !digits(minor)) || minor.len() > decimals {
+            return Err(invalid());
+        }
+
+        // 0.5 is 50 cents
+        let minor = format!("{:0<width$}", minor, width = decimals);
+        let major: i64 = major.parse().map_err(|_| MoneyError::Overflow)?;
+        let minor: i64 = if decimals == 0 { 0 } else { minor.parse().map_err(|_| invalid())? };
+        let money = Money::from_major(currency, major)?.checked_add(Money::new(currency, minor))?;
+        if negative {
+            money.checked_neg()
+        } else {
+            Ok(money)
+        }
+    }
+}
+
+impl Rate {
+    pub const ONE: Rate = Rate { numerator: 1, denominator: 1 };
+
+    fn inverse(&self) -> Rate {
+        Rate { numerator: self.denominator, denominator: self.numerator }
+    }
+
+    // rounds half away from zero, the way a bank counter would
+    fn apply(&self, minor_units: i64) -> Option<i64> {
+        let amount = i128::from(minor_units).unsigned_abs().checked_mul(self.numerator)?;
+        let quotient = amount / self.denominator;
+        let remainder = amount % self.denominator;
+        let rounded = if remainder * 2 >= self.denominator { quotient + 1 } else { quotient };
+        let rounded = i64::try_from(rounded).ok()?;
+        Some(if minor_units < 0 { -rounded } else { rounded })
+    }
+}
+
+// A positive decimal number, 0.92 or 1.0845 or 150
+impl FromStr for Rate {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Rate, MoneyError> {
+        let invalid = || MoneyError::InvalidRate(s.trim().to_string());
+        let s = s.trim();
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        // 30 digits keep every calculation inside an u128
+        if whole.is_empty() || whole.len() + fraction.len() > 30 {
+            return Err(invalid());
+        }
+        if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let numerator: u128 = format!("{}{}", whole, fraction).parse().map_err(|_| invalid())?;
+        let denominator = 10u128.pow(fraction.len() as u32);
+        if numerator == 0 {
+            return Err(invalid());
+        }
+        Ok(Rate { numerator, denominator })
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+impl RateTable {
+    pub fn new() -> RateTable {
+        RateTable::default()
+    }
+
+    // 1 `from` is `rate` of `to`. The other way round is the inverse unless it is set too.
+    pub fn set(&mut self, from: Currency, to: Currency, rate: Rate) {
+        self.rates.insert((from, to), rate);
+    }
+
+    pub fn rate(&self, from: Currency, to: Currency) -> Option<Rate> {
+        if from == to {
+            return Some(Rate::ONE);
+        }
+        match self.rates.get(&(from, to)) {
+            Some(rate) => Some(*rate),
+            None => self.rates.get(&(to, from)).map(Rate::inverse),
+        }
+    }
+
+    // Only direct rates are used: with USD->EUR and EUR->GBP there is still no USD->GBP,
+    // going through euros would round twice
+    pub fn convert(&self, money: Money, to: Currency) -> Result<Money, MoneyError> {
+        let rate = self.rate(money.currency, to).ok_or(MoneyError::NoRate { from: money.currency, to })?;
+        // minor units of different currencies could differ, so the rate is scaled by them
+        let scale = Rate {
+            numerator: rate.numerator * to.minor_per_major() as u128,
+            denominator: rate.denominator * money.currency.minor_per_major() as u128,
+        };
+        let minor_units = scale.apply(money.minor_units).ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(to, minor_units))
+    }
+}
+
+// One rate per line, blank lines and # comments skipped:
+//   USD EUR 0.92
+//   GBP EUR 1.17
+impl FromStr for RateTable {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<RateTable, MoneyError> {
+        let mut table = RateTable::new();
+        for line in s.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 3 {
+                return Err(MoneyError::InvalidRate(line.to_string()));
+            }
+            table.set(fields[0].parse()?, fields[1].parse()?, fields[2].parse()?);
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::UsState;
+
+    fn money(s: &str) -> Money {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn coins_are_dollar_denominations() {
+        assert_eq!(Coin::Quarter(UsState::Ohio).value(), Money::new(Currency::Usd, 25));
+        assert_eq!(Denomination::from(Coin::Dime).to_string(), "10¢ coin");
+        assert!(Currency::Usd.denominations().contains(&Denomination::from(Coin::Penny)));
+        let names: Vec<String> = Currency::Gbp.denominations().iter().map(|d| d.to_string()).collect();
+        assert_eq!(names[0], "1p coin");
+        assert_eq!(names[7], "£2 coin");
+        assert_eq!(names[11], "£50 note");
+
+        let bag: Bag = "3 quarters (Ohio), 2 dimes, 5 pennies".parse().unwrap();
+        assert_eq!(bag.total(), Ok(money("$1")));
+    }
+
+    #[test]
+    fn breaks_amounts_into_coins_and_notes() {
+        let parts = Currency::Eur.break_into(money("€38.75")).unwrap();
+        let parts: Vec<String> = parts.iter().map(|(d, count)| format!("{} x{}", d, count)).collect();
+        assert_eq!(parts, ["€20 note x1", "€10 note x1", "€5 note x1", "€2 coin x1", "€1 coin x1", "50c coin x1", "20c coin x1", "5c coin x1"]);
+
+        assert_eq!(Currency::Usd.break_into(Money::zero(Currency::Usd)), Some(Vec::new()));
+        assert_eq!(Currency::Usd.break_into(money("-$1")), None);
+        assert_eq!(Currency::Usd.break_into(money("€1")), None);
+    }
+
+    #[test]
+    fn formats_and_parses() {
+        assert_eq!(Money::new(Currency::Usd, 1234).to_string(), "$12.34");
+        assert_eq!(Money::new(Currency::Eur, -50).to_string(), "-€0.50");
+        assert_eq!(Money::new(Currency::Gbp, i64::MIN).to_string(), "-£92233720368547758.08");
+
+        assert_eq!(money("$12.34"), Money::new(Currency::Usd, 1234));
+        assert_eq!(money("-€0.5"), Money::new(Currency::Eur, -50));
+        assert_eq!(money("USD 12"), Money::new(Currency::Usd, 1200));
+        assert_eq!(money("12.34 gbp"), Money::new(Currency::Gbp, 1234));
+        assert_eq!(money("£3.07").to_string().parse(), Ok(money("£3.07")));
+
+        assert_eq!("$1.234".parse::<Money>(), Err(MoneyError::InvalidAmount(String::from("$1.234"))));
+        assert_eq!("12".parse::<Money>(), Err(MoneyError::UnknownCurrency(String::new())));
+        assert_eq!("¥100".parse::<Money>(), Err(MoneyError::UnknownCurrency(String::from("¥"))));
+        assert!("$1.2.3".parse::<Money>().is_err());
+        assert!("$".parse::<Money>().is_err());
+        assert_eq!("$99999999999999999999".parse::<Money>(), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn arithmetic_is_exact_and_checked() {
+        let sum = money("$0.10").checked_add(money("$0.20")).unwrap();
+        assert_eq!(sum, money("$0.30"));
+        assert_eq!(money("$1").checked_sub(money("$2.50")), Ok(money("-$1.50")));
+        assert_eq!(money("£1.25").checked_mul(3), Ok(money("£3.75")));
+
+        assert_eq!(
+            money("$1").checked_add(money("€1")),
+            Err(MoneyError::CurrencyMismatch { expected: Currency::Usd, found: Currency::Eur })
+        );
+        assert_eq!(Money::new(Currency::Usd, i64::MAX).checked_add(money("$0.01")), Err(MoneyError::Overflow));
+        assert_eq!(Money::new(Currency::Usd, i64::MIN).checked_neg(), Err(MoneyError::Overflow));
+
+        assert!(money("$1") < money("$2"));
+        assert_eq!(money("$1").partial_cmp(&money("€2")), None);
+
+        assert_eq!(Money::sum(Currency::Eur, vec![money("€1"), money("€2.50")]), Ok(money("€3.50")));
+        assert!(Money::sum(Currency::Eur, vec![money("€1"), money("$2.50")]).is_err());
+    }
+
+    #[test]
+    fn splits_without_losing_cents() {
+        assert_eq!(money("$10").split(3), Ok(vec![money("$3.34"), money("$3.33"), money("$3.33")]));
+        assert_eq!(money("-$0.05").split(2), Ok(vec![money("-$0.02"), money("-$0.03")]));
+        assert_eq!(money("$1").split(0), Err(MoneyError::NoParts));
+        let parts = money("€1000.01").split(7).unwrap();
+        assert_eq!(Money::sum(Currency::Eur, parts), Ok(money("€1000.01")));
+    }
+
+    #[test]
+    fn converts_with_a_rate_table() {
+        let table: RateTable = "# from the bank\nUSD EUR 0.92\n\nGBP EUR 1.1650  # this morning\n".parse().unwrap();
+
+        assert_eq!(table.convert(money("$100"), Currency::Eur), Ok(money("€92")));
+        assert_eq!(table.convert(money("$0.01"), Currency::Eur), Ok(money("€0.01")));
+        assert_eq!(table.convert(money("-£10"), Currency::Eur), Ok(money("-€11.65")));
+        // the inverse, 100 / 0.92 = 108.6956...
+        assert_eq!(table.convert(money("€100"), Currency::Usd), Ok(money("$108.70")));
+        assert_eq!(table.convert(money("€5"), Currency::Eur), Ok(money("€5")));
+        assert_eq!(
+            table.convert(money("$1"), Currency::Gbp),
+            Err(MoneyError::NoRate { from: Currency::Usd, to: Currency::Gbp })
+        );
+
+        let mut table = table;
+        table.set(Currency::Eur, Currency::Usd, "1.1".parse().unwrap());
+        assert_eq!(table.convert(money("€100"), Currency::Usd), Ok(money("$110")));
+        assert_eq!(table.convert(Money::new(Currency::Eur, i64::MAX), Currency::Usd), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn parses_rates() {
+        assert_eq!("0.92".parse::<Rate>().unwrap().to_string(), "92/100");
+        assert_eq!("150".parse::<Rate>().unwrap().to_string(), "150/1");
+        for bad in ["", "0", "0.000", "-1", "1e3", ".5", "1.2.3"].iter() {
+            assert_eq!(bad.parse::<Rate>(), Err(MoneyError::InvalidRate(bad.to_string())), "{}", bad);
+        }
+        assert_eq!("USD EUR".parse::<RateTable>(), Err(MoneyError::InvalidRate(String::from("USD EUR"))));
+        assert_eq!("USD JPY 150".parse::<RateTable>(), Err(MoneyError::UnknownCurrency(String::from("JPY"))));
+    }
+}
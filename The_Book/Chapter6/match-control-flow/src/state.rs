@@ -0,0 +1,266 @@
+use std::fmt;
+use std::str::FromStr;
+
+// The state on the back of a state quarter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum UsState {
+    Alabama,
+    Alaska,
+    Arizona,
+    Arkansas,
+    California,
+    Colorado,
+    Connecticut,
+    Delaware,
+    Florida,
+    Georgia,
+    Hawaii,
+    Idaho,
+    Illinois,
+    Indiana,
+    Iowa,
+    Kansas,
+    Kentucky,
+    Louisiana,
+    Maine,
+    Maryland,
+    Massachusetts,
+    Michigan,
+    Minnesota,
+    Mississippi,
+    Missouri,
+    Montana,
+    Nebraska,
+    Nevada,
+    NewHampshire,
+    NewJersey,
+    NewMexico,
+    NewYork,
+    NorthCarolina,
+    NorthDakota,
+    Ohio,
+    Oklahoma,
+    Oregon,
+    Pennsylvania,
+    RhodeIsland,
+    SouthCarolina,
+    SouthDakota,
+    Tennessee,
+    Texas,
+    Utah,
+    Vermont,
+    Virginia,
+    Washington,
+    WestVirginia,
+    Wisconsin,
+    Wyoming,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStateError(pub String);
+
+impl fmt::Display for ParseStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' is not a US state", self.0)
+    }
+}
+
+impl std::error::Error for ParseStateError {}
+
+// The 50 State Quarters came out from 1999 to 2008, five a year in the order the states
+// joined the Union
+pub const FIRST_RELEASE_YEAR: u16 = 1999;
+pub const LAST_RELEASE_YEAR: u16 = 2008;
+
+impl UsState {
+    pub const ALL: [UsState; 50] = [
+        UsState::Alabama,
+        UsState::Alaska,
+        UsState::Arizona,
+        UsState::Arkansas,
+        UsState::California,
+        UsState::Colorado,
+        UsState::Connecticut,
+        UsState::Delaware,
+        UsState::Florida,
+        UsState::Georgia,
+        UsState::Hawaii,
+        UsState::Idaho,
+        UsState::Illinois,
+        UsState::Indiana,
+        UsState::Iowa,
+        UsState::Kansas,
+        UsState::Kentucky,
+        UsState::Louisiana,
+        UsState::Maine,
+        UsState::Maryland,
+        UsState::Massachusetts,
+        UsState::Michigan,
+        UsState::Minnesota,
+        UsState::Mississippi,
+        UsState::Missouri,
+        UsState::Montana,
+        UsState::Nebraska,
+        UsState::Nevada,
+        UsState::NewHampshire,
+        UsState::NewJersey,
+        UsState::NewMexico,
+        UsState::NewYork,
+        UsState::NorthCarolina,
+        UsState::NorthDakota,
+        UsState::Ohio,
+        UsState::Oklahoma,
+        UsState::Oregon,
+        UsState::Pennsylvania,
+        UsState::RhodeIsland,
+        UsState::SouthCarolina,
+        UsState::SouthDakota,
+        UsState::Tennessee,
+        UsState::Texas,
+        UsState::Utah,
+        UsState::Vermont,
+        UsState::Virginia,
+        UsState::Washington,
+        UsState::WestVirginia,
+        UsState::Wisconsin,
+        UsState::Wyoming,
+    ];
+
+    // name, postal abbreviation and the year its quarter was released
+    fn info(&self) -> (&'static str, &'static str, u16) {
+        match self {
+            UsState::Alabama => ("Alabama", "AL", 2003),
+            UsState::Alaska => ("Alaska", "AK", 2008),
+            UsState::Arizona => ("Arizona", "AZ", 2008),
+            UsState::Arkansas => ("Arkansas", "AR", 2003),
+            UsState::California => ("California", "CA", 2005),
+            UsState::Colorado => ("Colorado", "CO", 2006),
+            UsState::Connecticut => ("Connecticut", "CT", 1999),
+            UsState::Delaware => ("Delaware", "DE", 1999),
+            UsState::Florida => ("Florida", "FL", 2004),
+            UsState::Georgia => ("Georgia", "GA", 1999),
+            UsState::Hawaii => ("Hawaii", "HI", 2008),
+            UsState::Idaho => ("Idaho", "ID", 2007),
+            UsState::Illinois => ("Illinois", "IL", 2003),
+            UsState::Indiana => ("Indiana", "IN", 2002),
+            UsState::Iowa => ("Iowa", "IA", 2004),
+            UsState::Kansas => ("Kansas", "KS", 2005),
+            UsState::Kentucky => ("Kentucky", "KY", 2001),
+            UsState::Louisiana => ("Louisiana", "LA", 2002),
+            UsState::Maine => ("Maine", "ME", 2003),
+            UsState::Maryland => ("Maryland", "MD", 2000),
+            UsState::Massachusetts => ("Massachusetts", "MA", 2000),
+            UsState::Michigan => ("Michigan", "MI", 2004),
+            UsState::Minnesota => ("Minnesota", "MN", 2005),
+            UsState::Mississippi => ("Mississippi", "MS", 2002),
+            UsState::Missouri => ("Missouri", "MO", 2003),
+            UsState::Montana => ("Montana", "MT", 2007),
+            UsState::Nebraska => ("Nebraska", "NE", 2006),
+            UsState::Nevada => ("Nevada", "NV", 2006),
+            UsState::NewHampshire => ("New Hampshire", "NH", 2000),
+            UsState::NewJersey => ("New Jersey", "NJ", 1999),
+            UsState::NewMexico => ("New Mexico", "NM", 2008),
+            UsState::NewYork => ("New York", "NY", 2001),
+            UsState::NorthCarolina => ("North Carolina", "NC", 2001),
+            UsState::NorthDakota => ("North Dakota", "ND", 2006),
+            UsState::Ohio => ("Ohio", "OH", 2002),
+            UsState::Oklahoma => ("Oklahoma", "OK", 2008),
+            UsState::Oregon => ("Oregon", "OR", 2005),
+            UsState::Pennsylvania => ("Pennsylvania", "PA", 1999),
+            UsState::RhodeIsland => ("Rhode Island", "RI", 2001),
+            UsState::SouthCarolina => ("South Carolina", "SC", 2000),
+            UsState::SouthDakota => ("South Dakota", "SD", 2006),
+            UsState::Tennessee => ("Tennessee", "TN", 2002),
+            UsState::Texas => ("Texas", "TX", 2004),
+            UsState::Utah => ("Utah", "UT", 2007),
+            UsState::Vermont => ("Vermont", "VT", 2001),
+            UsState::Virginia => ("Virginia", "VA", 2000),
+            UsState::Washington => ("Washington", "WA", 2007),
+            UsState::WestVirginia => ("West Virginia", "WV", 2005),
+            UsState::Wisconsin => ("Wisconsin", "WI", 2004),
+            UsState::Wyoming => ("Wyoming", "WY", 2007),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.info().0
+    }
+
+    pub fn abbreviation(&self) -> &'static str {
+        self.info().1
+    }
+
+    pub fn release_year(&self) -> u16 {
+        self.info().2
+    }
+
+    // The five states of a year, or none outside of 1999-2008
+    pub fn released_in(year: u16) -> Vec<UsState> {
+        UsState::ALL.iter().copied().filter(|state| state.release_year() == year).collect()
+    }
+}
+
+impl fmt::Display for UsState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// The name or the abbreviation in any case: "new york", "New York", "NY" and "ny" all work
+impl FromStr for UsState {
+    type Err = ParseStateError;
+
+    fn from_str(s: &str) -> Result<UsState, ParseStateError> {
+        let s = s.trim();
+        // "new   york" is still New York
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let name = words.join(" ");
+        UsState::ALL
+            .iter()
+            .find(|state| state.name().eq_ignore_ascii_case(&name) || state.abbreviation().eq_ignore_ascii_case(&name))
+            .copied()
+            .ok_or_else(|| ParseStateError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn parses_names_and_abbreviations() {
+        assert_eq!("Alaska".parse(), Ok(UsState::Alaska));
+        assert_eq!(" alabama ".parse(), Ok(UsState::Alabama));
+        assert_eq!("new  YORK".parse(), Ok(UsState::NewYork));
+        assert_eq!("wv".parse(), Ok(UsState::WestVirginia));
+        assert_eq!("Puerto Rico".parse::<UsState>(), Err(ParseStateError(String::from("Puerto Rico"))));
+        for state in UsState::ALL.iter() {
+            assert_eq!(state.to_string().parse(), Ok(*state));
+            assert_eq!(state.abbreviation().parse(), Ok(*state));
+        }
+    }
+
+    #[test]
+    fn every_state_once() {
+        let names: HashSet<&str> = UsState::ALL.iter().map(|state| state.name()).collect();
+        let abbreviations: HashSet<&str> = UsState::ALL.iter().map(|state| state.abbreviation()).collect();
+        assert_eq!(names.len(), 50);
+        assert_eq!(abbreviations.len(), 50);
+        // ALL is in the order of the enum, so sorted
+        assert!(UsState::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn five_quarters_a_year() {
+        for year in FIRST_RELEASE_YEAR..=LAST_RELEASE_YEAR {
+            assert_eq!(UsState::released_in(year).len(), 5, "{}", year);
+        }
+        assert!(UsState::released_in(2009).is_empty());
+        assert_eq!(
+            UsState::released_in(1999),
+            [UsState::Connecticut, UsState::Delaware, UsState::Georgia, UsState::NewJersey, UsState::Pennsylvania]
+        );
+        assert_eq!(UsState::Alaska.release_year(), 2008);
+    }
+}